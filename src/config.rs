@@ -1,10 +1,28 @@
+use reqwest::Url;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::Deserialize;
-use std::path::Path;
+use std::{fmt::Display, path::Path};
 
-const CONFIG_FILE_PATH: &str = "data/config.toml";
+use crate::{
+    db::storage::StorageBackend,
+    errors::config::{ConfigError, ConfigFieldError},
+    modules::{stats_check::pnl::CostBasisMethod, watch::alerts::WatchEntry},
+    polymarket::api::{
+        clob::typedefs::PriceProtection,
+        urls::{
+            ApiUrls, CLOB_API_URL, CLOB_WS_URL, DATA_API_URL, GAMMA_API_URL, LB_API_URL,
+            RELAYER_API_URL,
+        },
+    },
+    utils::notifier::{NotificationKind, NotifierConfig},
+};
+
+pub const CONFIG_FILE_PATH: &str = "data/config.toml";
+const PROFILES_KEY: &str = "profiles";
 
 #[derive(Deserialize, Clone, Debug)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
 pub struct Config {
     pub registration_sleep_range: [u64; 2],
     pub user_name_length_range: [usize; 2],
@@ -22,17 +40,551 @@ pub struct Config {
     pub batch_delay_range: [u64; 2],
     pub cycle_count: u64,
     pub withdraw_delay_range: [u64; 2],
+    pub redeem_delay_range: [u64; 2],
+    pub limit_order_delay_range: [u64; 2],
+    #[serde(default = "default_max_slippage_bps")]
+    pub max_slippage_bps: u32,
+    #[serde(default = "default_max_buy_price")]
+    pub max_buy_price: Decimal,
+    #[serde(default = "default_min_sell_price")]
+    pub min_sell_price: Decimal,
+    #[serde(default = "default_watch_interval")]
+    pub watch_interval: u64,
+    #[serde(default = "default_watch_volume_window")]
+    pub watch_volume_window: usize,
+    #[serde(default)]
+    pub watchlist: Vec<WatchEntry>,
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+    #[serde(default = "default_notify_on")]
+    pub notify_on: Vec<NotificationKind>,
+    #[serde(default = "default_low_balance_threshold")]
+    pub low_balance_threshold: Decimal,
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default = "default_gamma_api_url")]
+    pub gamma_api_url: String,
+    #[serde(default = "default_clob_api_url")]
+    pub clob_api_url: String,
+    #[serde(default = "default_data_api_url")]
+    pub data_api_url: String,
+    #[serde(default = "default_lb_api_url")]
+    pub lb_api_url: String,
+    #[serde(default = "default_relayer_api_url")]
+    pub relayer_api_url: String,
+    #[serde(default = "default_clob_ws_url")]
+    pub clob_ws_url: String,
+}
+
+fn default_max_slippage_bps() -> u32 {
+    500
+}
+
+fn default_max_buy_price() -> Decimal {
+    dec!(0.99)
+}
+
+fn default_min_sell_price() -> Decimal {
+    dec!(0.01)
+}
+
+fn default_watch_interval() -> u64 {
+    30
+}
+
+fn default_watch_volume_window() -> usize {
+    10
+}
+
+fn default_notify_on() -> Vec<NotificationKind> {
+    NotificationKind::ALL.to_vec()
+}
+
+fn default_low_balance_threshold() -> Decimal {
+    dec!(1)
+}
+
+fn default_gamma_api_url() -> String {
+    GAMMA_API_URL.to_string()
+}
+
+fn default_clob_api_url() -> String {
+    CLOB_API_URL.to_string()
+}
+
+fn default_data_api_url() -> String {
+    DATA_API_URL.to_string()
+}
+
+fn default_lb_api_url() -> String {
+    LB_API_URL.to_string()
+}
+
+fn default_relayer_api_url() -> String {
+    RELAYER_API_URL.to_string()
+}
+
+fn default_clob_ws_url() -> String {
+    CLOB_WS_URL.to_string()
 }
 
 impl Config {
-    async fn read_from_file(path: impl AsRef<Path>) -> eyre::Result<Self> {
-        let cfg_str = tokio::fs::read_to_string(path).await?;
-        Ok(toml::from_str(&cfg_str)?)
+    pub async fn read_from_file(
+        path: impl AsRef<Path>,
+        profile: Option<&str>,
+        overrides: &[(String, toml::Value)],
+    ) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let cfg_str =
+            tokio::fs::read_to_string(path)
+                .await
+                .map_err(|source| ConfigError::Read {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+
+        Self::from_toml_str(&cfg_str, profile, overrides)
+    }
+
+    // Profile keys are layered over the base file, overrides use the same keys and are applied last.
+    // Unknown keys are rejected everywhere, including in the profiles that are not selected
+    pub fn from_toml_str(
+        cfg_str: &str,
+        profile: Option<&str>,
+        overrides: &[(String, toml::Value)],
+    ) -> Result<Self, ConfigError> {
+        let mut table = toml::from_str::<toml::Table>(cfg_str)?;
+        let mut profiles = match table.remove(PROFILES_KEY) {
+            Some(toml::Value::Table(profiles)) => profiles,
+            _ => toml::Table::new(),
+        };
+
+        for (name, profile_table) in &profiles {
+            let toml::Value::Table(profile_table) = profile_table else {
+                continue;
+            };
+
+            let mut layered = table.clone();
+            layered.extend(profile_table.clone());
+            layered
+                .try_into::<Self>()
+                .map_err(|source| ConfigError::Profile {
+                    profile: name.clone(),
+                    source,
+                })?;
+        }
+
+        if let Some(profile) = profile {
+            match profiles.remove(profile) {
+                Some(toml::Value::Table(profile_table)) => table.extend(profile_table),
+                _ => return Err(ConfigError::UnknownProfile(profile.to_string())),
+            }
+        }
+
+        for (key, value) in overrides {
+            table.insert(key.to_uppercase().replace('-', "_"), value.clone());
+        }
+
+        let config: Self = table.try_into()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // `--set` values are parsed as TOML and fall back to plain strings
+    pub fn parse_override_value(value: &str) -> toml::Value {
+        toml::from_str::<toml::Table>(&format!("value = {value}"))
+            .ok()
+            .and_then(|mut parsed| parsed.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()))
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut validator = Validator::default();
+
+        validator.range("REGISTRATION_SLEEP_RANGE", &self.registration_sleep_range);
+        validator.range("USER_NAME_LENGTH_RANGE", &self.user_name_length_range);
+        validator.check(
+            "USER_NAME_LENGTH_RANGE",
+            self.user_name_length_range[0] > 0,
+            "username length must be at least 1",
+        );
+
+        if self.mobile_proxies {
+            validator.url("SWAP_IP_LINK", &self.swap_ip_link, &["http", "https"]);
+        }
+
+        validator.url("POLYGON_RPC_URL", &self.polygon_rpc_url, &["http", "https"]);
+
+        validator.range("USDC_AMOUNT_DEPOSIT_RANGE", &self.usdc_amount_deposit_range);
+        validator.check(
+            "USDC_AMOUNT_DEPOSIT_RANGE",
+            self.usdc_amount_deposit_range[0] >= 0.0,
+            "amount can't be negative",
+        );
+        validator.range("DEPOSIT_SLEEP_RANGE", &self.deposit_sleep_range);
+
+        validator.fraction(
+            "PRICE_DIFFERENCE_THRESHOLD",
+            self.price_difference_threshold,
+        );
+        validator.fraction("SPREAD_THRESHOLD", self.spread_threshold);
+        validator.check(
+            "MIN_EVENT_VOLUME",
+            self.min_event_volume >= 0.0,
+            "volume can't be negative",
+        );
+
+        validator.range("BET_BALANCE_PERCENTAGE", &self.bet_balance_percentage);
+        validator.check(
+            "BET_BALANCE_PERCENTAGE",
+            self.bet_balance_percentage[1] <= 100,
+            format!("percentage {} is over 100", self.bet_balance_percentage[1]),
+        );
+
+        validator.range("SELL_DELAY_RANGE", &self.sell_delay_range);
+        validator.range("BATCH_DELAY_RANGE", &self.batch_delay_range);
+        validator.check(
+            "CYCLE_COUNT",
+            self.cycle_count > 0,
+            "at least one cycle is required",
+        );
+        validator.check(
+            "MAX_BUY_PRICE",
+            self.max_buy_price > Decimal::ZERO && self.max_buy_price <= Decimal::ONE,
+            format!("{} is not between 0 and 1", self.max_buy_price),
+        );
+        validator.check(
+            "MIN_SELL_PRICE",
+            self.min_sell_price >= Decimal::ZERO && self.min_sell_price < Decimal::ONE,
+            format!("{} is not between 0 and 1", self.min_sell_price),
+        );
+        validator.range("WITHDRAW_DELAY_RANGE", &self.withdraw_delay_range);
+        validator.range("REDEEM_DELAY_RANGE", &self.redeem_delay_range);
+        validator.range("LIMIT_ORDER_DELAY_RANGE", &self.limit_order_delay_range);
+
+        validator.check(
+            "WATCH_INTERVAL",
+            self.watch_interval > 0,
+            "interval must be at least 1 second",
+        );
+        validator.check(
+            "WATCH_VOLUME_WINDOW",
+            self.watch_volume_window > 0,
+            "window must hold at least 1 interval",
+        );
+        for (i, entry) in self.watchlist.iter().enumerate() {
+            validator.watch_entry(i, entry);
+        }
+
+        for notifier in &self.notifiers {
+            match notifier {
+                NotifierConfig::Webhook(webhook) => {
+                    validator.url("NOTIFIERS", &webhook.url, &["http", "https"])
+                }
+                NotifierConfig::Bot(bot) => {
+                    validator.url("NOTIFIERS", &bot.url, &["http", "https"])
+                }
+                NotifierConfig::Smtp(smtp) => validator.check(
+                    "NOTIFIERS",
+                    !smtp.to.is_empty(),
+                    format!("SMTP sink on {} has no recipients", smtp.host),
+                ),
+            }
+        }
+        validator.check(
+            "LOW_BALANCE_THRESHOLD",
+            self.low_balance_threshold >= Decimal::ZERO,
+            "threshold can't be negative",
+        );
+
+        for (field, url) in [
+            ("GAMMA_API_URL", &self.gamma_api_url),
+            ("CLOB_API_URL", &self.clob_api_url),
+            ("DATA_API_URL", &self.data_api_url),
+            ("LB_API_URL", &self.lb_api_url),
+            ("RELAYER_API_URL", &self.relayer_api_url),
+        ] {
+            validator.url(field, url, &["http", "https"]);
+        }
+        validator.url("CLOB_WS_URL", &self.clob_ws_url, &["ws", "wss"]);
+
+        validator.finish()
     }
 
-    pub async fn read_default() -> Self {
-        Self::read_from_file(CONFIG_FILE_PATH)
-            .await
-            .expect("Default config to be valid")
+    pub fn price_protection(&self) -> PriceProtection {
+        PriceProtection {
+            max_slippage_bps: self.max_slippage_bps,
+            max_buy_price: self.max_buy_price,
+            min_sell_price: self.min_sell_price,
+        }
+    }
+
+    pub fn api_urls(&self) -> ApiUrls {
+        ApiUrls {
+            gamma: self.gamma_api_url.clone(),
+            clob: self.clob_api_url.clone(),
+            data: self.data_api_url.clone(),
+            leaderboard: self.lb_api_url.clone(),
+            relayer: self.relayer_api_url.clone(),
+            clob_ws: self.clob_ws_url.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Validator(Vec<ConfigFieldError>);
+
+impl Validator {
+    fn check(&mut self, field: &'static str, valid: bool, reason: impl Into<String>) {
+        if !valid {
+            self.0.push(ConfigFieldError {
+                field,
+                reason: reason.into(),
+            });
+        }
+    }
+
+    fn range<T: PartialOrd + Display>(&mut self, field: &'static str, range: &[T; 2]) {
+        self.check(
+            field,
+            range[0] <= range[1],
+            format!(
+                "range is inverted, {} is greater than {}",
+                range[0], range[1]
+            ),
+        );
+    }
+
+    fn fraction(&mut self, field: &'static str, value: f64) {
+        self.check(
+            field,
+            (0.0..=1.0).contains(&value),
+            format!("{value} is not between 0 and 1"),
+        );
+    }
+
+    fn url(&mut self, field: &'static str, value: &str, schemes: &[&str]) {
+        match Url::parse(value) {
+            Ok(url) => self.check(
+                field,
+                schemes.contains(&url.scheme()),
+                format!(
+                    "unsupported scheme `{}`, expected one of: {}",
+                    url.scheme(),
+                    schemes.join(", ")
+                ),
+            ),
+            Err(e) => self.check(field, false, format!("invalid url `{value}`: {e}")),
+        }
+    }
+
+    fn watch_entry(&mut self, index: usize, entry: &WatchEntry) {
+        self.check(
+            "WATCHLIST",
+            entry.event_slug.is_some() != entry.token_id.is_some(),
+            format!("entry {index} needs exactly one of EVENT_SLUG and TOKEN_ID"),
+        );
+        self.check(
+            "WATCHLIST",
+            entry.has_thresholds(),
+            format!("entry {index} has no thresholds to alert on"),
+        );
+
+        let prices = [
+            ("PRICE_ABOVE", entry.price_above),
+            ("PRICE_BELOW", entry.price_below),
+            ("MAX_SPREAD", entry.max_spread),
+        ];
+        for (key, value) in prices {
+            if let Some(value) = value {
+                self.check(
+                    "WATCHLIST",
+                    value > Decimal::ZERO && value < Decimal::ONE,
+                    format!("entry {index} {key} {value} is not between 0 and 1"),
+                );
+            }
+        }
+
+        if let Some(multiplier) = entry.volume_spike {
+            self.check(
+                "WATCHLIST",
+                multiplier > Decimal::ONE,
+                format!("entry {index} VOLUME_SPIKE {multiplier} must be greater than 1"),
+            );
+        }
+    }
+
+    fn finish(self) -> Result<(), ConfigError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError::Invalid(self.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT_CONFIG: &str = include_str!("../data/config.toml");
+
+    #[test]
+    fn test_default_config_is_valid() {
+        Config::from_toml_str(DEFAULT_CONFIG, None, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_profile_and_overrides_layering() {
+        let config = Config::from_toml_str(
+            DEFAULT_CONFIG,
+            Some("conservative"),
+            &[("cycle-count".to_string(), Config::parse_override_value("7"))],
+        )
+        .unwrap();
+
+        assert_eq!(config.bet_balance_percentage, [3, 5]);
+        assert_eq!(config.cycle_count, 7);
+
+        assert!(matches!(
+            Config::from_toml_str(DEFAULT_CONFIG, Some("missing"), &[]),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let misspelled_profile = format!("{DEFAULT_CONFIG}\n[profiles.typo]\nCYCLE_COUNTS = 5\n");
+        assert!(matches!(
+            Config::from_toml_str(&misspelled_profile, None, &[]),
+            Err(ConfigError::Profile { profile, .. }) if profile == "typo"
+        ));
+
+        let misspelled_base = DEFAULT_CONFIG.replacen("CYCLE_COUNT", "CYCLE_COUNTS", 1);
+        let error = Config::from_toml_str(&misspelled_base, None, &[]).unwrap_err();
+        assert!(error.to_string().contains("CYCLE_COUNTS"));
+
+        let overrides = [("CYCLE_COUNTS".to_string(), toml::Value::Integer(5))];
+        assert!(matches!(
+            Config::from_toml_str(DEFAULT_CONFIG, None, &overrides),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_price_protection_overrides_are_exact() {
+        let overrides = [("MAX_BUY_PRICE", "0.95"), ("MAX_SLIPPAGE_BPS", "150")]
+            .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+
+        let protection = Config::from_toml_str(DEFAULT_CONFIG, None, &overrides)
+            .unwrap()
+            .price_protection();
+
+        assert_eq!(protection.max_buy_price, dec!(0.95));
+        assert_eq!(protection.min_sell_price, dec!(0.01));
+        assert_eq!(protection.max_slippage_bps, 150);
+    }
+
+    #[test]
+    fn test_watchlist_entries() {
+        let overrides = [(
+            "WATCHLIST",
+            r#"[{ EVENT_SLUG = "fed-decision-in-december", PRICE_ABOVE = 0.7 }, { TOKEN_ID = "123", VOLUME_SPIKE = 3 }]"#,
+        )]
+        .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+
+        let config = Config::from_toml_str(DEFAULT_CONFIG, None, &overrides).unwrap();
+        assert_eq!(config.watchlist.len(), 2);
+        assert_eq!(config.watchlist[0].price_above, Some(dec!(0.7)));
+        assert_eq!(config.watchlist[1].volume_spike, Some(dec!(3)));
+
+        let overrides = [(
+            "WATCHLIST",
+            r#"[{ EVENT_SLUG = "fed-decision-in-december", TOKEN_ID = "123", MAX_SPREAD = 1.5 }, { TOKEN_ID = "123" }]"#,
+        )]
+        .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+
+        let Err(ConfigError::Invalid(errors)) =
+            Config::from_toml_str(DEFAULT_CONFIG, None, &overrides)
+        else {
+            panic!("watchlist should be invalid");
+        };
+
+        let reasons = errors
+            .iter()
+            .map(|error| error.reason.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            [
+                "entry 0 needs exactly one of EVENT_SLUG and TOKEN_ID",
+                "entry 0 MAX_SPREAD 1.5 is not between 0 and 1",
+                "entry 1 has no thresholds to alert on",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notification_settings() {
+        let config = Config::from_toml_str(DEFAULT_CONFIG, None, &[]).unwrap();
+        assert!(config.notifiers.is_empty());
+        assert_eq!(config.notify_on, NotificationKind::ALL);
+
+        let overrides = [
+            ("NOTIFY_ON", r#"["account-failed", "low-balance"]"#),
+            (
+                "NOTIFIERS",
+                r#"[{ KIND = "webhook", URL = "ftp://example.com/hook" }]"#,
+            ),
+        ]
+        .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+
+        let Err(ConfigError::Invalid(errors)) =
+            Config::from_toml_str(DEFAULT_CONFIG, None, &overrides)
+        else {
+            panic!("webhook url should be invalid");
+        };
+        assert_eq!(errors[0].field, "NOTIFIERS");
+
+        let overrides = [("NOTIFY_ON", r#"["account-failed", "low-balance"]"#)]
+            .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+        let config = Config::from_toml_str(DEFAULT_CONFIG, None, &overrides).unwrap();
+        assert_eq!(
+            config.notify_on,
+            [
+                NotificationKind::AccountFailed,
+                NotificationKind::LowBalance
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_reports_every_error() {
+        let overrides = [
+            ("POLYGON_RPC_URL", "not a url"),
+            ("DEPOSIT_SLEEP_RANGE", "[18, 13]"),
+            ("BET_BALANCE_PERCENTAGE", "[50, 150]"),
+        ]
+        .map(|(key, value)| (key.to_string(), Config::parse_override_value(value)));
+
+        let Err(ConfigError::Invalid(errors)) =
+            Config::from_toml_str(DEFAULT_CONFIG, None, &overrides)
+        else {
+            panic!("config should be invalid");
+        };
+
+        let fields = errors.iter().map(|error| error.field).collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                "POLYGON_RPC_URL",
+                "DEPOSIT_SLEEP_RANGE",
+                "BET_BALANCE_PERCENTAGE"
+            ]
+        );
     }
 }
@@ -11,16 +11,21 @@ use crate::{
     polymarket::api::clob::schemas::ClobApiKeyResponseBody, utils::poly::get_proxy_wallet_address,
 };
 
+use super::{
+    crypto::{decrypt_private_key_and_cipher, is_encrypted_field, FieldCipher},
+    order::OrderRecord,
+};
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Account {
-    address: String,
+    pub(super) address: String,
     #[serde(skip)]
-    private_key: String,
-    encrypted_private_key: String,
-    proxy: Option<String>,
-    recipient_address: String,
-    is_registered: bool,
-    funded: bool,
+    pub(super) private_key: String,
+    pub(super) encrypted_private_key: String,
+    pub(super) proxy: Option<String>,
+    pub(super) recipient_address: String,
+    pub(super) is_registered: bool,
+    pub(super) funded: bool,
     pub proxy_address: String,
     pub polymarket_nonce: Option<String>,
     pub polymarket_session: Option<String>,
@@ -39,6 +44,16 @@ pub struct Account {
         deserialize_with = "deserialize_arc_rwlock_option_string"
     )]
     pub passphrase: Arc<RwLock<Option<String>>>,
+    #[serde(default)]
+    pub(super) orders: Vec<OrderRecord>,
+    #[serde(default)]
+    pub(super) tags: Vec<String>,
+    // Set once the account is unlocked, seals the session and API credentials on save
+    #[serde(skip)]
+    pub(super) cipher: Option<FieldCipher>,
+    // Position in storage, accounts added during the run have none and are saved last
+    #[serde(skip)]
+    pub(super) position: Option<usize>,
 }
 
 fn serialize_arc_rwlock_option_string<S>(
@@ -86,6 +101,56 @@ impl Account {
         }
     }
 
+    // Decrypts the private key, session and API credentials in memory. Returns true if any of them
+    // were still stored in plaintext
+    pub fn unlock(&mut self, password: &str) -> eyre::Result<bool> {
+        let (private_key, cipher) =
+            decrypt_private_key_and_cipher(&self.encrypted_private_key, password)?;
+        let mut has_plaintext = false;
+
+        let mut open = |value: &mut Option<String>| -> eyre::Result<()> {
+            if let Some(sealed) = value.as_mut() {
+                match is_encrypted_field(sealed) {
+                    true => *sealed = cipher.decrypt(sealed)?,
+                    false => has_plaintext = true,
+                }
+            }
+            Ok(())
+        };
+
+        open(&mut self.polymarket_nonce)?;
+        open(&mut self.polymarket_session)?;
+        open(&mut self.api_key.write().unwrap())?;
+        open(&mut self.secret.write().unwrap())?;
+        open(&mut self.passphrase.write().unwrap())?;
+
+        self.private_key = private_key;
+        self.cipher = Some(cipher);
+
+        Ok(has_plaintext)
+    }
+
+    // Copy of the account as it is written to storage, with the session and API credentials encrypted
+    pub(super) fn sealed(&self) -> eyre::Result<Self> {
+        let cipher = self
+            .cipher
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("Account {} is not unlocked", self.address))?;
+        let seal = |value: &Option<String>| value.as_deref().map(|value| cipher.encrypt(value));
+        let seal_shared = |value: &Arc<RwLock<Option<String>>>| {
+            Arc::new(RwLock::new(seal(&value.read().unwrap())))
+        };
+
+        Ok(Self {
+            polymarket_nonce: seal(&self.polymarket_nonce),
+            polymarket_session: seal(&self.polymarket_session),
+            api_key: seal_shared(&self.api_key),
+            secret: seal_shared(&self.secret),
+            passphrase: seal_shared(&self.passphrase),
+            ..self.clone()
+        })
+    }
+
     pub fn get_is_registered(&self) -> bool {
         self.is_registered
     }
@@ -104,14 +169,14 @@ impl Account {
         Arc::new(PrivateKeySigner::from_str(&self.private_key).unwrap())
     }
 
-    pub fn set_private_key(&mut self, private_key: &str) {
-        self.private_key = private_key.to_string()
-    }
-
     pub fn get_encrypted_private_key(&self) -> &str {
         &self.encrypted_private_key
     }
 
+    pub fn set_encrypted_private_key(&mut self, encrypted_private_key: &str) {
+        self.encrypted_private_key = encrypted_private_key.to_string()
+    }
+
     pub fn set_polymarket_session(&mut self, polymarket_session: &str) {
         self.polymarket_session = Some(polymarket_session.to_string())
     }
@@ -140,6 +205,38 @@ impl Account {
         Address::from_str(&self.recipient_address).unwrap()
     }
 
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|entry| entry == tag)
+    }
+
+    // Returns false if the account already has the tag
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        if self.has_tag(tag) {
+            return false;
+        }
+
+        self.tags.push(tag.to_string());
+        true
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|entry| entry != tag);
+        self.tags.len() != len
+    }
+
+    pub fn get_orders(&self) -> &[OrderRecord] {
+        &self.orders
+    }
+
+    pub fn add_order(&mut self, order: OrderRecord) {
+        self.orders.push(order)
+    }
+
     pub fn update_credentials(&self, response: ClobApiKeyResponseBody) {
         *self.api_key.write().unwrap() = Some(response.api_key);
         *self.secret.write().unwrap() = Some(response.secret);
@@ -170,3 +267,43 @@ pub struct ApiCreds {
     pub api_passphrase: String,
     pub api_secret: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::crypto::{encrypt_private_key_with_params, KdfParams};
+
+    const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[test]
+    fn test_seal_and_unlock() {
+        let params = KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let encrypted_private_key = encrypt_private_key_with_params(PRIVATE_KEY, "password", params);
+
+        // Plaintext secrets from an older database
+        let mut account = Account::new(PRIVATE_KEY, &encrypted_private_key, None, None);
+        account.set_polymarket_session("session");
+        *account.secret.write().unwrap() = Some("secret".to_string());
+
+        assert!(account.sealed().is_err());
+        assert!(account.unlock("password").unwrap());
+
+        let sealed = account.sealed().unwrap();
+        let stored = serde_json::to_string(&sealed).unwrap();
+        assert!(!stored.contains(":\"session\""));
+        assert!(!stored.contains(":\"secret\""));
+        assert_eq!(account.polymarket_session.as_deref(), Some("session"));
+
+        let mut loaded: Account = serde_json::from_str(&stored).unwrap();
+        assert!(loaded.unlock("wrong_password").is_err());
+        assert!(!loaded.unlock("password").unwrap());
+        assert_eq!(loaded.get_private_key(), PRIVATE_KEY);
+        assert_eq!(loaded.polymarket_session.as_deref(), Some("session"));
+        assert_eq!(loaded.secret.read().unwrap().as_deref(), Some("secret"));
+        assert_eq!(loaded.polymarket_nonce, None);
+    }
+}
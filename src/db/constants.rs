@@ -1,7 +1,12 @@
 pub const PROXIES_FILE_PATH: &str = "data/proxies.txt";
 pub const ENCRYPTED_PRIVATE_KEYS_FILE_PATH: &str = "data/encrypted_private_keys.txt";
 pub const DB_FILE_PATH: &str = "data/db.json";
+pub const SQLITE_DB_FILE_PATH: &str = "data/db.sqlite";
+pub const DB_LOCK_FILE_PATH: &str = "data/db.lock";
+pub const DB_BACKUPS_DIR_PATH: &str = "data/backups";
+pub const FILLS_FILE_PATH: &str = "data/fills.json";
 pub const RECIPIENTS_FILE_PATH: &str = "data/recipients.txt";
 pub const ADDRESS_FILE_PATH: &str = "data/addresses.txt";
 pub const PROXY_ADDRESS_FILE_PATH: &str = "data/proxy_addresses.txt";
-pub const DEFAULT_PASSWORD: &str = "naixi666.";
\ No newline at end of file
+pub const DEFAULT_PASSWORD: &str = "naixi666.";
+pub const KEYSTORES_DIR_PATH: &str = "data/keystores";
@@ -1,7 +1,101 @@
 use aes_gcm::{aead::{Aead, KeyInit}, AeadCore, Aes256Gcm, Key as AesKey};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
 use sha2::{Digest, Sha256};
 
+use super::constants::DEFAULT_PASSWORD;
 
+// v1 layout: `v1$argon2id$m=<KiB>,t=<iterations>,p=<lanes>$<salt hex>$<nonce + ciphertext hex>`
+const V1_PREFIX: &str = "v1";
+const V1_KDF: &str = "argon2id";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+// Account secrets sealed with a `FieldCipher`: `enc$<nonce + ciphertext hex>`
+const FIELD_PREFIX: &str = "enc";
+// Upper bounds for parameters read from stored blobs, past them a tampered file could stall the bot
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+// OWASP recommended Argon2id settings
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn derive_key(&self, password: &str, salt: &[u8]) -> eyre::Result<[u8; 32]> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| eyre::eyre!("Invalid KDF parameters: {e}"))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), salt, &mut key)
+            .map_err(|e| eyre::eyre!("Key derivation failed: {e}"))?;
+
+        Ok(key)
+    }
+
+    fn parse(params: &str) -> eyre::Result<Self> {
+        let mut kdf_params = Self::default();
+
+        for param in params.split(',') {
+            let (name, value) = param
+                .split_once('=')
+                .ok_or_else(|| eyre::eyre!("Invalid KDF parameter: {param}"))?;
+            let value = value.parse::<u32>()?;
+
+            match name {
+                "m" => kdf_params.memory_kib = value,
+                "t" => kdf_params.iterations = value,
+                "p" => kdf_params.parallelism = value,
+                _ => eyre::bail!("Unknown KDF parameter: {name}"),
+            }
+        }
+
+        kdf_params.validate()?;
+
+        Ok(kdf_params)
+    }
+
+    fn validate(&self) -> eyre::Result<()> {
+        if !(Params::MIN_P_COST..=MAX_PARALLELISM).contains(&self.parallelism) {
+            eyre::bail!("KDF parallelism must be between {} and {MAX_PARALLELISM}, got {}", Params::MIN_P_COST, self.parallelism);
+        }
+
+        if !(Params::MIN_T_COST..=MAX_ITERATIONS).contains(&self.iterations) {
+            eyre::bail!("KDF iterations must be between {} and {MAX_ITERATIONS}, got {}", Params::MIN_T_COST, self.iterations);
+        }
+
+        // Argon2 needs at least 8 KiB per lane
+        let min_memory_kib = Params::MIN_M_COST.max(8 * self.parallelism);
+        if !(min_memory_kib..=MAX_MEMORY_KIB).contains(&self.memory_kib) {
+            eyre::bail!("KDF memory cost must be between {min_memory_kib} and {MAX_MEMORY_KIB} KiB, got {}", self.memory_kib);
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for KdfParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "m={},t={},p={}", self.memory_kib, self.iterations, self.parallelism)
+    }
+}
+
+// Legacy v0 key: a single unsalted SHA-256 of the password, only kept to read old blobs
 pub fn derive_key_from_password(password: &str) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(password.as_bytes());
@@ -9,13 +103,99 @@ pub fn derive_key_from_password(password: &str) -> [u8; 32] {
     result.into()
 }
 
+pub fn is_legacy_format(encrypted: &str) -> bool {
+    !encrypted.starts_with(&format!("{V1_PREFIX}$"))
+}
+
+// New blobs need a password of the user's own, the public default only reads legacy keys
+pub fn encryption_password(password: Option<&str>) -> eyre::Result<&str> {
+    match password {
+        Some(password) if !password.is_empty() && password != DEFAULT_PASSWORD => Ok(password),
+        _ => eyre::bail!("Set a password to encrypt private keys with, the built-in default password is public"),
+    }
+}
+
 pub fn encrypt_private_key(private_key: &str, password: &str) -> String {
-    let encryption_key = derive_key_from_password(password);
-    let cipher = Aes256Gcm::new(AesKey::<Aes256Gcm>::from_slice(&encryption_key));
+    encrypt_private_key_with_params(private_key, password, KdfParams::default())
+}
+
+pub fn encrypt_private_key_with_params(private_key: &str, password: &str, params: KdfParams) -> String {
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let encryption_key = params
+        .derive_key(password, &salt)
+        .expect("KDF parameters to be valid");
+
+    format!("{V1_PREFIX}${V1_KDF}${params}${}${}", hex::encode(salt), encrypt_with_key(private_key, &encryption_key))
+}
+
+pub fn decrypt_private_key(encrypted: &str, password: &str) -> eyre::Result<String> {
+    Ok(decrypt_private_key_and_cipher(encrypted, password)?.0)
+}
+
+// Decrypts the private key and keeps its password-derived key for the account's other secrets
+pub fn decrypt_private_key_and_cipher(encrypted: &str, password: &str) -> eyre::Result<(String, FieldCipher)> {
+    let (encryption_key, payload) = derive_key_for(encrypted, password)?;
+    let private_key = decrypt_with_key(payload, &encryption_key)?;
+
+    Ok((private_key, FieldCipher(encryption_key)))
+}
+
+fn derive_key_for<'a>(encrypted: &'a str, password: &str) -> eyre::Result<([u8; 32], &'a str)> {
+    if is_legacy_format(encrypted) {
+        return Ok((derive_key_from_password(password), encrypted));
+    }
+
+    let parts = encrypted.split('$').collect::<Vec<_>>();
+    let [_, kdf, params, salt, payload] = parts.as_slice() else {
+        return Err(eyre::eyre!("Invalid encrypted data"));
+    };
+
+    if *kdf != V1_KDF {
+        return Err(eyre::eyre!("Unsupported KDF: {kdf}"));
+    }
+
+    let salt = hex::decode(salt)
+        .map_err(|_| eyre::eyre!("Invalid hex data"))?;
+
+    Ok((KdfParams::parse(params)?.derive_key(password, &salt)?, payload))
+}
+
+// Seals the API credentials and session of an account with the key of its encrypted private key
+#[derive(Clone)]
+pub struct FieldCipher([u8; 32]);
+
+impl std::fmt::Debug for FieldCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FieldCipher(..)")
+    }
+}
+
+impl FieldCipher {
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        format!("{FIELD_PREFIX}${}", encrypt_with_key(plaintext, &self.0))
+    }
+
+    pub fn decrypt(&self, value: &str) -> eyre::Result<String> {
+        let payload = value
+            .strip_prefix(&format!("{FIELD_PREFIX}$"))
+            .ok_or_else(|| eyre::eyre!("Invalid encrypted data"))?;
+
+        decrypt_with_key(payload, &self.0)
+    }
+}
+
+pub fn is_encrypted_field(value: &str) -> bool {
+    value.starts_with(&format!("{FIELD_PREFIX}$"))
+}
+
+fn encrypt_with_key(plaintext: &str, encryption_key: &[u8; 32]) -> String {
+    let cipher = Aes256Gcm::new(AesKey::<Aes256Gcm>::from_slice(encryption_key));
     let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
 
     let ciphertext = cipher
-        .encrypt(&nonce, private_key.as_bytes())
+        .encrypt(&nonce, plaintext.as_bytes())
         .expect("encryption failure!");
 
     let mut combined = nonce.to_vec();
@@ -24,17 +204,16 @@ pub fn encrypt_private_key(private_key: &str, password: &str) -> String {
     hex::encode(combined)
 }
 
-pub fn decrypt_private_key(encrypted: &str, password: &str) -> eyre::Result<String> {
+fn decrypt_with_key(encrypted: &str, encryption_key: &[u8; 32]) -> eyre::Result<String> {
     let encrypted_data = hex::decode(encrypted)
         .map_err(|_| eyre::eyre!("Invalid hex data"))?;
 
-    if encrypted_data.len() < 12 {
+    if encrypted_data.len() < NONCE_LENGTH {
         return Err(eyre::eyre!("Invalid encrypted data"));
     }
 
-    let encryption_key = derive_key_from_password(password);
-    let (nonce, ciphertext) = encrypted_data.split_at(12);
-    let cipher = Aes256Gcm::new(AesKey::<Aes256Gcm>::from_slice(&encryption_key));
+    let (nonce, ciphertext) = encrypted_data.split_at(NONCE_LENGTH);
+    let cipher = Aes256Gcm::new(AesKey::<Aes256Gcm>::from_slice(encryption_key));
 
     let plaintext = cipher
         .decrypt(nonce.into(), ciphertext)
@@ -90,4 +269,81 @@ mod tests {
         let key3 = derive_key_from_password(password2);
         assert_ne!(key1, key3);
     }
-}
\ No newline at end of file
+
+    fn legacy_encrypt(private_key: &str, password: &str) -> String {
+        let encryption_key = derive_key_from_password(password);
+        let cipher = Aes256Gcm::new(AesKey::<Aes256Gcm>::from_slice(&encryption_key));
+        let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
+
+        let mut combined = nonce.to_vec();
+        combined.extend(cipher.encrypt(&nonce, private_key.as_bytes()).unwrap());
+
+        hex::encode(combined)
+    }
+
+    #[test]
+    fn test_versioned_format() {
+        let private_key = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+        let encrypted = encrypt_private_key_with_params(private_key, "password", params);
+        assert!(encrypted.starts_with("v1$argon2id$m=64,t=1,p=1$"));
+        assert!(!is_legacy_format(&encrypted));
+        assert_eq!(decrypt_private_key(&encrypted, "password").unwrap(), private_key);
+        assert!(decrypt_private_key(&encrypted, "wrong_password").is_err());
+
+        // 相同明文每次使用不同的盐
+        assert_ne!(encrypted, encrypt_private_key_with_params(private_key, "password", params));
+
+        // 旧版 v0 数据仍可解密
+        let legacy = legacy_encrypt(private_key, "password");
+        assert!(is_legacy_format(&legacy));
+        assert_eq!(decrypt_private_key(&legacy, "password").unwrap(), private_key);
+    }
+
+    #[test]
+    fn test_encryption_password() {
+        assert_eq!(encryption_password(Some("password")).unwrap(), "password");
+        assert!(encryption_password(Some(DEFAULT_PASSWORD)).is_err());
+        assert!(encryption_password(Some("")).is_err());
+        assert!(encryption_password(None).is_err());
+    }
+
+    #[test]
+    fn test_kdf_params_bounds() {
+        assert_eq!(KdfParams::parse("m=64,t=1,p=1").unwrap(), KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 });
+        assert_eq!(KdfParams::parse(&KdfParams::default().to_string()).unwrap(), KdfParams::default());
+
+        for params in ["m=4,t=1,p=1", "m=64,t=1,p=16", "m=4294967295,t=1,p=1", "m=64,t=0,p=1", "m=64,t=100000,p=1", "m=64,t=1,p=0", "m=1048576,t=1,p=1024"] {
+            assert!(KdfParams::parse(params).is_err(), "{params} should be rejected");
+        }
+
+        let encrypted = format!("v1$argon2id$m=4294967295,t=1,p=1${}${}", hex::encode([0u8; SALT_LENGTH]), hex::encode([0u8; 64]));
+        let error = decrypt_private_key(&encrypted, "password").unwrap_err();
+        assert!(error.to_string().contains("KDF memory cost"));
+    }
+
+    #[test]
+    fn test_field_cipher() {
+        let private_key = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+        let encrypted = encrypt_private_key_with_params(private_key, "password", params);
+
+        let (decrypted, cipher) = decrypt_private_key_and_cipher(&encrypted, "password").unwrap();
+        assert_eq!(decrypted, private_key);
+
+        let sealed = cipher.encrypt("api-secret");
+        assert!(is_encrypted_field(&sealed));
+        assert!(!sealed.contains(&hex::encode("api-secret")));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "api-secret");
+        assert!(cipher.decrypt("api-secret").is_err());
+
+        // 其他密码派生的密钥无法解密
+        let (_, other_cipher) = decrypt_private_key_and_cipher(
+            &encrypt_private_key_with_params(private_key, "other", params),
+            "other",
+        )
+        .unwrap();
+        assert!(other_cipher.decrypt(&sealed).is_err());
+    }
+}
@@ -1,67 +1,172 @@
-use std::fs::File;
+use std::{fs::File, path::Path, sync::OnceLock};
+
+use chrono::Local;
 
 use super::{
     account::Account,
-    constants::{DB_FILE_PATH, ENCRYPTED_PRIVATE_KEYS_FILE_PATH, PROXIES_FILE_PATH, RECIPIENTS_FILE_PATH},
+    constants::{DB_BACKUPS_DIR_PATH, DB_LOCK_FILE_PATH, PROXIES_FILE_PATH, RECIPIENTS_FILE_PATH},
+    crypto::encryption_password,
+    keystore::read_account_lines,
+    selection::Selection,
+    source::AccountSource,
+    storage::storage,
 };
 use crate::db::constants::DEFAULT_PASSWORD;
-use crate::db::crypto::decrypt_private_key;
-use crate::utils::files::read_file_lines;
+use crate::utils::dry_run;
 use rand::{
     seq::{IteratorRandom, SliceRandom},
     thread_rng,
 };
 use serde::{Deserialize, Serialize};
 
+const MAX_DB_BACKUPS: usize = 10;
+
+// Held for the lifetime of the process once the database is opened
+static DB_LOCK: OnceLock<File> = OnceLock::new();
+
+// Accounts left out by `select` are kept in the second field so `update` still writes them back,
+// both are saved in their storage order whatever order the modules process them in
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Database(pub Vec<Account>);
+pub struct Database(pub Vec<Account>, #[serde(skip)] Vec<Account>);
+
+// Locks the database, the key file and the fills ledger against other running instances. The first call per process
+// also backs up the current file
+pub(crate) fn lock_database() -> eyre::Result<()> {
+    if DB_LOCK.get().is_some() {
+        return Ok(());
+    }
+
+    let lock_file = File::create(DB_LOCK_FILE_PATH)?;
+    lock_file.try_lock().map_err(|_| {
+        eyre::eyre!("Database is locked by another running instance ({})", DB_LOCK_FILE_PATH)
+    })?;
+
+    if DB_LOCK.set(lock_file).is_ok() {
+        backup_database()?;
+    }
+
+    Ok(())
+}
+
+fn backup_database() -> eyre::Result<()> {
+    let db_file_path = storage().file_path();
+
+    if !db_file_path.exists() {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(DB_BACKUPS_DIR_PATH)?;
+
+    let extension = db_file_path.extension().unwrap_or_default().to_string_lossy();
+    let backup_path = Path::new(DB_BACKUPS_DIR_PATH).join(format!("db-{}.{extension}", Local::now().format("%Y%m%d-%H%M%S")));
+    std::fs::copy(db_file_path, &backup_path)?;
+
+    let mut backups = std::fs::read_dir(DB_BACKUPS_DIR_PATH)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("db-")))
+        .collect::<Vec<_>>();
+    backups.sort();
+
+    for stale_backup in backups.iter().rev().skip(MAX_DB_BACKUPS) {
+        std::fs::remove_file(stale_backup)?;
+    }
+
+    Ok(())
+}
+
+// The key file is paired line by line, other sources decide the account count and extra lines are left unused
+fn accounts_count(source: &AccountSource, keys_count: usize, proxies: &[String], recipients: &[String]) -> usize {
+    if let AccountSource::EncryptedKeys = source {
+        return keys_count.max(proxies.len()).max(recipients.len());
+    }
+
+    for (file_path, lines) in [(PROXIES_FILE_PATH, proxies), (RECIPIENTS_FILE_PATH, recipients)] {
+        if lines.len() > keys_count {
+            tracing::warn!("{file_path} has {} lines for {keys_count} accounts, the extra lines are ignored", lines.len());
+        }
+    }
+
+    keys_count
+}
 
 impl Database {
-    async fn read_from_file(file_path: &str, password: Option<&str>) -> eyre::Result<Self> {
+    pub async fn read(password: Option<&str>) -> eyre::Result<Self> {
         let password = password.unwrap_or(DEFAULT_PASSWORD);
-        let contents = tokio::fs::read_to_string(file_path).await?;
-        let mut db = serde_json::from_str::<Self>(&contents)?;
-        for account in &mut db.0 {
-            let private_key = decrypt_private_key(&account.get_encrypted_private_key(), password)?;
-            account.set_private_key(&private_key);
+        lock_database()?;
+        let mut db = Self(storage().load_accounts()?, vec![]);
+        let mut has_plaintext = false;
+        for (position, account) in db.0.iter_mut().enumerate() {
+            account.position = Some(position);
+            has_plaintext |= account.unlock(password)?;
         }
+
+        // Databases written before credentials were encrypted are sealed on first read
+        if has_plaintext {
+            db.update()?;
+            tracing::info!("Encrypted plaintext sessions and API credentials in {}", storage().file_path().display());
+        }
+
         Ok(db)
     }
 
-    #[allow(unused)]
-    pub async fn read(password: Option<&str>) -> eyre::Result<Self> {
-        Self::read_from_file(DB_FILE_PATH, password).await
+    pub async fn new(password: Option<&str>) -> eyre::Result<Self> {
+        Self::new_from_source(&AccountSource::EncryptedKeys, password).await
     }
 
-    pub async fn new(password: Option<&str>) -> eyre::Result<Self> {
-        let password = password.unwrap_or(DEFAULT_PASSWORD);
-        let encrypted_private_keys = read_file_lines(ENCRYPTED_PRIVATE_KEYS_FILE_PATH).await.unwrap();
-        let proxies = read_file_lines(PROXIES_FILE_PATH).await.unwrap();
-        let recipients = read_file_lines(RECIPIENTS_FILE_PATH).await.unwrap();
-        let mut data = Vec::with_capacity(encrypted_private_keys.len());
-
-        let max_len = encrypted_private_keys
-            .len()
-            .max(proxies.len())
-            .max(recipients.len());
-
-        for i in 0..max_len {
-            let encrypted_private_key = encrypted_private_keys.get(i)
+    pub async fn new_from_source(source: &AccountSource, password: Option<&str>) -> eyre::Result<Self> {
+        // Keys of other sources are encrypted anew, the key file is only read
+        let password = match source {
+            AccountSource::EncryptedKeys => password.unwrap_or(DEFAULT_PASSWORD),
+            _ => encryption_password(password)?,
+        };
+        lock_database()?;
+        let source_keys = source.load(password).await?;
+        let proxies = read_account_lines(PROXIES_FILE_PATH).await?;
+        let recipients = read_account_lines(RECIPIENTS_FILE_PATH).await?;
+        let mut data = Vec::with_capacity(source_keys.len());
+
+        let accounts_count = accounts_count(source, source_keys.len(), &proxies, &recipients);
+
+        for i in 0..accounts_count {
+            let source_key = source_keys.get(i)
                 .ok_or_else(|| eyre::eyre!("Missing private key at position {}", i))?;
-            // println!("Decrypting private key {} {}", encrypted_private_key, password);
-            let private_key = decrypt_private_key(encrypted_private_key, password)
-                .map_err(|e| eyre::eyre!("Failed to decrypt private key at position {}: {}", i, e))?;
 
             let proxy = proxies.get(i).cloned();
             let recipient = recipients.get(i).cloned();
-            let account = Account::new(&private_key, &encrypted_private_key, proxy, recipient);
+            let mut account = Account::new(&source_key.private_key, &source_key.encrypted_private_key, proxy, recipient);
+            account.unlock(password)?;
+            account.position = Some(i);
             data.push(account);
         }
 
-        let db_file = File::create(DB_FILE_PATH)?;
-        serde_json::to_writer_pretty(db_file, &data)?;
+        let db = Self(data, vec![]);
+        db.update()?;
+
+        Ok(db)
+    }
+
+    // Narrows the database to the accounts matching the selection, indexes are database positions
+    pub async fn select(mut self, selection: &Selection) -> eyre::Result<Self> {
+        if selection.is_empty() {
+            return Ok(self);
+        }
+
+        let total = self.0.len() + self.1.len();
+        let mut selected = vec![];
+
+        for (index, account) in std::mem::take(&mut self.0).into_iter().enumerate() {
+            let index = account.position.unwrap_or(index);
+
+            match selection.matches(index, &account).await? {
+                true => selected.push(account),
+                false => self.1.push(account),
+            }
+        }
+
+        self.0 = selected;
+        tracing::info!("Selected {} of {total} accounts", self.0.len());
 
-        Ok(Self(data))
+        Ok(self)
     }
 
     pub fn get_random_account_with_filter<F>(&mut self, filter: F) -> Option<&mut Account>
@@ -76,13 +181,67 @@ impl Database {
             .choose(&mut rng)
     }
 
-    pub fn update(&self) {
-        let file = File::create(DB_FILE_PATH).expect("Default database must be vaild");
-        let _ = serde_json::to_writer_pretty(file, &self);
+    pub fn update(&self) -> eyre::Result<()> {
+        if dry_run::is_enabled() {
+            return Ok(());
+        }
+
+        let sealed = self.stored_order().into_iter().map(Account::sealed).collect::<eyre::Result<Vec<_>>>()?;
+        storage().save_accounts(&sealed)
+    }
+
+    fn stored_order(&self) -> Vec<&Account> {
+        let mut accounts = self.0.iter().chain(self.1.iter()).collect::<Vec<_>>();
+        accounts.sort_by_key(|account| account.position.unwrap_or(usize::MAX));
+        accounts
     }
 
+    // Only the processing order changes, storage keeps the original one
     pub fn shuffle(&mut self) {
         self.0.shuffle(&mut thread_rng());
-        self.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::random_account;
+
+    #[tokio::test]
+    async fn test_selection_keeps_stored_order() {
+        let accounts = (0..4)
+            .map(|position| Account {
+                position: Some(position),
+                ..random_account()
+            })
+            .collect::<Vec<_>>();
+        let addresses = accounts.iter().map(|account| account.address.clone()).collect::<Vec<_>>();
+
+        let mut db = Database(accounts, vec![]).select(&"index:2-".parse().unwrap()).await.unwrap();
+        db.shuffle();
+        db.0.push(random_account());
+
+        let stored = db.stored_order().into_iter().map(|account| account.address.clone()).collect::<Vec<_>>();
+        assert_eq!(stored[..4], addresses);
+        assert_eq!(stored[4], db.0[2].address);
+
+        // Indexes stay database positions on a narrowed database
+        let db = db.select(&"index:3".parse().unwrap()).await.unwrap();
+        assert_eq!(db.0[0].address, addresses[3]);
+    }
+
+    #[test]
+    fn test_derived_accounts_ignore_extra_proxies() {
+        let proxies = vec!["proxy-0".to_string(), "proxy-1".to_string()];
+        let mnemonic = AccountSource::Mnemonic {
+            phrase: String::new(),
+            derivation_path: String::new(),
+            start: 0,
+            count: 1,
+        };
+
+        assert_eq!(accounts_count(&mnemonic, 1, &proxies, &[]), 1);
+        // A key missing from the key file is still an error
+        assert_eq!(accounts_count(&AccountSource::EncryptedKeys, 1, &proxies, &[]), 2);
     }
 }
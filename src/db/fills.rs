@@ -0,0 +1,403 @@
+use itertools::Itertools;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::polymarket::api::clob::{
+    schemas::{Trade, TradeStatus, TraderSide, UserTradeEvent},
+    typedefs::Side,
+};
+
+use super::{database::lock_database, storage::storage};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub proxy_address: String,
+    pub trade_id: String,
+    pub order_id: String,
+    pub token_id: String,
+    pub market: String,
+    pub outcome: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub fee: Decimal,
+    pub tx_hash: String,
+    pub timestamp: i64,
+}
+
+impl Fill {
+    // A taker trade is a single fill at the trade price, a maker trade may have matched several of our resting orders
+    pub fn from_trade(trade: &Trade, proxy_address: &str) -> Vec<Self> {
+        let timestamp = trade.match_time.parse::<i64>().unwrap_or_default();
+
+        match trade.trader_side {
+            TraderSide::Taker => vec![Self {
+                proxy_address: proxy_address.to_string(),
+                trade_id: trade.id.clone(),
+                order_id: trade.taker_order_id.clone(),
+                token_id: trade.asset_id.clone(),
+                market: trade.market.clone(),
+                outcome: trade.outcome.clone(),
+                side: trade.side.clone(),
+                price: trade.price,
+                size: trade.size,
+                fee: calculate_fee(trade.fee_rate_bps, trade.price, trade.size),
+                tx_hash: trade.transaction_hash.clone(),
+                timestamp,
+            }],
+            TraderSide::Maker => trade
+                .maker_orders
+                .iter()
+                .filter(|order| order.maker_address.eq_ignore_ascii_case(proxy_address))
+                .map(|order| Self {
+                    proxy_address: proxy_address.to_string(),
+                    trade_id: trade.id.clone(),
+                    order_id: order.order_id.clone(),
+                    token_id: order.asset_id.clone(),
+                    market: trade.market.clone(),
+                    outcome: order.outcome.clone(),
+                    side: order.side.clone(),
+                    price: order.price,
+                    size: order.matched_amount,
+                    fee: calculate_fee(order.fee_rate_bps, order.price, order.matched_amount),
+                    tx_hash: trade.transaction_hash.clone(),
+                    timestamp,
+                })
+                .collect(),
+        }
+    }
+
+    // Same as `from_trade` for a user channel trade, our maker orders are the ones owned by our API
+    // key. Maker orders on the same token take the other side of the taker, the ones on the
+    // complementary token take the same side
+    pub fn from_user_trade(
+        trade: &UserTradeEvent,
+        proxy_address: &str,
+        api_key: &str,
+    ) -> Vec<Self> {
+        let timestamp = trade.matchtime.parse::<i64>().unwrap_or_default();
+        let own_maker_orders = trade.own_maker_orders(api_key).collect_vec();
+
+        if own_maker_orders.is_empty() {
+            let (price, size) = (trade.price, trade.size);
+
+            return vec![Self {
+                proxy_address: proxy_address.to_string(),
+                trade_id: trade.id.clone(),
+                order_id: trade.taker_order_id.clone(),
+                token_id: trade.asset_id.clone(),
+                market: trade.market.clone(),
+                outcome: trade.outcome.clone(),
+                side: trade.side.clone(),
+                price,
+                size,
+                fee: calculate_fee(trade.fee_rate_bps.unwrap_or_default(), price, size),
+                tx_hash: trade.transaction_hash.clone(),
+                timestamp,
+            }];
+        }
+
+        own_maker_orders
+            .into_iter()
+            .map(|order| {
+                let (price, size) = (order.price, order.matched_amount);
+                let side = order.side.clone().unwrap_or_else(|| {
+                    match (order.asset_id == trade.asset_id, &trade.side) {
+                        (true, Side::Buy) => Side::Sell,
+                        (true, Side::Sell) => Side::Buy,
+                        (false, side) => side.clone(),
+                    }
+                });
+
+                Self {
+                    proxy_address: proxy_address.to_string(),
+                    trade_id: trade.id.clone(),
+                    order_id: order.order_id.clone(),
+                    token_id: order.asset_id.clone(),
+                    market: trade.market.clone(),
+                    outcome: order.outcome.clone(),
+                    side,
+                    price,
+                    size,
+                    fee: calculate_fee(order.fee_rate_bps.unwrap_or_default(), price, size),
+                    tx_hash: trade.transaction_hash.clone(),
+                    timestamp,
+                }
+            })
+            .collect()
+    }
+
+    pub fn value(&self) -> Decimal {
+        self.price * self.size
+    }
+}
+
+// Polymarket charges fees on the cheaper side of the binary outcome
+fn calculate_fee(fee_rate_bps: Decimal, price: Decimal, size: Decimal) -> Decimal {
+    fee_rate_bps / Decimal::from(10_000) * price.min(Decimal::ONE - price) * size
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct FillsLedger(pub Vec<Fill>);
+
+impl FillsLedger {
+    pub async fn read() -> eyre::Result<Self> {
+        Ok(Self(storage().load_fills()?))
+    }
+
+    pub fn update(&self) -> eyre::Result<()> {
+        lock_database()?;
+        storage().save_fills(&self.0)
+    }
+
+    pub fn insert(&mut self, fills: Vec<Fill>) -> usize {
+        let mut inserted = 0;
+
+        for fill in fills {
+            let exists = self.0.iter().any(|existing| {
+                existing.trade_id == fill.trade_id && existing.order_id == fill.order_id
+            });
+
+            if !exists {
+                self.0.push(fill);
+                inserted += 1;
+            }
+        }
+
+        self.0.sort_by_key(|fill| fill.timestamp);
+
+        inserted
+    }
+
+    // Records a trade from the trade history, returns the number of fills added or removed
+    pub fn apply_trade(&mut self, trade: &Trade, proxy_address: &str) -> usize {
+        match trade.status {
+            TradeStatus::Failed => self.remove_trade(&trade.id),
+            _ => self.insert(Fill::from_trade(trade, proxy_address)),
+        }
+    }
+
+    // Records a trade reported on the user channel, returns the number of fills added or removed
+    pub fn apply_user_trade(
+        &mut self,
+        trade: &UserTradeEvent,
+        proxy_address: &str,
+        api_key: &str,
+    ) -> usize {
+        match trade.status {
+            TradeStatus::Failed => self.remove_trade(&trade.id),
+            _ => self.insert(Fill::from_user_trade(trade, proxy_address, api_key)),
+        }
+    }
+
+    // Drops the fills of a trade that failed on chain after it was matched
+    pub fn remove_trade(&mut self, trade_id: &str) -> usize {
+        let count = self.0.len();
+        self.0.retain(|fill| fill.trade_id != trade_id);
+
+        count - self.0.len()
+    }
+
+    pub fn account_fills<'a>(&'a self, proxy_address: &'a str) -> impl Iterator<Item = &'a Fill> {
+        self.0
+            .iter()
+            .filter(move |fill| fill.proxy_address.eq_ignore_ascii_case(proxy_address))
+    }
+
+    pub fn volume(&self, proxy_address: &str) -> Decimal {
+        self.account_fills(proxy_address).map(Fill::value).sum()
+    }
+
+    pub fn average_entry_price(&self, proxy_address: &str) -> Option<Decimal> {
+        let (value, size) = self
+            .account_fills(proxy_address)
+            .filter(|fill| fill.side == Side::Buy)
+            .fold((Decimal::ZERO, Decimal::ZERO), |(value, size), fill| {
+                (value + fill.value(), size + fill.size)
+            });
+
+        match size > Decimal::ZERO {
+            true => Some(value / size),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    const PROXY_ADDRESS: &str = "0x1111111111111111111111111111111111111111";
+
+    fn trade(trader_side: &str) -> Trade {
+        serde_json::from_value(serde_json::json!({
+            "id": "trade-1",
+            "taker_order_id": "0xtaker",
+            "market": "0xmarket",
+            "asset_id": "123",
+            "side": "BUY",
+            "size": "10",
+            "fee_rate_bps": "100",
+            "price": "0.4",
+            "status": "CONFIRMED",
+            "match_time": "1700000000",
+            "outcome": "Yes",
+            "maker_address": PROXY_ADDRESS,
+            "transaction_hash": "0xhash",
+            "trader_side": trader_side,
+            "maker_orders": [
+                {
+                    "order_id": "0xmaker-1",
+                    "maker_address": PROXY_ADDRESS.to_uppercase().replace("0X", "0x"),
+                    "matched_amount": "4",
+                    "price": "0.6",
+                    "fee_rate_bps": "0",
+                    "asset_id": "456",
+                    "outcome": "No",
+                    "side": "BUY"
+                },
+                {
+                    "order_id": "0xmaker-2",
+                    "maker_address": "0x2222222222222222222222222222222222222222",
+                    "matched_amount": "6",
+                    "price": "0.6",
+                    "fee_rate_bps": "0",
+                    "asset_id": "456",
+                    "outcome": "No",
+                    "side": "BUY"
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_taker_trade_normalization() {
+        let fills = Fill::from_trade(&trade("TAKER"), PROXY_ADDRESS);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, "0xtaker");
+        assert_eq!(fills[0].token_id, "123");
+        assert_eq!(fills[0].size, dec!(10));
+        assert_eq!(fills[0].fee, dec!(0.04));
+        assert_eq!(fills[0].timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_maker_trade_normalization() {
+        let fills = Fill::from_trade(&trade("MAKER"), PROXY_ADDRESS);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, "0xmaker-1");
+        assert_eq!(fills[0].token_id, "456");
+        assert_eq!(fills[0].size, dec!(4));
+        assert_eq!(fills[0].price, dec!(0.6));
+    }
+
+    #[test]
+    fn test_ledger_deduplication_and_aggregates() {
+        let mut ledger = FillsLedger::default();
+
+        assert_eq!(
+            ledger.insert(Fill::from_trade(&trade("TAKER"), PROXY_ADDRESS)),
+            1
+        );
+        assert_eq!(
+            ledger.insert(Fill::from_trade(&trade("TAKER"), PROXY_ADDRESS)),
+            0
+        );
+        assert_eq!(
+            ledger.insert(Fill::from_trade(&trade("MAKER"), PROXY_ADDRESS)),
+            1
+        );
+
+        assert_eq!(ledger.volume(PROXY_ADDRESS), dec!(6.4));
+        assert_eq!(
+            ledger
+                .average_entry_price(PROXY_ADDRESS)
+                .map(|price| price.round_dp(6)),
+            Some(dec!(0.457143))
+        );
+        assert_eq!(ledger.average_entry_price("0xdead"), None);
+    }
+
+    #[test]
+    fn test_failed_trade_is_removed_on_sync() {
+        let mut ledger = FillsLedger::default();
+        let mut trade = trade("TAKER");
+
+        assert_eq!(ledger.apply_trade(&trade, PROXY_ADDRESS), 1);
+        assert_eq!(ledger.apply_trade(&trade, PROXY_ADDRESS), 0);
+
+        trade.status = TradeStatus::Failed;
+        assert_eq!(ledger.apply_trade(&trade, PROXY_ADDRESS), 1);
+        assert!(ledger.0.is_empty());
+
+        // A failed trade seen for the first time is not recorded either
+        assert_eq!(ledger.apply_trade(&trade, PROXY_ADDRESS), 0);
+        assert!(ledger.0.is_empty());
+    }
+
+    #[test]
+    fn test_user_trade_normalization() {
+        let mut trade = serde_json::from_value::<UserTradeEvent>(serde_json::json!({
+            "id": "trade-2",
+            "taker_order_id": "0xtaker",
+            "market": "0xmarket",
+            "asset_id": "123",
+            "outcome": "Yes",
+            "side": "BUY",
+            "price": "0.4",
+            "size": "10",
+            "status": "MATCHED",
+            "fee_rate_bps": "100",
+            "matchtime": "1700000000",
+            "maker_orders": [
+                {
+                    "order_id": "0xmaker-1",
+                    "owner": "our-api-key",
+                    "asset_id": "123",
+                    "outcome": "Yes",
+                    "matched_amount": "4",
+                    "price": "0.4"
+                },
+                {
+                    "order_id": "0xmaker-2",
+                    "owner": "our-api-key",
+                    "asset_id": "456",
+                    "outcome": "No",
+                    "matched_amount": "6",
+                    "price": "0.6"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let fills = Fill::from_user_trade(&trade, PROXY_ADDRESS, "our-api-key");
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].side, Side::Sell);
+        assert_eq!(fills[0].size, dec!(4));
+        assert_eq!(fills[1].side, Side::Buy);
+        assert_eq!(fills[1].token_id, "456");
+
+        let fills = Fill::from_user_trade(&trade, PROXY_ADDRESS, "other-api-key");
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, "0xtaker");
+        assert_eq!(fills[0].fee, dec!(0.04));
+        assert_eq!(fills[0].timestamp, 1_700_000_000);
+
+        let mut ledger = FillsLedger::default();
+        ledger.insert(fills);
+        trade.id = "trade-3".to_string();
+        ledger.insert(Fill::from_user_trade(
+            &trade,
+            PROXY_ADDRESS,
+            "other-api-key",
+        ));
+
+        assert_eq!(ledger.remove_trade("trade-2"), 1);
+        assert_eq!(ledger.0[0].trade_id, "trade-3");
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+
+use crate::utils::files::{read_file_lines, write_atomic};
+
+use super::{
+    constants::ENCRYPTED_PRIVATE_KEYS_FILE_PATH,
+    crypto::{decrypt_private_key, encrypt_private_key},
+    database::lock_database,
+};
+
+// The encrypted private keys file, one encrypted key per line. Line order matches proxies.txt and recipients.txt.
+// Addresses are decrypted once and cached in the second field, every decryption runs the KDF
+#[derive(Debug, Default)]
+pub struct Keystore(pub Vec<String>, Option<Vec<Address>>);
+
+// Lines of the key, proxies and recipients files, blank lines are skipped in all three so positions
+// line up. A missing file has no lines
+pub async fn read_account_lines(file_path: &str) -> eyre::Result<Vec<String>> {
+    if !Path::new(file_path).exists() {
+        return Ok(vec![]);
+    }
+
+    Ok(read_file_lines(file_path)
+        .await?
+        .into_iter()
+        .filter(|line| !line.trim().is_empty())
+        .collect())
+}
+
+impl Keystore {
+    pub async fn read() -> eyre::Result<Self> {
+        Self::read_from(ENCRYPTED_PRIVATE_KEYS_FILE_PATH).await
+    }
+
+    pub async fn read_from(file_path: &str) -> eyre::Result<Self> {
+        Ok(Self(read_account_lines(file_path).await?, None))
+    }
+
+    pub async fn write(&self) -> eyre::Result<()> {
+        lock_database()?;
+
+        let contents = self
+            .0
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+
+        write_atomic(ENCRYPTED_PRIVATE_KEYS_FILE_PATH, contents.as_bytes())
+    }
+
+    pub fn decrypt(&self, index: usize, password: &str) -> eyre::Result<PrivateKeySigner> {
+        let encrypted = self
+            .0
+            .get(index)
+            .ok_or_else(|| eyre::eyre!("Missing private key at position {}", index))?;
+        let private_key = decrypt_private_key(encrypted, password).map_err(|e| {
+            eyre::eyre!("Failed to decrypt private key at position {}: {}", index, e)
+        })?;
+
+        Ok(PrivateKeySigner::from_str(&private_key)?)
+    }
+
+    pub fn addresses(&self, password: &str) -> eyre::Result<Vec<Address>> {
+        (0..self.0.len())
+            .map(|index| Ok(self.decrypt(index, password)?.address()))
+            .collect()
+    }
+
+    fn cached_addresses(&mut self, password: &str) -> eyre::Result<&[Address]> {
+        let stale = self
+            .1
+            .as_ref()
+            .is_none_or(|addresses| addresses.len() != self.0.len());
+
+        if stale {
+            self.1 = Some(self.addresses(password)?);
+        }
+
+        Ok(self.1.as_deref().unwrap_or_default())
+    }
+
+    pub fn position(&mut self, address: Address, password: &str) -> eyre::Result<Option<usize>> {
+        Ok(self
+            .cached_addresses(password)?
+            .iter()
+            .position(|entry| *entry == address))
+    }
+
+    // Returns the index of the new entry, or None if the account is already in the keystore
+    pub fn add(&mut self, private_key: &str, password: &str) -> eyre::Result<Option<usize>> {
+        let private_key = private_key.trim();
+        let signer = PrivateKeySigner::from_str(private_key)
+            .map_err(|e| eyre::eyre!("Invalid private key: {e}"))?;
+
+        if self.position(signer.address(), password)?.is_some() {
+            return Ok(None);
+        }
+
+        self.0.push(encrypt_private_key(private_key, password));
+        if let Some(addresses) = self.1.as_mut() {
+            addresses.push(signer.address());
+        }
+
+        Ok(Some(self.0.len() - 1))
+    }
+
+    pub fn remove(&mut self, address: Address, password: &str) -> eyre::Result<Option<usize>> {
+        let index = self.position(address, password)?;
+
+        if let Some(index) = index {
+            self.0.remove(index);
+            if let Some(addresses) = self.1.as_mut() {
+                addresses.remove(index);
+            }
+        }
+
+        Ok(index)
+    }
+}
+
+// Writes a standard Ethereum V3 JSON keystore named after the account address
+pub fn export_v3_keystore(
+    signer: &PrivateKeySigner,
+    dir: impl AsRef<Path>,
+    password: &str,
+) -> eyre::Result<PathBuf> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let name = signer.address().to_string();
+    PrivateKeySigner::encrypt_keystore(
+        dir,
+        &mut rand::thread_rng(),
+        signer.to_bytes(),
+        password,
+        Some(&name),
+    )?;
+
+    Ok(dir.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[test]
+    fn test_add_remove_and_v3_export() {
+        let mut keystore = Keystore::default();
+        let signer = PrivateKeySigner::from_str(PRIVATE_KEY).unwrap();
+
+        assert_eq!(keystore.add(PRIVATE_KEY, "password").unwrap(), Some(0));
+        assert_eq!(keystore.add(PRIVATE_KEY, "password").unwrap(), None);
+        assert!(keystore.add("not a key", "password").is_err());
+        assert_eq!(keystore.decrypt(0, "password").unwrap(), signer);
+
+        // Duplicate checks read the cached addresses instead of decrypting every entry again
+        assert_eq!(keystore.1.as_deref(), Some(&[signer.address()][..]));
+
+        let dir = std::env::temp_dir().join(format!("keystore-test-{}", uuid::Uuid::new_v4()));
+        let path = export_v3_keystore(&signer, &dir, "export").unwrap();
+        assert_eq!(
+            PrivateKeySigner::decrypt_keystore(&path, "export").unwrap(),
+            signer
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(
+            keystore.remove(signer.address(), "password").unwrap(),
+            Some(0)
+        );
+        assert!(keystore.0.is_empty());
+        assert_eq!(keystore.1.as_deref(), Some(&[][..]));
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use crate::utils::files::{read_file_lines, write_atomic};
+
+use super::{
+    constants::{DEFAULT_PASSWORD, ENCRYPTED_PRIVATE_KEYS_FILE_PATH},
+    crypto::{decrypt_private_key, encrypt_private_key, encryption_password, is_legacy_format},
+    database::{lock_database, Database},
+    storage::storage,
+};
+
+// Re-encrypts legacy v0 blobs in the key file and the database with the current format.
+// Every blob is re-encrypted when a new password is given. Account sessions and API credentials
+// follow the key of their private key. Both stores are re-encrypted in memory before either is
+// written, so a failure leaves them on the old password
+pub async fn migrate_encryption(
+    password: Option<&str>,
+    new_password: Option<&str>,
+) -> eyre::Result<()> {
+    let current_password = password.unwrap_or(DEFAULT_PASSWORD);
+    let target_password = encryption_password(new_password.or(password))?;
+    let reencrypt_all = new_password.is_some();
+
+    let mut key_lines = match Path::new(ENCRYPTED_PRIVATE_KEYS_FILE_PATH).exists() {
+        true => Some(read_file_lines(ENCRYPTED_PRIVATE_KEYS_FILE_PATH).await?),
+        false => None,
+    };
+    let mut db = match storage().file_path().exists() {
+        true => Some(Database::read(password).await?),
+        false => None,
+    };
+
+    let migrated_keys = match key_lines.as_mut() {
+        Some(lines) => migrate_key_lines(lines, current_password, target_password, reencrypt_all)?,
+        None => 0,
+    };
+    let migrated_accounts = match db.as_mut() {
+        Some(db) => migrate_accounts(db, target_password, reencrypt_all)?,
+        None => 0,
+    };
+
+    lock_database()?;
+
+    // The key file goes first, its lines already on the target password are skipped on a retry
+    if let Some(lines) = key_lines {
+        if migrated_keys > 0 {
+            write_atomic(
+                ENCRYPTED_PRIVATE_KEYS_FILE_PATH,
+                (lines.join("\n") + "\n").as_bytes(),
+            )?;
+        }
+
+        tracing::info!(
+            "{ENCRYPTED_PRIVATE_KEYS_FILE_PATH}: re-encrypted {migrated_keys} private keys"
+        );
+    }
+
+    if let Some(db) = db {
+        if migrated_accounts > 0 {
+            db.update()?;
+        }
+
+        tracing::info!(
+            "{}: re-encrypted {migrated_accounts} private keys",
+            storage().file_path().display()
+        );
+    }
+
+    Ok(())
+}
+
+// Lines that already decrypt with the target password are left as they are
+fn migrate_key_lines(
+    lines: &mut [String],
+    current_password: &str,
+    target_password: &str,
+    reencrypt_all: bool,
+) -> eyre::Result<usize> {
+    let mut migrated = 0;
+
+    for (i, line) in lines.iter_mut().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let needs_migration = is_legacy_format(line)
+            || (reencrypt_all && decrypt_private_key(line, target_password).is_err());
+
+        if !needs_migration {
+            continue;
+        }
+
+        let private_key = decrypt_private_key(line, current_password)
+            .map_err(|e| eyre::eyre!("Failed to decrypt private key at position {}: {}", i, e))?;
+        *line = encrypt_private_key(&private_key, target_password);
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+fn migrate_accounts(
+    db: &mut Database,
+    target_password: &str,
+    reencrypt_all: bool,
+) -> eyre::Result<usize> {
+    let mut migrated = 0;
+
+    for account in db.0.iter_mut() {
+        if !reencrypt_all && !is_legacy_format(account.get_encrypted_private_key()) {
+            continue;
+        }
+
+        let encrypted = encrypt_private_key(account.get_private_key(), target_password);
+        account.set_encrypted_private_key(&encrypted);
+        // Re-derive the key the session and API credentials are sealed with
+        account.unlock(target_password)?;
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEYS: [&str; 2] = [
+        "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "0x2222222222222222222222222222222222222222222222222222222222222222",
+    ];
+
+    #[test]
+    fn test_interrupted_password_change_can_be_retried() {
+        // The first key was re-encrypted by a run that failed before finishing
+        let mut lines = vec![
+            encrypt_private_key(PRIVATE_KEYS[0], "new"),
+            String::new(),
+            encrypt_private_key(PRIVATE_KEYS[1], "old"),
+        ];
+        let migrated_line = lines[0].clone();
+
+        assert_eq!(
+            migrate_key_lines(&mut lines, "old", "new", true).unwrap(),
+            1
+        );
+        assert_eq!(lines[0], migrated_line);
+        assert!(lines[1].is_empty());
+        assert_eq!(
+            decrypt_private_key(&lines[2], "new").unwrap(),
+            PRIVATE_KEYS[1]
+        );
+
+        assert_eq!(
+            migrate_key_lines(&mut lines, "old", "new", true).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_lines_on_old_password() {
+        let mut lines = vec![
+            encrypt_private_key(PRIVATE_KEYS[0], "old"),
+            encrypt_private_key(PRIVATE_KEYS[1], "other"),
+        ];
+
+        assert!(migrate_key_lines(&mut lines, "old", "new", true).is_err());
+        assert!(decrypt_private_key(&lines[1], "other").is_ok());
+    }
+}
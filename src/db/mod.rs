@@ -1,4 +1,11 @@
 pub mod account;
 pub mod constants;
+pub mod crypto;
 pub mod database;
-mod crypto;
\ No newline at end of file
+pub mod fills;
+pub mod keystore;
+pub mod migrate;
+pub mod order;
+pub mod selection;
+pub mod source;
+pub mod storage;
@@ -0,0 +1,20 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::polymarket::api::clob::{
+    schemas::{OrderStatus, OrderType},
+    typedefs::Side,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrderRecord {
+    pub order_id: String,
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: OrderType,
+    pub expiration: u64,
+    pub created_at: i64,
+    pub status: Option<OrderStatus>,
+}
@@ -0,0 +1,210 @@
+use std::{collections::HashSet, str::FromStr};
+
+use alloy::primitives::Address;
+
+use crate::polymarket::api::user::endpoints::get_user_positions;
+
+use super::account::Account;
+
+pub const SELECTION_HELP: &str = "tag:<name>, index:<n> | index:<from>-[<to>], \
+address:<address>,... | address:@<file>, registered, funded, has-positions. \
+Prefix with `!` to negate, every expression must match";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Tag(String),
+    // Inclusive range of database positions
+    Index { from: usize, to: Option<usize> },
+    // Matches both the owner and the proxy wallet address
+    Address(HashSet<Address>),
+    Registered,
+    Funded,
+    HasPositions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Criterion {
+    filter: Filter,
+    negated: bool,
+}
+
+// Whitespace separated expressions combined with AND, the empty selection matches every account
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection(Vec<Criterion>);
+
+impl FromStr for Filter {
+    type Err = eyre::Report;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = match expression.split_once(':') {
+            Some((kind, value)) => (kind, Some(value.trim())),
+            None => (expression, None),
+        };
+
+        let filter = match (kind.trim().to_lowercase().as_str(), value) {
+            ("tag", Some(tag)) if !tag.is_empty() => Self::Tag(tag.to_string()),
+            ("index", Some(range)) => {
+                let parse = |index: &str| {
+                    index
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| eyre::eyre!("Invalid account index `{index}`"))
+                };
+
+                match range.split_once('-') {
+                    Some((from, "")) => Self::Index {
+                        from: parse(from)?,
+                        to: None,
+                    },
+                    Some((from, to)) => Self::Index {
+                        from: parse(from)?,
+                        to: Some(parse(to)?),
+                    },
+                    None => Self::Index {
+                        from: parse(range)?,
+                        to: Some(parse(range)?),
+                    },
+                }
+            }
+            ("address", Some(addresses)) => {
+                let addresses = match addresses.strip_prefix('@') {
+                    Some(file_path) => std::fs::read_to_string(file_path)
+                        .map_err(|e| eyre::eyre!("Failed to read {file_path}: {e}"))?,
+                    None => addresses.replace(',', "\n"),
+                };
+
+                Self::Address(
+                    addresses
+                        .lines()
+                        .map(str::trim)
+                        .filter(|address| !address.is_empty())
+                        .map(|address| {
+                            Address::from_str(address)
+                                .map_err(|_| eyre::eyre!("Invalid address `{address}`"))
+                        })
+                        .collect::<eyre::Result<_>>()?,
+                )
+            }
+            ("registered", None) => Self::Registered,
+            ("funded", None) => Self::Funded,
+            ("has-positions", None) => Self::HasPositions,
+            _ => eyre::bail!("Invalid selection `{expression}`, expected one of: {SELECTION_HELP}"),
+        };
+
+        Ok(filter)
+    }
+}
+
+impl FromStr for Selection {
+    type Err = eyre::Report;
+
+    fn from_str(expressions: &str) -> Result<Self, Self::Err> {
+        expressions
+            .split_whitespace()
+            .map(|expression| {
+                let (negated, expression) = match expression.strip_prefix('!') {
+                    Some(expression) => (true, expression),
+                    None => (false, expression),
+                };
+
+                Ok(Criterion {
+                    filter: expression.parse()?,
+                    negated,
+                })
+            })
+            .collect::<eyre::Result<_>>()
+            .map(Self)
+    }
+}
+
+impl Selection {
+    pub fn parse_all<S: AsRef<str>>(expressions: &[S]) -> eyre::Result<Self> {
+        let mut selection = Self::default();
+
+        for expressions in expressions {
+            selection.0.extend(expressions.as_ref().parse::<Self>()?.0);
+        }
+
+        Ok(selection)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // `has-positions` queries the data API, so it is checked after every local criterion
+    pub async fn matches(&self, index: usize, account: &Account) -> eyre::Result<bool> {
+        let (remote, local): (Vec<_>, Vec<_>) = self
+            .0
+            .iter()
+            .partition(|criterion| criterion.filter == Filter::HasPositions);
+
+        for criterion in local.into_iter().chain(remote) {
+            let matches = match &criterion.filter {
+                Filter::Tag(tag) => account.has_tag(tag),
+                Filter::Index { from, to } => index >= *from && to.is_none_or(|to| index <= to),
+                Filter::Address(addresses) => {
+                    addresses.contains(&account.signer().address())
+                        || addresses.contains(&account.get_proxy_address())
+                }
+                Filter::Registered => account.get_is_registered(),
+                Filter::Funded => account.get_funded(),
+                Filter::HasPositions => {
+                    let proxy = account.proxy();
+                    !get_user_positions(&account.proxy_address, proxy.as_ref())
+                        .await?
+                        .is_empty()
+                }
+            };
+
+            if matches == criterion.negated {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[tokio::test]
+    async fn test_selection() {
+        let mut account = Account::new(PRIVATE_KEY, "encrypted", None, None);
+        account.add_tag("whales");
+        account.set_funded(true);
+        let address = account.signer().address();
+
+        let matches = |expressions: &str| {
+            let selection = expressions.parse::<Selection>().unwrap();
+            let account = &account;
+            async move { selection.matches(3, account).await.unwrap() }
+        };
+
+        assert!(matches("").await);
+        assert!(matches("tag:whales funded !registered").await);
+        assert!(!matches("tag:whales registered").await);
+        assert!(!matches("tag:others").await);
+        assert!(matches("index:3").await);
+        assert!(matches("index:0-3").await);
+        assert!(matches("index:2-").await);
+        assert!(!matches("index:4-").await);
+        assert!(matches(&format!("address:{},{address}", Address::ZERO)).await);
+        assert!(matches(&format!("address:{}", account.proxy_address)).await);
+        assert!(!matches(&format!("!address:{address}")).await);
+
+        assert!("tag:".parse::<Selection>().is_err());
+        assert!("index:a-b".parse::<Selection>().is_err());
+        assert!("address:0x1234".parse::<Selection>().is_err());
+        assert!("positions".parse::<Selection>().is_err());
+
+        assert_eq!(
+            Selection::parse_all(&["funded", "tag:a tag:b"]).unwrap(),
+            "funded tag:a tag:b".parse().unwrap()
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use alloy::signers::local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner};
+
+use super::{
+    constants::ENCRYPTED_PRIVATE_KEYS_FILE_PATH,
+    crypto::{decrypt_private_key, encrypt_private_key},
+    keystore::read_account_lines,
+};
+
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0";
+
+#[derive(Debug, Clone, Default)]
+pub enum AccountSource {
+    #[default]
+    EncryptedKeys,
+    KeystoreDir {
+        path: PathBuf,
+        password: String,
+    },
+    // Accounts are derived at `<derivation_path>/<index>` for every index in `start..start + count`
+    Mnemonic {
+        phrase: String,
+        derivation_path: String,
+        start: u32,
+        count: u32,
+    },
+}
+
+pub struct SourceKey {
+    pub private_key: String,
+    pub encrypted_private_key: String,
+}
+
+impl SourceKey {
+    fn from_signer(signer: &PrivateKeySigner, password: &str) -> Self {
+        let private_key = format!("0x{}", hex::encode(signer.to_bytes()));
+        let encrypted_private_key = encrypt_private_key(&private_key, password);
+
+        Self {
+            private_key,
+            encrypted_private_key,
+        }
+    }
+}
+
+impl AccountSource {
+    // Keys from other sources are encrypted with the database password so `Database::read` can decrypt them
+    pub async fn load(&self, password: &str) -> eyre::Result<Vec<SourceKey>> {
+        match self {
+            Self::EncryptedKeys => {
+                let encrypted_private_keys =
+                    read_account_lines(ENCRYPTED_PRIVATE_KEYS_FILE_PATH).await?;
+
+                encrypted_private_keys
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, encrypted_private_key)| {
+                        let private_key = decrypt_private_key(&encrypted_private_key, password)
+                            .map_err(|e| {
+                                eyre::eyre!(
+                                    "Failed to decrypt private key at position {}: {}",
+                                    i,
+                                    e
+                                )
+                            })?;
+
+                        Ok(SourceKey {
+                            private_key,
+                            encrypted_private_key,
+                        })
+                    })
+                    .collect()
+            }
+            Self::KeystoreDir {
+                path,
+                password: keystore_password,
+            } => {
+                let mut keystore_paths = std::fs::read_dir(path)?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.is_file()
+                            && !path
+                                .file_name()
+                                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                    })
+                    .collect::<Vec<_>>();
+                keystore_paths.sort();
+
+                keystore_paths
+                    .iter()
+                    .map(|keystore_path| {
+                        let signer =
+                            PrivateKeySigner::decrypt_keystore(keystore_path, keystore_password)
+                                .map_err(|e| {
+                                    eyre::eyre!(
+                                        "Failed to decrypt keystore {}: {}",
+                                        keystore_path.display(),
+                                        e
+                                    )
+                                })?;
+
+                        Ok(SourceKey::from_signer(&signer, password))
+                    })
+                    .collect()
+            }
+            Self::Mnemonic {
+                phrase,
+                derivation_path,
+                start,
+                count,
+            } => (*start..start + count)
+                .map(|index| {
+                    let signer = MnemonicBuilder::<English>::default()
+                        .phrase(phrase.trim())
+                        .derivation_path(format!(
+                            "{}/{index}",
+                            derivation_path.trim_end_matches('/')
+                        ))?
+                        .build()?;
+
+                    Ok(SourceKey::from_signer(&signer, password))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hardhat/Anvil default mnemonic
+    const PHRASE: &str = "test test test test test test test test test test test junk";
+
+    #[tokio::test]
+    async fn test_mnemonic_derivation() {
+        let source = AccountSource::Mnemonic {
+            phrase: PHRASE.to_string(),
+            derivation_path: DEFAULT_DERIVATION_PATH.to_string(),
+            start: 1,
+            count: 2,
+        };
+
+        let keys = source.load("password").await.unwrap();
+        let addresses = keys
+            .iter()
+            .map(|key| {
+                assert_eq!(
+                    decrypt_private_key(&key.encrypted_private_key, "password").unwrap(),
+                    key.private_key
+                );
+                key.private_key
+                    .parse::<PrivateKeySigner>()
+                    .unwrap()
+                    .address()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            addresses,
+            [
+                "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+                "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC"
+            ]
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use crate::{
+    db::{
+        account::Account,
+        constants::{DB_FILE_PATH, FILLS_FILE_PATH},
+        fills::Fill,
+    },
+    utils::files::write_json_atomic,
+};
+
+use super::{Storage, TransactionRecord};
+
+// `db.json` and `fills.json`, transaction history is not kept
+pub struct JsonStorage;
+
+impl Storage for JsonStorage {
+    fn file_path(&self) -> &Path {
+        Path::new(DB_FILE_PATH)
+    }
+
+    fn load_accounts(&self) -> eyre::Result<Vec<Account>> {
+        let contents = std::fs::read_to_string(DB_FILE_PATH)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save_accounts(&self, accounts: &[Account]) -> eyre::Result<()> {
+        write_json_atomic(DB_FILE_PATH, &accounts)
+    }
+
+    fn load_fills(&self) -> eyre::Result<Vec<Fill>> {
+        match std::fs::read_to_string(FILLS_FILE_PATH) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_fills(&self, fills: &[Fill]) -> eyre::Result<()> {
+        write_json_atomic(FILLS_FILE_PATH, &fills)
+    }
+
+    fn record_transaction(&self, _record: &TransactionRecord) -> eyre::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::{path::Path, sync::OnceLock};
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::utils::dry_run;
+
+use super::{account::Account, fills::Fill};
+
+pub mod json;
+pub mod sqlite;
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+#[derive(Debug, Clone)]
+pub enum TransactionRecord {
+    Deposit {
+        proxy_address: String,
+        amount: Decimal,
+        success: bool,
+    },
+    Withdrawal {
+        proxy_address: String,
+        recipient: String,
+        amount: Decimal,
+        transaction_id: String,
+    },
+    Relayer {
+        proxy_address: String,
+        transaction_id: String,
+        tx_hash: Option<String>,
+        kind: String,
+    },
+}
+
+// Accounts are persisted with their private keys encrypted, decryption stays in `Database`
+pub trait Storage: Send + Sync {
+    fn file_path(&self) -> &Path;
+
+    fn load_accounts(&self) -> eyre::Result<Vec<Account>>;
+
+    fn save_accounts(&self, accounts: &[Account]) -> eyre::Result<()>;
+
+    fn load_fills(&self) -> eyre::Result<Vec<Fill>>;
+
+    // Replaces the stored ledger with `fills`
+    fn save_fills(&self, fills: &[Fill]) -> eyre::Result<()>;
+
+    fn record_transaction(&self, record: &TransactionRecord) -> eyre::Result<()>;
+}
+
+impl StorageBackend {
+    pub fn storage(&self) -> Box<dyn Storage> {
+        match self {
+            Self::Json => Box::new(json::JsonStorage),
+            Self::Sqlite => Box::new(sqlite::SqliteStorage::default()),
+        }
+    }
+}
+
+// Selects the backend for the rest of the process, the JSON backend is used when this is never called
+pub fn init_storage(backend: StorageBackend) {
+    if STORAGE.set(backend.storage()).is_err() {
+        tracing::warn!("Storage backend is already initialized");
+    }
+}
+
+pub fn storage() -> &'static dyn Storage {
+    STORAGE
+        .get_or_init(|| StorageBackend::default().storage())
+        .as_ref()
+}
+
+// Copies `db.json` and `fills.json` into the SQLite database, existing rows for the same accounts and fills are replaced
+pub fn import_json_into_sqlite() -> eyre::Result<()> {
+    let sqlite = sqlite::SqliteStorage::default();
+    let accounts = json::JsonStorage.load_accounts()?;
+    let fills = json::JsonStorage.load_fills()?;
+
+    let (mut merged_accounts, mut merged_fills) = match sqlite.file_path().exists() {
+        true => (sqlite.load_accounts()?, sqlite.load_fills()?),
+        false => (vec![], vec![]),
+    };
+    merged_accounts.retain(|account| {
+        !accounts
+            .iter()
+            .any(|imported| imported.address == account.address)
+    });
+    merged_accounts.extend(accounts.iter().cloned());
+    merged_fills.retain(|fill| {
+        !fills.iter().any(|imported| {
+            imported.trade_id == fill.trade_id && imported.order_id == fill.order_id
+        })
+    });
+    merged_fills.extend(fills.iter().cloned());
+    merged_fills.sort_by_key(|fill| fill.timestamp);
+
+    sqlite.save_accounts(&merged_accounts)?;
+    sqlite.save_fills(&merged_fills)?;
+
+    tracing::info!(
+        "Imported {} accounts and {} fills into {}",
+        accounts.len(),
+        fills.len(),
+        sqlite.file_path().display()
+    );
+
+    Ok(())
+}
+
+// History is best effort, a failed write must not abort a module after funds have moved
+pub fn record_transaction(record: TransactionRecord) {
+    if dry_run::is_enabled() {
+        return;
+    }
+
+    if let Err(e) = storage().record_transaction(&record) {
+        tracing::warn!("Failed to record transaction {record:?}: {e}");
+    }
+}
@@ -0,0 +1,33 @@
+use itertools::Itertools;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file `{path}`: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("Failed to parse config: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Profile `{0}` is not defined in the config")]
+    UnknownProfile(String),
+
+    #[error("Failed to parse profile `{profile}`: {source}")]
+    Profile {
+        profile: String,
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid config:\n{}", .0.iter().map(|e| format!("  - {e}")).join("\n"))]
+    Invalid(Vec<ConfigFieldError>),
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("{field}: {reason}")]
+pub struct ConfigFieldError {
+    pub field: &'static str,
+    pub reason: String,
+}
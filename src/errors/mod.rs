@@ -1 +1,3 @@
+pub mod config;
 pub mod custom;
+pub mod price_protection;
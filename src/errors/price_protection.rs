@@ -0,0 +1,37 @@
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::polymarket::api::clob::typedefs::Side;
+
+// Reasons a market order is refused before it is signed, the amount is in USDC for buys and in
+// shares for sells
+#[derive(Error, Debug, PartialEq)]
+pub enum PriceProtectionError {
+    #[error("Order book has no liquidity to {side} {amount}")]
+    EmptyBook { side: Side, amount: Decimal },
+
+    #[error("Order book depth of {available} is not enough to {side} {amount}")]
+    InsufficientDepth {
+        side: Side,
+        amount: Decimal,
+        available: Decimal,
+    },
+
+    #[error("{side} {amount} would fill at {price} against the best price {best_price}, {slippage_bps} bps of slippage is over the {max_slippage_bps} bps limit")]
+    SlippageExceeded {
+        side: Side,
+        amount: Decimal,
+        price: Decimal,
+        best_price: Decimal,
+        slippage_bps: Decimal,
+        max_slippage_bps: u32,
+    },
+
+    #[error("{side} {amount} would fill at {price}, worse than the {limit} price limit")]
+    WorstPriceExceeded {
+        side: Side,
+        amount: Decimal,
+        price: Decimal,
+        limit: Decimal,
+    },
+}
@@ -1,6 +1,10 @@
-use modules::menu::menu;
+use clap::Parser;
+use modules::cli::{run, Cli};
 
-use utils::logger::init_default_logger;
+use utils::{
+    logger::init_default_logger,
+    notifier::{notify, NotificationKind},
+};
 
 mod config;
 mod db;
@@ -8,14 +12,20 @@ mod errors;
 mod modules;
 mod onchain;
 mod polymarket;
+#[cfg(test)]
+mod testing;
 mod utils;
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
-    let _guard = init_default_logger();
+    let cli = Cli::parse();
+    let guard = init_default_logger();
 
-    if let Err(e) = menu().await {
+    if let Err(e) = run(cli).await {
         tracing::error!("Execution stopped with error: {e}");
+        notify(NotificationKind::ModuleFailed, "Execution stopped", e.to_string()).await;
+        drop(guard);
+        std::process::exit(1);
     }
 
     Ok(())
@@ -0,0 +1,81 @@
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::db::{database::Database, selection::Selection};
+
+#[derive(Tabled)]
+struct AccountEntry {
+    #[tabled(rename = "Index")]
+    index: usize,
+
+    #[tabled(rename = "Address")]
+    address: String,
+
+    #[tabled(rename = "Proxy Address")]
+    proxy_address: String,
+
+    #[tabled(rename = "Tags")]
+    tags: String,
+
+    #[tabled(rename = "Registered")]
+    registered: bool,
+
+    #[tabled(rename = "Funded")]
+    funded: bool,
+}
+
+// Indexes are database positions, the same ones `index:` selections refer to
+pub async fn display_accounts(db: &Database, selection: &Selection) -> eyre::Result<()> {
+    let mut entries = vec![];
+
+    for (index, account) in db.0.iter().enumerate() {
+        if !selection.matches(index, account).await? {
+            continue;
+        }
+
+        entries.push(AccountEntry {
+            index,
+            address: account.signer().address().to_string(),
+            proxy_address: account.proxy_address.clone(),
+            tags: account.get_tags().join(", "),
+            registered: account.get_is_registered(),
+            funded: account.get_funded(),
+        });
+    }
+
+    if entries.is_empty() {
+        tracing::info!("No accounts match the selection");
+        return Ok(());
+    }
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+
+    Ok(())
+}
+
+pub fn tag_accounts(mut db: Database, add: &[String], remove: &[String]) -> eyre::Result<()> {
+    // Selections are whitespace separated, so a tag with spaces could never be selected
+    if let Some(tag) = add
+        .iter()
+        .find(|tag| tag.is_empty() || tag.contains(char::is_whitespace))
+    {
+        eyre::bail!("Invalid tag `{tag}`, tags can't be empty or contain whitespace");
+    }
+
+    for account in db.0.iter_mut() {
+        let added = add.iter().filter(|tag| account.add_tag(tag)).count();
+        let removed = remove.iter().filter(|tag| account.remove_tag(tag)).count();
+
+        if added + removed > 0 {
+            tracing::info!(
+                "{} | Tags: [{}]",
+                account.proxy_address,
+                account.get_tags().join(", ")
+            );
+        }
+    }
+
+    db.update()
+}
@@ -5,7 +5,7 @@ use crate::{
     config::Config,
     errors::custom::CustomError,
     polymarket::api::events::{
-        endpoints::get_events,
+        endpoints::{get_events, EventsQuery},
         schemas::{Event, Market},
     },
 };
@@ -14,14 +14,15 @@ pub async fn get_filtered_events(
     proxy: Option<&Proxy>,
     config: &Config,
 ) -> Result<Vec<Event>, CustomError> {
+    let query = EventsQuery::default();
     let mut offset = 0;
     let mut filtered_events = vec![];
 
     loop {
-        let events = get_events(None, offset, proxy)
+        let events = get_events(&query, offset, proxy)
             .await?
             .into_iter()
-            .filter(|event| event.volume >= config.min_event_volume)
+            .filter(|event| event.volume.unwrap_or_default() >= config.min_event_volume)
             .collect_vec();
 
         if events.is_empty() {
@@ -43,16 +44,17 @@ pub async fn get_filtered_events(
             },
         ));
 
-        offset += 20;
+        offset += query.limit;
     }
 
     Ok(filtered_events)
 }
 
 fn market_fits_filters(market: &Market, max_price_diff: f64, min_spread: f64) -> bool {
-    let outcome_prices = market.outcome_prices.unwrap_or([0.0, 1000.0]);
-
-    let price_diff = (outcome_prices[0] - outcome_prices[1]).abs();
+    let price_diff = match market.outcome_prices.as_deref() {
+        Some([first, second]) if market.is_binary() => (first - second).abs(),
+        _ => return false,
+    };
 
     let price_diff_suitable = price_diff <= max_price_diff;
     let min_spread_suitable = market.spread <= min_spread;
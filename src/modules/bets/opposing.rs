@@ -1,38 +1,49 @@
-use core::f64;
 use std::{cmp::min, sync::Arc, time::Duration};
 
 use alloy::{
-    primitives::{utils::format_units, Address, U256},
+    primitives::{Address, U256},
     providers::ProviderBuilder,
 };
 use itertools::Itertools;
 use rand::{seq::SliceRandom, thread_rng};
 use reqwest::{Proxy, Url};
+use rust_decimal::Decimal;
 use tokio::task::JoinSet;
 
 use crate::{
     config::Config,
     db::{account::Account, database::Database},
-    modules::registration::create_or_derive_api_key,
+    modules::{fills::record_user_fills, registration::get_or_create_api_key},
     onchain::{multicall::multicall_balance_of, types::token::Token},
     polymarket::api::{
         clob::{
             endpoints::{get_neg_risk, get_order_book, place_order},
             math::calculate_market_price,
             order_builder::OrderBuilder,
-            schemas::{OrderRequest, OrderType, PlaceOrderResponseBody},
+            schemas::{OrderBookData, OrderRequest, OrderType, PlaceOrderResponseBody},
             typedefs::{
-                CreateOrderOptions, Side, SignedOrder, TickSize, UserMarketOrder, UserOrder,
+                CreateOrderOptions, PriceProtection, Side, SignedOrder, TickSize, UserMarketOrder,
+                UserOrder,
             },
+            websocket::{market::MarketStream, user::UserStream},
         },
         events::schemas::Event,
         user::{endpoints::get_user_positions, schemas::UserPosition},
     },
-    utils::misc::random_in_range,
+    utils::{
+        dry_run,
+        misc::random_in_range,
+        notifier::{notify, NotificationKind},
+    },
 };
 
 use super::events_monitor::get_filtered_events;
 
+// How long to wait for a live snapshot before falling back to the REST book
+const LIVE_BOOK_TIMEOUT: Duration = Duration::from_secs(2);
+const USER_STREAM_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const FILL_TIMEOUT: Duration = Duration::from_secs(20);
+
 pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
     let proxy = db.0.first().and_then(|account| account.proxy());
 
@@ -55,8 +66,26 @@ pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
             .on_http(Url::parse(&config.polygon_rpc_url)?),
     );
 
-    let bet_amounts = multicall_balance_of(&addresses, Token::USDCE, provider)
-        .await?
+    let balances = multicall_balance_of(&addresses, Token::USDCE, provider).await?;
+
+    for (address, balance) in addresses.iter().zip(&balances) {
+        let balance = Token::USDCE.to_amount(*balance)?;
+
+        if balance < config.low_balance_threshold {
+            tracing::warn!("{address} | Proxy wallet balance is {balance} USDC.e");
+            notify(
+                NotificationKind::LowBalance,
+                "Low proxy wallet balance",
+                format!(
+                    "{address} holds {balance} USDC.e, below the {} USDC.e threshold",
+                    config.low_balance_threshold
+                ),
+            )
+            .await;
+        }
+    }
+
+    let bet_amounts = balances
         .chunks(2)
         .map(|pair| min(pair[0], pair[1]))
         .map(|max_bet| {
@@ -65,6 +94,16 @@ pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
         })
         .collect_vec();
 
+    // Orders are priced from the live books, the REST book is only fetched while a live one is
+    // not available
+    let market_stream = Arc::new(MarketStream::subscribe(
+        filtered_events
+            .iter()
+            .filter_map(|event| event.markets.first())
+            .flat_map(|market| market.clob_token_ids.clone())
+            .collect(),
+    ));
+
     let mut handles = JoinSet::new();
 
     for _ in 0..config.cycle_count {
@@ -72,6 +111,8 @@ pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
             let event = filtered_events.choose(&mut thread_rng()).unwrap().clone();
             let spawn_delay = random_in_range(config.batch_delay_range);
             let sell_delay_range = config.sell_delay_range;
+            let price_protection = config.price_protection();
+            let market_stream = market_stream.clone();
 
             let first_account = accounts_pair[0].clone();
             let second_account = accounts_pair[1].clone();
@@ -86,6 +127,8 @@ pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
                     event,
                     amount,
                     sell_delay_range,
+                    price_protection,
+                    &market_stream,
                 )
                 .await
             });
@@ -96,13 +139,28 @@ pub async fn opposing_bets(db: Database, config: &Config) -> eyre::Result<()> {
 
             match result {
                 Ok(pair) => tracing::info!("Pair {pair} is finished"),
-                Err(e) => tracing::error!("Unexpected error during placing opposing bets: {e}"),
+                Err(e) => {
+                    tracing::error!("Unexpected error during placing opposing bets: {e}");
+                    notify(
+                        NotificationKind::AccountFailed,
+                        "Opposing bets failed",
+                        e.to_string(),
+                    )
+                    .await;
+                }
             }
         }
 
-        db.update();
+        db.update()?;
     }
 
+    notify(
+        NotificationKind::ModuleCompleted,
+        "Opposing bets finished",
+        format!("{} cycles on {} accounts", config.cycle_count, db.0.len()),
+    )
+    .await;
+
     Ok(())
 }
 
@@ -112,6 +170,8 @@ async fn place_opposing_bets_with_timeout(
     event: Event,
     amount: U256,
     sell_delay_range: [u64; 2],
+    price_protection: PriceProtection,
+    market_stream: &MarketStream,
 ) -> eyre::Result<String> {
     tracing::info!(
         "{} - {} | Event chosen: {event}",
@@ -129,27 +189,60 @@ async fn place_opposing_bets_with_timeout(
 
     let tick_size = TickSize::from_str(&market.order_price_min_tick_size.to_string()).unwrap();
 
-    let float_amount = format_units(amount, "mwei")?.parse::<f64>()?;
+    let usdc_amount = Token::USDCE.to_amount(amount)?;
+
+    let (first_user_stream, second_user_stream) = tokio::join!(
+        open_user_stream(&first_account),
+        open_user_stream(&second_account)
+    );
 
     let futures_results = tokio::join!(
         create_and_place_buy_market_order(
             &first_account,
             token_ids[0],
             &event,
-            float_amount,
-            tick_size
+            usdc_amount,
+            tick_size,
+            &price_protection,
+            Some(market_stream)
         ),
         create_and_place_buy_market_order(
             &second_account,
             token_ids[1],
             &event,
-            float_amount,
-            tick_size
+            usdc_amount,
+            tick_size,
+            &price_protection,
+            Some(market_stream)
         ),
     );
 
+    // Simulated orders never fill, the sells that would follow them are reported instead
+    if dry_run::is_enabled() {
+        for (account, token_id, result) in [
+            (&first_account, token_ids[0], &futures_results.0),
+            (&second_account, token_ids[1], &futures_results.1),
+        ] {
+            match result {
+                Ok(_) => dry_run::record(
+                    &account.proxy_address,
+                    "Sell position",
+                    format!("token {token_id} once the buy order fills"),
+                ),
+                Err(e) => {
+                    tracing::error!("{} | Failed to place an order: {e}", account.proxy_address)
+                }
+            }
+        }
+
+        return Ok(format!(
+            "{} - {}",
+            first_account.proxy_address, second_account.proxy_address
+        ));
+    }
+
     match futures_results {
-        (Ok(_), Err(e)) => {
+        (Ok(order), Err(e)) => {
             tracing::info!(
                 "{} - {} | Failed to place an order: {e}. Selling position on {}", // first succeeded, second failed -> sell the position on the first account
                 first_account.proxy_address,
@@ -157,9 +250,20 @@ async fn place_opposing_bets_with_timeout(
                 first_account.proxy_address,
             );
 
-            create_and_place_sell_market_order(&first_account, token_ids[0], tick_size).await?;
+            let filled_size =
+                wait_for_filled_size(&first_account, first_user_stream.as_ref(), &order).await;
+
+            create_and_place_sell_market_order(
+                &first_account,
+                token_ids[0],
+                tick_size,
+                &price_protection,
+                Some(market_stream),
+                filled_size,
+            )
+            .await?;
         }
-        (Err(e), Ok(_)) => {
+        (Err(e), Ok(order)) => {
             tracing::info!(
                 "{} - {} | Failed to place an order: {e}. Selling position on {}", // second succeeded, first failed -> sell the position on the second account
                 first_account.proxy_address,
@@ -167,9 +271,25 @@ async fn place_opposing_bets_with_timeout(
                 second_account.proxy_address,
             );
 
-            create_and_place_sell_market_order(&second_account, token_ids[1], tick_size).await?;
+            let filled_size =
+                wait_for_filled_size(&second_account, second_user_stream.as_ref(), &order).await;
+
+            create_and_place_sell_market_order(
+                &second_account,
+                token_ids[1],
+                tick_size,
+                &price_protection,
+                Some(market_stream),
+                filled_size,
+            )
+            .await?;
         }
-        (Ok(_), Ok(_)) => {
+        (Ok(first_order), Ok(second_order)) => {
+            let (first_filled_size, second_filled_size) = tokio::join!(
+                wait_for_filled_size(&first_account, first_user_stream.as_ref(), &first_order),
+                wait_for_filled_size(&second_account, second_user_stream.as_ref(), &second_order),
+            );
+
             let delay = Duration::from_secs(random_in_range(sell_delay_range)); // both landed, then sleep for random delay and sell
 
             tracing::info!(
@@ -181,8 +301,22 @@ async fn place_opposing_bets_with_timeout(
             tokio::time::sleep(delay).await;
 
             let _ = tokio::join!(
-                create_and_place_sell_market_order(&first_account, token_ids[0], tick_size),
-                create_and_place_sell_market_order(&second_account, token_ids[1], tick_size),
+                create_and_place_sell_market_order(
+                    &first_account,
+                    token_ids[0],
+                    tick_size,
+                    &price_protection,
+                    Some(market_stream),
+                    first_filled_size
+                ),
+                create_and_place_sell_market_order(
+                    &second_account,
+                    token_ids[1],
+                    tick_size,
+                    &price_protection,
+                    Some(market_stream),
+                    second_filled_size
+                ),
             );
         }
         _ => {
@@ -204,21 +338,21 @@ pub async fn create_and_place_sell_market_order(
     account: &Account,
     token_id: &str,
     tick_size: TickSize,
+    price_protection: &PriceProtection,
+    market_stream: Option<&MarketStream>,
+    filled_size: Option<Decimal>,
 ) -> eyre::Result<PlaceOrderResponseBody> {
-    let signed_order =
-        build_market_sell_signed_order_for_account(account, token_id, tick_size).await?;
-
-    let api_key = {
-        let maybe_key = account.api_key.read().unwrap().clone();
-        if let Some(key) = maybe_key {
-            key
-        } else {
-            let response =
-                create_or_derive_api_key(account.signer(), account.proxy().as_ref()).await?;
-            account.update_credentials(response);
-            account.api_key.read().unwrap().as_ref().unwrap().clone()
-        }
-    };
+    let signed_order = build_market_sell_signed_order_for_account(
+        account,
+        token_id,
+        tick_size,
+        price_protection,
+        market_stream,
+        filled_size,
+    )
+    .await?;
+
+    let api_key = get_or_create_api_key(account).await?;
 
     let order_request = OrderRequest::new(signed_order, &api_key, Some(OrderType::Gtc));
 
@@ -233,24 +367,23 @@ async fn create_and_place_buy_market_order(
     account: &Account,
     token_id: &str,
     event: &Event,
-    amount_in: f64,
+    amount_in: Decimal,
     tick_size: TickSize,
+    price_protection: &PriceProtection,
+    market_stream: Option<&MarketStream>,
 ) -> eyre::Result<PlaceOrderResponseBody> {
-    let signed_order =
-        build_market_buy_signed_order_for_account(account, token_id, event, amount_in, tick_size)
-            .await?;
+    let signed_order = build_market_buy_signed_order_for_account(
+        account,
+        token_id,
+        event,
+        amount_in,
+        tick_size,
+        price_protection,
+        market_stream,
+    )
+    .await?;
 
-    let api_key = {
-        let maybe_key = account.api_key.read().unwrap().clone();
-        if let Some(key) = maybe_key {
-            key
-        } else {
-            let response =
-                create_or_derive_api_key(account.signer(), account.proxy().as_ref()).await?;
-            account.update_credentials(response);
-            account.api_key.read().unwrap().as_ref().unwrap().clone()
-        }
-    };
+    let api_key = get_or_create_api_key(account).await?;
 
     let order_request = OrderRequest::new(signed_order, &api_key, None);
 
@@ -265,12 +398,14 @@ async fn build_market_buy_signed_order_for_account(
     account: &Account,
     token_id: &str,
     event: &Event,
-    amount_in: f64,
+    amount_in: Decimal,
     tick_size: TickSize,
+    price_protection: &PriceProtection,
+    market_stream: Option<&MarketStream>,
 ) -> eyre::Result<SignedOrder> {
-    let build_order_args = |price: f64,
+    let build_order_args = |price: Decimal,
                             token_id: String,
-                            amount: f64,
+                            amount: Decimal,
                             tick_size: TickSize,
                             neg_risk: bool|
      -> (CreateOrderOptions, UserMarketOrder) {
@@ -283,8 +418,8 @@ async fn build_market_buy_signed_order_for_account(
     let proxy_wallet_address = account.get_proxy_address().to_string();
     let proxy = account.proxy();
 
-    let order_book = get_order_book(token_id, proxy.as_ref()).await?;
-    let market_price = calculate_market_price(Side::Buy, order_book, amount_in, None);
+    let order_book = get_latest_order_book(token_id, proxy.as_ref(), market_stream).await?;
+    let market_price = calculate_market_price(Side::Buy, order_book, amount_in, price_protection)?;
 
     let order_builder = OrderBuilder::new(account.signer(), 137, None, Some(&proxy_wallet_address));
 
@@ -311,26 +446,41 @@ async fn build_market_sell_signed_order_for_account(
     account: &Account,
     token_id: &str,
     tick_size: TickSize,
+    price_protection: &PriceProtection,
+    market_stream: Option<&MarketStream>,
+    filled_size: Option<Decimal>,
 ) -> eyre::Result<SignedOrder> {
     let proxy = account.proxy();
     let proxy_wallet_address = account.get_proxy_address().to_string();
     let order_builder = OrderBuilder::new(account.signer(), 137, None, Some(&proxy_wallet_address));
 
-    let position =
-        wait_for_matching_user_position(&proxy_wallet_address, proxy.clone(), token_id, None)
+    // The positions endpoint lags behind the fills, it is only polled when the fill is unknown
+    let (size, neg_risk) = match filled_size {
+        Some(size) => (size, get_neg_risk(token_id, proxy.as_ref()).await?),
+        None => {
+            let position = wait_for_matching_user_position(
+                &proxy_wallet_address,
+                proxy.clone(),
+                token_id,
+                None,
+            )
             .await?;
 
-    let order_book = get_order_book(token_id, proxy.as_ref()).await?;
-    let market_price = calculate_market_price(Side::Sell, order_book, position.size, None);
+            (position.size, position.negative_risk)
+        }
+    };
+
+    let order_book = get_latest_order_book(token_id, proxy.as_ref(), market_stream).await?;
+    let market_price = calculate_market_price(Side::Sell, order_book, size, price_protection)?;
 
     let order = UserOrder::default()
         .with_token_id(token_id)
         .with_price(market_price)
         .with_side(Side::Sell)
-        .with_size(position.size)
+        .with_size(size)
         .with_taker(Address::ZERO.to_string());
 
-    let order_options = CreateOrderOptions::new(tick_size, Some(position.negative_risk));
+    let order_options = CreateOrderOptions::new(tick_size, Some(neg_risk));
 
     let signed_order = order_builder
         .build_signed_order(order, order_options)
@@ -339,6 +489,83 @@ async fn build_market_sell_signed_order_for_account(
     Ok(signed_order)
 }
 
+// Fills are awaited on the user channel, `None` leaves the sell to poll the positions instead
+async fn open_user_stream(account: &Account) -> Option<UserStream> {
+    if dry_run::is_enabled() {
+        return None;
+    }
+
+    if let Err(e) = get_or_create_api_key(account).await {
+        tracing::warn!(
+            "{} | Failed to get API credentials: {e}",
+            account.proxy_address
+        );
+        return None;
+    }
+
+    let user_stream = match UserStream::subscribe(account) {
+        Ok(user_stream) => user_stream,
+        Err(e) => {
+            tracing::warn!(
+                "{} | Failed to open the user stream: {e}",
+                account.proxy_address
+            );
+            return None;
+        }
+    };
+
+    record_user_fills(&user_stream);
+
+    if !user_stream
+        .wait_until_connected(USER_STREAM_CONNECT_TIMEOUT)
+        .await
+    {
+        tracing::warn!(
+            "{} | User stream is not connected yet, fills may be missed",
+            account.proxy_address
+        );
+    }
+
+    Some(user_stream)
+}
+
+async fn wait_for_filled_size(
+    account: &Account,
+    user_stream: Option<&UserStream>,
+    order: &PlaceOrderResponseBody,
+) -> Option<Decimal> {
+    let (Some(user_stream), Some(order_id)) = (user_stream, order.order_id.as_deref()) else {
+        return None;
+    };
+
+    match user_stream.wait_for_fill(order_id, FILL_TIMEOUT).await {
+        Ok(trade) => Some(trade.filled_size(order_id)),
+        Err(e) => {
+            tracing::warn!(
+                "{} | {e}, falling back to the positions endpoint",
+                account.proxy_address
+            );
+            None
+        }
+    }
+}
+
+async fn get_latest_order_book(
+    token_id: &str,
+    proxy: Option<&Proxy>,
+    market_stream: Option<&MarketStream>,
+) -> eyre::Result<OrderBookData> {
+    let live_order_book = match market_stream {
+        Some(stream) => stream.wait_for_book(token_id, LIVE_BOOK_TIMEOUT).await,
+        None => None,
+    };
+
+    match live_order_book {
+        Some(order_book) => Ok(order_book),
+        None => Ok(get_order_book(token_id, proxy).await?),
+    }
+}
+
 async fn wait_for_matching_user_position(
     proxy_wallet_address: &str,
     proxy: Option<Proxy>,
@@ -376,3 +603,100 @@ async fn wait_for_matching_user_position(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{
+        errors::price_protection::PriceProtectionError,
+        testing::mock_server::{mock_server, random_account},
+    };
+
+    const TOKEN_ID: &str =
+        "48331043336612883890938759509493159234755048973500640148014422747788308965732";
+
+    const PRICE_PROTECTION: PriceProtection = PriceProtection {
+        max_slippage_bps: 500,
+        max_buy_price: dec!(0.99),
+        min_sell_price: dec!(0.01),
+    };
+
+    #[tokio::test]
+    async fn test_sell_waits_for_position_sync_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+
+        mock.add_position(proxy_wallet, TOKEN_ID, 25.0);
+        mock.delay_positions(proxy_wallet, 1);
+
+        let response = create_and_place_sell_market_order(
+            &account,
+            TOKEN_ID,
+            TickSize::OneHundredth,
+            &PRICE_PROTECTION,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.making_amount.as_deref(), Some("25000000"));
+        assert!(mock.positions(proxy_wallet).is_empty());
+
+        let orders = mock.orders(proxy_wallet);
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0]["orderType"], "GTC");
+        assert_eq!(orders[0]["order"]["side"], "SELL");
+        assert_eq!(orders[0]["order"]["tokenId"], TOKEN_ID);
+    }
+
+    #[tokio::test]
+    async fn test_sell_uses_filled_size_without_polling_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+
+        let response = create_and_place_sell_market_order(
+            &account,
+            TOKEN_ID,
+            TickSize::OneHundredth,
+            &PRICE_PROTECTION,
+            None,
+            Some(dec!(18.5)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.making_amount.as_deref(), Some("18500000"));
+        assert_eq!(mock.orders(proxy_wallet).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sell_is_refused_on_thin_book_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+
+        mock.add_position(proxy_wallet, TOKEN_ID, 5000.0);
+
+        let error = create_and_place_sell_market_order(
+            &account,
+            TOKEN_ID,
+            TickSize::OneHundredth,
+            &PRICE_PROTECTION,
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(PriceProtectionError::InsufficientDepth { .. })
+        ));
+        assert!(mock.orders(proxy_wallet).is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use chrono::{DateTime, Utc};
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use itertools::Itertools;
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::polymarket::api::events::{
+    endpoints::{EventPages, EventStatus, EventsQuery},
+    schemas::{Event, Market},
+};
+
+#[derive(Tabled)]
+struct EventEntry {
+    #[tabled(rename = "#")]
+    index: usize,
+
+    #[tabled(rename = "Title")]
+    title: String,
+
+    #[tabled(rename = "Slug")]
+    slug: String,
+
+    #[tabled(rename = "Status")]
+    status: &'static str,
+
+    #[tabled(rename = "End Date")]
+    end_date: String,
+
+    #[tabled(rename = "Volume")]
+    volume: String,
+
+    #[tabled(rename = "24h Volume")]
+    volume_24hr: String,
+
+    #[tabled(rename = "Liquidity")]
+    liquidity: String,
+
+    #[tabled(rename = "Markets")]
+    markets: usize,
+
+    #[tabled(rename = "Tags")]
+    tags: String,
+}
+
+#[derive(Tabled)]
+struct MarketEntry {
+    #[tabled(rename = "Question")]
+    question: String,
+
+    #[tabled(rename = "Status")]
+    status: &'static str,
+
+    #[tabled(rename = "Outcomes")]
+    outcomes: String,
+
+    #[tabled(rename = "Bid / Ask")]
+    bid_ask: String,
+
+    #[tabled(rename = "Volume")]
+    volume: String,
+
+    #[tabled(rename = "Liquidity")]
+    liquidity: String,
+
+    #[tabled(rename = "End Date")]
+    end_date: String,
+
+    #[tabled(rename = "Condition ID")]
+    condition_id: String,
+
+    #[tabled(rename = "Token IDs")]
+    token_ids: String,
+}
+
+// Prints one page of events, with the markets of every event when `show_markets` is set
+pub async fn browse_events(query: EventsQuery, page: u64, show_markets: bool) -> eyre::Result<()> {
+    let mut pages = EventPages::new(query, page, None);
+
+    let Some(events) = pages.next_page().await? else {
+        tracing::info!("No events found");
+        return Ok(());
+    };
+
+    display_events(&events);
+
+    if show_markets {
+        for event in &events {
+            println!("{event}");
+            display_markets(&event.markets);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn browse_events_interactive() -> eyre::Result<()> {
+    let theme = ColorfulTheme::default();
+
+    let text: String = Input::with_theme(&theme)
+        .with_prompt("Search (empty for the most traded events)")
+        .allow_empty(true)
+        .interact_text()?;
+
+    let status = Select::with_theme(&theme)
+        .with_prompt("Status:")
+        .items(&["Active", "Closed", "All"])
+        .default(0)
+        .interact()?;
+
+    let query = EventsQuery {
+        text: Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        status: match status {
+            0 => EventStatus::Active,
+            1 => EventStatus::Closed,
+            _ => EventStatus::All,
+        },
+        ..Default::default()
+    };
+
+    let mut pages = EventPages::new(query, 0, None);
+
+    loop {
+        let Some(events) = pages.next_page().await? else {
+            tracing::info!("No more events");
+            return Ok(());
+        };
+
+        // The page number already points past the returned page, which may have skipped some
+        let page = pages.page_number();
+
+        println!("Page {page}");
+        display_events(&events);
+
+        loop {
+            let action = Select::with_theme(&theme)
+                .with_prompt("Action:")
+                .items(&["Next page", "Event markets", "Back"])
+                .default(0)
+                .interact()?;
+
+            match action {
+                0 => break,
+                1 => {
+                    let titles = events.iter().map(|event| &event.title).collect_vec();
+                    let index = Select::with_theme(&theme)
+                        .with_prompt("Event:")
+                        .items(&titles)
+                        .default(0)
+                        .interact()?;
+
+                    println!("{}", events[index]);
+                    if let Some(description) = &events[index].description {
+                        println!("{description}");
+                    }
+                    display_markets(&events[index].markets);
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn display_events(events: &[Event]) {
+    let entries = events
+        .iter()
+        .enumerate()
+        .map(|(index, event)| EventEntry {
+            index,
+            title: event.title.clone(),
+            slug: event.slug.clone(),
+            status: event.status(),
+            end_date: format_date(event.end_date),
+            volume: format_amount(event.volume),
+            volume_24hr: format_amount(event.volume_24hr),
+            liquidity: format_amount(event.liquidity),
+            markets: event.markets.len(),
+            tags: event
+                .tags
+                .iter()
+                .filter_map(|tag| tag.label.as_deref())
+                .join(", "),
+        })
+        .collect_vec();
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+}
+
+fn display_markets(markets: &[Market]) {
+    let entries = markets
+        .iter()
+        .map(|market| MarketEntry {
+            question: market.question.clone(),
+            status: market.status(),
+            outcomes: format_outcomes(market),
+            bid_ask: match (market.best_bid, market.best_ask) {
+                (Some(bid), Some(ask)) => format!("{bid} / {ask}"),
+                _ => "-".to_string(),
+            },
+            volume: format_amount(market.volume),
+            liquidity: format_amount(market.liquidity),
+            end_date: format_date(market.end_date),
+            condition_id: market.condition_id.clone().unwrap_or_default(),
+            token_ids: market.clob_token_ids.join("\n"),
+        })
+        .collect_vec();
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+}
+
+// Each outcome on its own line with its price when Gamma has one
+fn format_outcomes(market: &Market) -> String {
+    market
+        .outcomes
+        .iter()
+        .enumerate()
+        .map(|(index, outcome)| {
+            match market
+                .outcome_prices
+                .as_ref()
+                .and_then(|prices| prices.get(index))
+            {
+                Some(price) => format!("{outcome} {price}"),
+                None => outcome.clone(),
+            }
+        })
+        .join("\n")
+}
+
+fn format_amount(amount: Option<f64>) -> String {
+    amount.map_or("-".to_string(), |amount| format!("{amount:.2}"))
+}
+
+fn format_date(date: Option<DateTime<Utc>>) -> String {
+    date.map_or("-".to_string(), |date| {
+        date.format("%Y-%m-%d %H:%M").to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS: &str = include_str!("../../testing/fixtures/gamma_events.json");
+
+    #[test]
+    fn test_market_columns() {
+        let events = serde_json::from_str::<Vec<Event>>(EVENTS).unwrap();
+
+        let market = &events[0].markets[0];
+        assert_eq!(format_outcomes(market), "Yes 0.615\nNo 0.385");
+        assert_eq!(format_amount(market.liquidity), "95310.40");
+        assert_eq!(format_date(events[0].end_date), "2024-12-18 12:00");
+
+        let market = &events[1].markets[2];
+        assert_eq!(format_outcomes(market), "Under 2 0\n2-4 1\nOver 4 0");
+        assert_eq!(format_amount(market.volume), "-");
+    }
+}
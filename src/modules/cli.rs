@@ -0,0 +1,686 @@
+use alloy::primitives::Address;
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dialoguer::{theme::ColorfulTheme, Password};
+use rust_decimal::Decimal;
+
+use super::{
+    accounts::{display_accounts, tag_accounts},
+    bets::opposing::opposing_bets,
+    browse::browse_events,
+    deposit::deposit_to_accounts,
+    keys::{
+        add_account, change_keystore_password, export_accounts, import_private_keys, remove_account,
+    },
+    menu::{menu, read_data_from_txt, read_db},
+    orders::limit::{display_placed_orders, place_limit_orders, LimitOrderParams},
+    redeem::redeem_for_all,
+    registration::register_accounts,
+    sell::sell_all::sell_all_open_positions,
+    stats_check::{
+        check_and_display_stats_from_db, check_and_display_stats_from_text,
+        get_proxy_address_from_txt, pnl::display_pnl_report,
+    },
+    watch::watch_markets,
+    withdraw::withdraw_for_all,
+};
+use crate::{
+    config::{Config, CONFIG_FILE_PATH},
+    db::{
+        constants::{ADDRESS_FILE_PATH, KEYSTORES_DIR_PATH, PROXY_ADDRESS_FILE_PATH},
+        crypto::encryption_password,
+        database::Database,
+        migrate::migrate_encryption,
+        selection::{Selection, SELECTION_HELP},
+        source::{AccountSource, DEFAULT_DERIVATION_PATH},
+        storage::{import_json_into_sqlite, init_storage},
+    },
+    polymarket::api::{
+        clob::typedefs::Side,
+        events::endpoints::{EventStatus, EventsQuery, DEFAULT_PAGE_SIZE},
+        urls::set_api_urls,
+    },
+    utils::{dry_run, notifier::init_notifiers},
+};
+
+const DEFAULT_PASSWORD_ENV: &str = "POLYMARKET_BOT_PASSWORD";
+
+/// Polymarket multi-account bot. Runs the interactive menu when no subcommand is given.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the config file
+    #[arg(long, global = true, default_value = CONFIG_FILE_PATH)]
+    config: String,
+
+    /// Config profile from the `[profiles.<name>]` tables layered over the base values
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Environment variable holding the database password
+    #[arg(long, global = true, default_value = DEFAULT_PASSWORD_ENV)]
+    password_env: String,
+
+    /// File descriptor to read the database password from, takes precedence over the environment.
+    /// Unix only, rejected on Windows
+    #[arg(long, global = true)]
+    password_fd: Option<u32>,
+
+    /// Override a config value, e.g. `--set DEPOSIT_SLEEP_RANGE=[5,10]`
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+
+    /// Override POLYGON_RPC_URL
+    #[arg(long, global = true)]
+    rpc_url: Option<String>,
+
+    /// Simulate transactions and print signed requests instead of sending them, same as `DRY_RUN = true`
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Run on the matching accounts only, e.g. `--select "tag:main !funded"`
+    #[arg(long, global = true, value_name = "EXPR", long_help = format!(
+        "Run on the matching accounts only, e.g. `--select \"tag:main !funded\"`.\n\nExpressions: {SELECTION_HELP}"
+    ))]
+    select: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create the database from an account source and register the accounts
+    Register {
+        #[command(flatten)]
+        source: SourceArgs,
+    },
+    /// Proxy wallets stats check
+    Stats {
+        /// Read proxy addresses from a txt file instead of the database
+        #[arg(long, num_args = 0..=1, default_missing_value = PROXY_ADDRESS_FILE_PATH)]
+        from_txt: Option<String>,
+    },
+    /// Realized and unrealized P&L report from recorded fills
+    Pnl,
+    /// USDC deposit to proxy wallets
+    Deposit,
+    /// Opposing bets
+    Opposing,
+    /// Sell all open positions
+    SellAll,
+    /// Withdraw proxy wallet balances
+    Withdraw,
+    /// Redeem resolved positions
+    Redeem,
+    /// Place a limit order on every account
+    LimitOrder {
+        #[arg(long)]
+        token_id: String,
+        #[arg(long, value_enum)]
+        side: OrderSide,
+        #[arg(long)]
+        price: Decimal,
+        /// Size in shares
+        #[arg(long)]
+        size: Decimal,
+        /// Expiration in hours, good-till-cancelled when omitted
+        #[arg(long)]
+        expiration_hours: Option<u64>,
+    },
+    /// Limit orders placed by the bot
+    Orders,
+    /// Search Gamma events and print them as tables
+    Events {
+        /// Free text search
+        #[arg(long)]
+        search: Option<String>,
+        /// Tag slug, e.g. `politics`
+        #[arg(long)]
+        tag: Option<String>,
+        /// Event slug
+        #[arg(long)]
+        slug: Option<String>,
+        /// Events ending on or after this date, e.g. `2024-12-01`
+        #[arg(long)]
+        ends_after: Option<NaiveDate>,
+        /// Events ending on or before this date
+        #[arg(long)]
+        ends_before: Option<NaiveDate>,
+        #[arg(long, value_enum, default_value_t = EventStatusArg::Active)]
+        status: EventStatusArg,
+        /// Include archived events
+        #[arg(long)]
+        archived: bool,
+        /// Events per page
+        #[arg(long, default_value_t = DEFAULT_PAGE_SIZE)]
+        limit: u64,
+        /// Page number, starting from 0
+        #[arg(long, default_value_t = 0)]
+        page: u64,
+        /// Print the markets of every event
+        #[arg(long)]
+        markets: bool,
+    },
+    /// Follow the `WATCHLIST` markets and alert on price, spread and volume thresholds
+    Watch,
+    /// Manage the encrypted private keys file
+    Keys {
+        #[command(subcommand)]
+        command: KeysCommand,
+    },
+    /// Re-encrypt legacy private keys in the key file and the database with the current format
+    MigrateKeys {
+        /// Environment variable holding a new password to re-encrypt every key with
+        #[arg(long)]
+        new_password_env: Option<String>,
+    },
+    /// Import db.json and fills.json into the SQLite database
+    ImportJson,
+    /// List the accounts with their database index and tags
+    Accounts,
+    /// Add or remove tags on the selected accounts
+    Tag {
+        #[arg(long)]
+        add: Vec<String>,
+        #[arg(long)]
+        remove: Vec<String>,
+    },
+    /// Get proxy addresses for the owner addresses in a txt file
+    ProxyAddress {
+        #[arg(long, default_value = ADDRESS_FILE_PATH)]
+        file: String,
+    },
+}
+
+#[derive(Args, Debug)]
+struct SourceArgs {
+    /// Where the account private keys come from
+    #[arg(long, value_enum, default_value_t = SourceKind::EncryptedKeys)]
+    source: SourceKind,
+
+    /// Directory of V3 JSON keystores
+    #[arg(long, default_value = KEYSTORES_DIR_PATH)]
+    keystore_dir: String,
+
+    /// Environment variable holding the V3 keystores password, the database password is used by default
+    #[arg(long)]
+    keystore_password_env: Option<String>,
+
+    /// Environment variable holding the BIP-39 mnemonic, it is prompted for otherwise
+    #[arg(long)]
+    mnemonic_env: Option<String>,
+
+    /// BIP-44 derivation path, the account index is appended to it
+    #[arg(long, default_value = DEFAULT_DERIVATION_PATH)]
+    derivation_path: String,
+
+    /// First derivation index
+    #[arg(long, default_value_t = 0)]
+    start_index: u32,
+
+    /// Number of accounts to derive from the mnemonic
+    #[arg(long, default_value_t = 1)]
+    count: u32,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SourceKind {
+    EncryptedKeys,
+    KeystoreDir,
+    Mnemonic,
+}
+
+impl SourceArgs {
+    fn into_source(self, aes_key: Option<&str>) -> eyre::Result<AccountSource> {
+        Ok(match self.source {
+            SourceKind::EncryptedKeys => AccountSource::EncryptedKeys,
+            SourceKind::KeystoreDir => AccountSource::KeystoreDir {
+                path: self.keystore_dir.into(),
+                password: match self.keystore_password_env {
+                    Some(var) => read_env_var(&var)?,
+                    None => aes_key
+                        .ok_or_else(|| eyre::eyre!("Set the V3 keystores password"))?
+                        .to_string(),
+                },
+            },
+            SourceKind::Mnemonic => AccountSource::Mnemonic {
+                phrase: match self.mnemonic_env {
+                    Some(var) => read_env_var(&var)?,
+                    None => Password::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Mnemonic")
+                        .interact()?,
+                },
+                derivation_path: self.derivation_path,
+                start: self.start_index,
+                count: self.count,
+            },
+        })
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum KeysCommand {
+    /// Encrypt a plaintext private key list (one per line) into the keystore
+    Import {
+        #[arg(long)]
+        file: String,
+    },
+    /// Add a single account, the private key is prompted for unless `--key-env` is given
+    Add {
+        /// Environment variable holding the private key
+        #[arg(long)]
+        key_env: Option<String>,
+    },
+    /// Remove a single account along with its proxy and recipient lines
+    Remove { address: Address },
+    /// Re-encrypt every key in the keystore and the database with a new password
+    ChangePassword {
+        /// Environment variable holding the new password
+        #[arg(long)]
+        new_password_env: String,
+    },
+    /// Export accounts as Ethereum V3 JSON keystores, every account when no address is given
+    Export {
+        #[arg(long = "address")]
+        addresses: Vec<Address>,
+        #[arg(long, default_value = KEYSTORES_DIR_PATH)]
+        out_dir: String,
+        /// Environment variable holding the keystore password, the database password is used by default
+        #[arg(long)]
+        export_password_env: Option<String>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl From<OrderSide> for Side {
+    fn from(side: OrderSide) -> Self {
+        match side {
+            OrderSide::Buy => Side::Buy,
+            OrderSide::Sell => Side::Sell,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum EventStatusArg {
+    Active,
+    Closed,
+    All,
+}
+
+impl From<EventStatusArg> for EventStatus {
+    fn from(status: EventStatusArg) -> Self {
+        match status {
+            EventStatusArg::Active => EventStatus::Active,
+            EventStatusArg::Closed => EventStatus::Closed,
+            EventStatusArg::All => EventStatus::All,
+        }
+    }
+}
+
+fn read_env_var(var: &str) -> eyre::Result<String> {
+    std::env::var(var).map_err(|_| eyre::eyre!("Environment variable `{var}` is not set"))
+}
+
+fn parse_override(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{arg}`"))
+}
+
+#[cfg(unix)]
+fn read_password_fd(fd: u32) -> eyre::Result<String> {
+    use std::{io::Read, os::fd::FromRawFd};
+
+    let fd = i32::try_from(fd).map_err(|_| eyre::eyre!("Invalid --password-fd {fd}"))?;
+    // SAFETY: the descriptor is inherited from the parent process and nothing else in the process
+    // uses it, it is closed once the password is read
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+    let mut password = String::new();
+    file.read_to_string(&mut password)
+        .map_err(|e| eyre::eyre!("Failed to read the password from --password-fd {fd}: {e}"))?;
+
+    Ok(password)
+}
+
+#[cfg(not(unix))]
+fn read_password_fd(_fd: u32) -> eyre::Result<String> {
+    eyre::bail!(
+        "--password-fd is only supported on Unix, set the password environment variable instead"
+    )
+}
+
+impl Cli {
+    fn read_password(&self) -> eyre::Result<Option<String>> {
+        if let Some(fd) = self.password_fd {
+            let password = read_password_fd(fd)?;
+            return Ok(Some(password.trim_end_matches(['\r', '\n']).to_string()));
+        }
+
+        Ok(std::env::var(&self.password_env).ok())
+    }
+
+    async fn read_config(&self) -> eyre::Result<Config> {
+        let mut overrides = self
+            .overrides
+            .iter()
+            .map(|(key, value)| (key.clone(), Config::parse_override_value(value)))
+            .collect::<Vec<_>>();
+
+        if self.dry_run {
+            overrides.push(("DRY_RUN".to_string(), toml::Value::Boolean(true)));
+        }
+
+        if let Some(rpc_url) = &self.rpc_url {
+            overrides.push((
+                "POLYGON_RPC_URL".to_string(),
+                toml::Value::String(rpc_url.clone()),
+            ));
+        }
+
+        Ok(Config::read_from_file(&self.config, self.profile.as_deref(), &overrides).await?)
+    }
+}
+
+pub async fn run(cli: Cli) -> eyre::Result<()> {
+    let config = cli.read_config().await?;
+    init_storage(config.storage_backend);
+    set_api_urls(config.api_urls());
+    init_notifiers(&config.notifiers, &config.notify_on)?;
+
+    if config.dry_run {
+        dry_run::enable();
+        tracing::warn!("Dry run: nothing will be sent or saved");
+    }
+
+    let password = cli.read_password()?;
+    let selection = Selection::parse_all(&cli.select)?;
+
+    let Some(command) = cli.command else {
+        return menu(config, password).await;
+    };
+
+    let aes_key = password.as_deref().filter(|password| !password.is_empty());
+
+    // These write the key files directly, a dry run would leave them out of sync with the database
+    if dry_run::is_enabled()
+        && matches!(
+            command,
+            Command::Keys { .. } | Command::MigrateKeys { .. } | Command::ImportJson
+        )
+    {
+        eyre::bail!("Dry run is not supported for key and storage management commands");
+    }
+
+    match command {
+        Command::Register { source } => {
+            let db = Database::new_from_source(&source.into_source(aes_key)?, aes_key).await?;
+            register_accounts(db, &config).await?;
+        }
+        Command::Stats { from_txt } => match from_txt {
+            Some(file_path) => {
+                let data = read_data_from_txt(&file_path).await?;
+                check_and_display_stats_from_text(data, &config).await?;
+            }
+            None => {
+                let db = read_selected_db(aes_key, &selection).await?;
+                check_and_display_stats_from_db(db, &config).await?;
+            }
+        },
+        Command::Pnl => {
+            let db = read_selected_db(aes_key, &selection).await?;
+            display_pnl_report(db, &config).await?;
+        }
+        Command::Deposit => {
+            let mut db = read_selected_db(aes_key, &selection).await?;
+            db.shuffle();
+            deposit_to_accounts(db, &config).await?;
+        }
+        Command::Opposing => {
+            let mut db = read_selected_db(aes_key, &selection).await?;
+            db.shuffle();
+            opposing_bets(db, &config).await?;
+        }
+        Command::SellAll => {
+            let db = read_selected_db(aes_key, &selection).await?;
+            sell_all_open_positions(db, &config).await?;
+        }
+        Command::Withdraw => {
+            let mut db = read_selected_db(aes_key, &selection).await?;
+            withdraw_for_all(&mut db, &config).await?;
+        }
+        Command::Redeem => {
+            let db = read_selected_db(aes_key, &selection).await?;
+            redeem_for_all(db, &config).await?;
+        }
+        Command::LimitOrder {
+            token_id,
+            side,
+            price,
+            size,
+            expiration_hours,
+        } => {
+            if price <= Decimal::ZERO || price >= Decimal::ONE {
+                eyre::bail!("Price must be between 0 and 1");
+            }
+
+            if size <= Decimal::ZERO {
+                eyre::bail!("Size must be positive");
+            }
+
+            let params =
+                LimitOrderParams::new(&token_id, side.into(), price, size, expiration_hours);
+            let db = read_selected_db(aes_key, &selection).await?;
+            place_limit_orders(db, &params, &config).await?;
+        }
+        Command::Orders => {
+            let db = read_selected_db(aes_key, &selection).await?;
+            display_placed_orders(&db);
+        }
+        Command::Events {
+            search,
+            tag,
+            slug,
+            ends_after,
+            ends_before,
+            status,
+            archived,
+            limit,
+            page,
+            markets,
+        } => {
+            if limit == 0 {
+                eyre::bail!("Limit must be positive");
+            }
+
+            let query = EventsQuery {
+                text: search,
+                tag,
+                slug,
+                end_date_min: ends_after,
+                end_date_max: ends_before,
+                status: status.into(),
+                include_archived: archived,
+                limit,
+                ..Default::default()
+            };
+            browse_events(query, page, markets).await?;
+        }
+        Command::Watch => watch_markets(&config).await?,
+        Command::Keys { command } => run_keys_command(command, aes_key).await?,
+        Command::MigrateKeys { new_password_env } => {
+            let new_password = new_password_env.as_deref().map(read_env_var).transpose()?;
+
+            migrate_encryption(aes_key, new_password.as_deref()).await?;
+        }
+        Command::ImportJson => import_json_into_sqlite()?,
+        Command::Accounts => {
+            let db = read_db(aes_key).await?;
+            display_accounts(&db, &selection).await?;
+        }
+        Command::Tag { add, remove } => {
+            if add.is_empty() && remove.is_empty() {
+                eyre::bail!("Nothing to do, pass `--add` or `--remove`");
+            }
+
+            let db = read_selected_db(aes_key, &selection).await?;
+            tag_accounts(db, &add, &remove)?;
+        }
+        Command::ProxyAddress { file } => {
+            let data = read_data_from_txt(&file).await?;
+            get_proxy_address_from_txt(data).await?;
+        }
+    }
+
+    if dry_run::is_enabled() {
+        dry_run::display_summary();
+    }
+
+    Ok(())
+}
+
+async fn read_selected_db(aes_key: Option<&str>, selection: &Selection) -> eyre::Result<Database> {
+    read_db(aes_key).await?.select(selection).await
+}
+
+async fn run_keys_command(command: KeysCommand, aes_key: Option<&str>) -> eyre::Result<()> {
+    match command {
+        KeysCommand::Import { file } => import_private_keys(&file, aes_key).await,
+        KeysCommand::Add { key_env } => {
+            let private_key = match key_env {
+                Some(var) => read_env_var(&var)?,
+                None => Password::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Private key")
+                    .interact()?,
+            };
+
+            add_account(&private_key, aes_key).await
+        }
+        KeysCommand::Remove { address } => remove_account(address, aes_key).await,
+        KeysCommand::ChangePassword { new_password_env } => {
+            change_keystore_password(aes_key, &read_env_var(&new_password_env)?).await
+        }
+        KeysCommand::Export {
+            addresses,
+            out_dir,
+            export_password_env,
+        } => {
+            let export_password = match export_password_env {
+                Some(var) => read_env_var(&var)?,
+                None => aes_key.unwrap_or_default().to_string(),
+            };
+
+            if encryption_password(Some(&export_password)).is_err() {
+                eyre::bail!("Set a password for the exported keystores");
+            }
+
+            export_accounts(&addresses, aes_key, &export_password, &out_dir).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+
+        Cli::command().debug_assert();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_password_fd() {
+        use std::os::fd::IntoRawFd;
+
+        let path = std::env::temp_dir().join(format!("password-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "secret\n").unwrap();
+        let fd = std::fs::File::open(&path).unwrap().into_raw_fd();
+
+        assert_eq!(read_password_fd(fd as u32).unwrap(), "secret\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_limit_order_with_overrides() {
+        let cli = Cli::try_parse_from([
+            "polymarket-bot",
+            "limit-order",
+            "--token-id",
+            "123",
+            "--side",
+            "sell",
+            "--price",
+            "0.42",
+            "--size",
+            "10",
+            "--set",
+            "LIMIT_ORDER_DELAY_RANGE=[1, 2]",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            cli.command,
+            Some(Command::LimitOrder {
+                side: OrderSide::Sell,
+                expiration_hours: None,
+                ..
+            })
+        ));
+        assert_eq!(
+            cli.overrides,
+            vec![("LIMIT_ORDER_DELAY_RANGE".to_string(), "[1, 2]".to_string())]
+        );
+        assert_eq!(cli.password_env, DEFAULT_PASSWORD_ENV);
+    }
+
+    #[tokio::test]
+    async fn test_rpc_url_override_is_kept_verbatim() {
+        let rpc_url = r#"http://127.0.0.1:8545/?key=a"b\c"#;
+        let cli = Cli::try_parse_from(["polymarket-bot", "--rpc-url", rpc_url]).unwrap();
+
+        assert_eq!(cli.read_config().await.unwrap().polygon_rpc_url, rpc_url);
+    }
+
+    #[test]
+    fn test_parse_events_filters() {
+        let cli = Cli::try_parse_from([
+            "polymarket-bot",
+            "events",
+            "--tag",
+            "economy",
+            "--ends-before",
+            "2024-12-31",
+            "--status",
+            "all",
+            "--markets",
+        ])
+        .unwrap();
+
+        let Some(Command::Events {
+            tag,
+            ends_before,
+            status: EventStatusArg::All,
+            limit,
+            page: 0,
+            markets: true,
+            ..
+        }) = cli.command
+        else {
+            panic!("expected the events command, got {:?}", cli.command);
+        };
+        assert_eq!(tag.as_deref(), Some("economy"));
+        assert_eq!(ends_before, NaiveDate::from_ymd_opt(2024, 12, 31));
+        assert_eq!(limit, DEFAULT_PAGE_SIZE);
+    }
+}
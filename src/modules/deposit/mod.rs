@@ -8,12 +8,20 @@ use alloy::{
 };
 use alloy_chains::NamedChain;
 use reqwest::Url;
+use rust_decimal::{Decimal, RoundingStrategy};
 
 use crate::{
     config::Config,
-    db::{account::Account, database::Database},
+    db::{
+        account::Account,
+        database::Database,
+        storage::{record_transaction, TransactionRecord},
+    },
     onchain::{client::EvmClient, types::token::Token},
-    utils::misc::{pretty_sleep, random_in_range},
+    utils::{
+        misc::{pretty_sleep, random_in_range},
+        notifier::{notify, NotificationKind},
+    },
 };
 
 pub async fn deposit_to_accounts(mut db: Database, config: &Config) -> eyre::Result<()> {
@@ -23,13 +31,23 @@ pub async fn deposit_to_accounts(mut db: Database, config: &Config) -> eyre::Res
             .on_http(Url::parse(&config.polygon_rpc_url)?),
     );
 
+    let mut funded = 0;
+
     while let Some(account) = db.get_random_account_with_filter(|a| !a.get_funded()) {
         process_account(provider.clone(), account, config).await?;
-        db.update();
+        db.update()?;
+        funded += 1;
 
         pretty_sleep(config.deposit_sleep_range).await;
     }
 
+    notify(
+        NotificationKind::ModuleCompleted,
+        "Deposit finished",
+        format!("{funded} proxy wallets funded"),
+    )
+    .await;
+
     Ok(())
 }
 
@@ -43,8 +61,9 @@ where
     T: Transport + Clone,
 {
     let proxy_wallet_address = account.get_proxy_address();
-    let amount = random_in_range(config.usdc_amount_deposit_range);
     let token = Token::USDCE;
+    let amount = Decimal::try_from(random_in_range(config.usdc_amount_deposit_range))?
+        .round_dp_with_strategy(token.decimals as u32, RoundingStrategy::ToZero);
 
     let client = EvmClient::new(
         provider.clone(),
@@ -63,19 +82,35 @@ where
         client.get_token_balance(&token, None)
     )?;
 
-    let mut value = token.to_wei(amount);
+    let mut value = token.to_wei(amount)?;
 
     if value > wallet_balance {
+        notify(
+            NotificationKind::LowBalance,
+            "Low wallet balance",
+            format!(
+                "{} holds {} {}, less than the {amount} deposit to {proxy_wallet_address}",
+                client.address(),
+                format_units(wallet_balance, "mwei")?,
+                Token::USDCE
+            ),
+        )
+        .await;
+
         value = wallet_balance;
     }
 
-    if config.ignore_existing_balance {
-        client.transfer(proxy_wallet_address, value, &token).await?;
-    } else if proxy_wallet_balance > U256::ZERO {
+    if !config.ignore_existing_balance && proxy_wallet_balance > U256::ZERO {
         let ui_amount = format_units(proxy_wallet_balance, "mwei")?;
         tracing::warn!("Proxy wallet already holds {} {}", ui_amount, Token::USDCE);
     } else {
-        client.transfer(proxy_wallet_address, value, &token).await?;
+        let success = client.transfer(proxy_wallet_address, value, &token).await?;
+
+        record_transaction(TransactionRecord::Deposit {
+            proxy_address: account.proxy_address.clone(),
+            amount: token.to_amount(value)?,
+            success,
+        });
     }
 
     account.set_funded(true);
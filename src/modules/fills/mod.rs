@@ -0,0 +1,98 @@
+use tokio::sync::{broadcast::error::RecvError, Mutex};
+
+use crate::{
+    db::{database::Database, fills::FillsLedger},
+    polymarket::api::clob::{
+        endpoints::get_trades,
+        schemas::UserTradeEvent,
+        websocket::user::{UserEvent, UserStream},
+    },
+    utils::dry_run,
+};
+
+// Recorders of different accounts share the ledger file
+static LEDGER_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub async fn sync_fills(db: &Database) -> eyre::Result<FillsLedger> {
+    let mut ledger = FillsLedger::read().await?;
+
+    for account in db.0.iter() {
+        if account.get_api_creds().is_none() {
+            tracing::warn!(
+                "{} | Account has no API credentials, skipping trade history",
+                account.proxy_address
+            );
+            continue;
+        }
+
+        match get_trades(account).await {
+            Ok(trades) => {
+                // Failed trades are dropped even if their fills were recorded while matched
+                let changed: usize = trades
+                    .iter()
+                    .map(|trade| ledger.apply_trade(trade, &account.proxy_address))
+                    .sum();
+
+                tracing::info!(
+                    "{} | Recorded or removed {changed} fills",
+                    account.proxy_address
+                );
+            }
+            Err(e) => tracing::error!(
+                "{} | Failed to get trade history: {e}",
+                account.proxy_address
+            ),
+        }
+    }
+
+    ledger.update()?;
+
+    Ok(ledger)
+}
+
+// Records the fills of every trade seen on the user channel until the stream is dropped, trades
+// that fail on chain are removed again
+pub fn record_user_fills(user_stream: &UserStream) {
+    let mut events = user_stream.events();
+    let proxy_address = user_stream.proxy_address.clone();
+    let api_key = user_stream.api_key.clone();
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(UserEvent::Trade(trade)) => {
+                    if let Err(e) = record_user_trade(&trade, &proxy_address, &api_key).await {
+                        tracing::warn!(
+                            "{proxy_address} | Failed to record fills of trade {}: {e}",
+                            trade.id
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(
+                    "{proxy_address} | Skipped {skipped} user events, missed fills are picked up by the next trade history sync"
+                ),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+async fn record_user_trade(
+    trade: &UserTradeEvent,
+    proxy_address: &str,
+    api_key: &str,
+) -> eyre::Result<()> {
+    if dry_run::is_enabled() {
+        return Ok(());
+    }
+
+    let _guard = LEDGER_LOCK.lock().await;
+    let mut ledger = FillsLedger::read().await?;
+
+    if ledger.apply_user_trade(trade, proxy_address, api_key) > 0 {
+        ledger.update()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use alloy::primitives::Address;
+
+use crate::{
+    db::{
+        account::Account,
+        constants::{DEFAULT_PASSWORD, PROXIES_FILE_PATH, RECIPIENTS_FILE_PATH},
+        crypto::encryption_password,
+        database::Database,
+        keystore::{export_v3_keystore, read_account_lines, Keystore},
+        migrate::migrate_encryption,
+        storage::storage,
+    },
+    utils::files::{read_file_lines, write_atomic},
+};
+
+pub async fn import_private_keys(file_path: &str, password: Option<&str>) -> eyre::Result<()> {
+    let password = encryption_password(password)?;
+    let private_keys = read_file_lines(file_path).await?;
+    let mut keystore = Keystore::read().await?;
+    let mut imported = 0;
+
+    for (i, private_key) in private_keys.iter().enumerate() {
+        if private_key.trim().is_empty() {
+            continue;
+        }
+
+        match keystore.add(private_key, password) {
+            Ok(Some(_)) => imported += 1,
+            Ok(None) => tracing::warn!("Private key at line {} is already in the keystore", i + 1),
+            Err(e) => eyre::bail!("Failed to import private key at line {}: {e}", i + 1),
+        }
+    }
+
+    keystore.write().await?;
+
+    tracing::info!(
+        "Imported {imported} private keys, the keystore now holds {} accounts",
+        keystore.0.len()
+    );
+
+    Ok(())
+}
+
+pub async fn add_account(private_key: &str, password: Option<&str>) -> eyre::Result<()> {
+    let encryption_password = encryption_password(password)?;
+    let mut keystore = Keystore::read().await?;
+
+    let Some(index) = keystore.add(private_key, encryption_password)? else {
+        tracing::warn!("Account is already in the keystore");
+        return Ok(());
+    };
+
+    keystore.write().await?;
+
+    // Same pairing with proxies and recipients by line as `Database::new`
+    if storage().file_path().exists() {
+        let mut db = Database::read(password).await?;
+        let proxy = read_account_lines(PROXIES_FILE_PATH)
+            .await?
+            .get(index)
+            .cloned();
+        let recipient = read_account_lines(RECIPIENTS_FILE_PATH)
+            .await?
+            .get(index)
+            .cloned();
+        let private_key = private_key.trim();
+
+        let mut account = Account::new(private_key, &keystore.0[index], proxy, recipient);
+        account.unlock(encryption_password)?;
+
+        db.0.push(account);
+        db.update()?;
+    }
+
+    tracing::info!("Account added to the keystore at position {index}");
+
+    Ok(())
+}
+
+pub async fn remove_account(address: Address, password: Option<&str>) -> eyre::Result<()> {
+    let mut keystore = Keystore::read().await?;
+
+    let Some(index) = keystore.remove(address, password.unwrap_or(DEFAULT_PASSWORD))? else {
+        eyre::bail!("Account {address} is not in the keystore");
+    };
+
+    keystore.write().await?;
+
+    // Keep proxies and recipients aligned with the remaining keys
+    for file_path in [PROXIES_FILE_PATH, RECIPIENTS_FILE_PATH] {
+        remove_account_line(file_path, index).await?;
+    }
+
+    if storage().file_path().exists() {
+        let mut db = Database::read(password).await?;
+        db.0.retain(|account| account.signer().address() != address);
+        db.update()?;
+    }
+
+    tracing::info!("Account {address} removed from the keystore");
+
+    Ok(())
+}
+
+pub async fn change_keystore_password(
+    password: Option<&str>,
+    new_password: &str,
+) -> eyre::Result<()> {
+    migrate_encryption(password, Some(new_password)).await
+}
+
+// Exports every account when no addresses are given
+pub async fn export_accounts(
+    addresses: &[Address],
+    password: Option<&str>,
+    export_password: &str,
+    out_dir: &str,
+) -> eyre::Result<()> {
+    let password = password.unwrap_or(DEFAULT_PASSWORD);
+    let keystore = Keystore::read().await?;
+    let mut exported = vec![];
+
+    for index in 0..keystore.0.len() {
+        let signer = keystore.decrypt(index, password)?;
+
+        if !addresses.is_empty() && !addresses.contains(&signer.address()) {
+            continue;
+        }
+
+        let path = export_v3_keystore(&signer, out_dir, export_password)?;
+        tracing::info!("{} | Exported to {}", signer.address(), path.display());
+
+        exported.push(signer.address());
+    }
+
+    for address in addresses
+        .iter()
+        .filter(|address| !exported.contains(address))
+    {
+        tracing::warn!("Account {address} is not in the keystore");
+    }
+
+    Ok(())
+}
+
+// `index` is a keystore position, blank lines are skipped the same way
+async fn remove_account_line(file_path: &str, index: usize) -> eyre::Result<()> {
+    let mut lines = read_account_lines(file_path).await?;
+
+    if index < lines.len() {
+        lines.remove(index);
+        write_atomic(file_path, (lines.join("\n") + "\n").as_bytes())?;
+        tracing::info!("Removed position {index} from {file_path}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::local::PrivateKeySigner;
+
+    use super::*;
+    use crate::db::crypto::encrypt_private_key;
+
+    #[tokio::test]
+    async fn test_blank_key_line_keeps_proxies_aligned() {
+        let dir = std::env::temp_dir().join(format!("keys-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let keys_path = dir.join("encrypted_private_keys.txt");
+        let proxies_path = dir.join("proxies.txt");
+        let keys_path = keys_path.to_str().unwrap();
+        let proxies_path = proxies_path.to_str().unwrap();
+
+        let signers = [(); 3].map(|_| PrivateKeySigner::random());
+        let lines = signers
+            .iter()
+            .map(|signer| {
+                encrypt_private_key(&const_hex::encode_prefixed(signer.to_bytes()), "password")
+            })
+            .collect::<Vec<_>>();
+        let keys = format!("{}\n\n{}\n{}\n", lines[0], lines[1], lines[2]);
+        std::fs::write(keys_path, keys).unwrap();
+        std::fs::write(proxies_path, "proxy-0\nproxy-1\n\nproxy-2\n").unwrap();
+
+        let mut keystore = Keystore::read_from(keys_path).await.unwrap();
+        let index = keystore
+            .remove(signers[1].address(), "password")
+            .unwrap()
+            .unwrap();
+        remove_account_line(proxies_path, index).await.unwrap();
+
+        assert_eq!(index, 1);
+        assert_eq!(
+            read_account_lines(proxies_path).await.unwrap(),
+            ["proxy-0", "proxy-2"]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
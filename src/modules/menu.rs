@@ -1,13 +1,25 @@
 use super::{
+    accounts::{display_accounts, tag_accounts},
     bets::opposing::opposing_bets, deposit::deposit_to_accounts, registration::register_accounts,
-    stats_check::check_and_display_stats,
 };
 use crate::db::constants::{ADDRESS_FILE_PATH, PROXY_ADDRESS_FILE_PATH};
-use crate::modules::stats_check::{check_and_display_stats_from_db, check_and_display_stats_from_text, get_proxy_address_from_txt};
+use crate::db::selection::{Selection, SELECTION_HELP};
+use crate::utils::dry_run;
+use crate::modules::stats_check::{check_and_display_stats_from_db, check_and_display_stats_from_text, get_proxy_address_from_txt, pnl::display_pnl_report};
 use crate::{
     config::Config,
     db::database::Database,
-    modules::{sell::sell_all::sell_all_open_positions, withdraw::withdraw_for_all},
+    modules::{
+        browse::browse_events_interactive,
+        orders::{
+            limit::{display_placed_orders, place_limit_orders, prompt_limit_order_params},
+            open::manage_open_orders,
+        },
+        redeem::redeem_for_all,
+        sell::sell_all::sell_all_open_positions,
+        watch::watch_markets,
+        withdraw::withdraw_for_all,
+    },
 };
 use colored::Colorize;
 use dialoguer::{theme::ColorfulTheme, Password, Select};
@@ -26,48 +38,86 @@ const LOGO: &str = r#"
                                          Author:[𝕏] @0xNaiXi
 "#;
 
-pub async fn menu() -> eyre::Result<()> {
-    async fn read_or_create_db(password: Option<&str>) -> eyre::Result<Database> {
-        match Database::read(password).await {
-            Ok(db) => Ok(db),
-            Err(_) => Database::new(password).await,
+pub(super) async fn read_db(password: Option<&str>) -> eyre::Result<Database> {
+    match Database::read(password).await {
+        Ok(db) => Ok(db),
+        Err(e) => {
+            println!(
+                "{}",
+                "✘ Failed to read database! (password is wrong?)".red()
+            );
+            Err(e) // 直接返回错误，不创建新数据库
         }
     }
+}
 
-    async fn read_db(password: Option<&str>) -> eyre::Result<Database> {
-        match Database::read(password).await {
-            Ok(db) => Ok(db),
-            Err(e) => {
-                println!(
-                    "{}",
-                    "✘ Failed to read database! (password is wrong?)".red()
-                );
-                Err(e) // 直接返回错误，不创建新数据库
-            }
+// 选择要处理的账户，留空则处理全部
+fn prompt_selection() -> eyre::Result<Selection> {
+    println!("Account selection: {SELECTION_HELP}");
+
+    loop {
+        let expressions: String = dialoguer::Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Accounts (empty for all)")
+            .allow_empty(true)
+            .interact_text()?;
+
+        match expressions.parse() {
+            Ok(selection) => return Ok(selection),
+            Err(e) => println!("{}", format!("✘ {e}").red()),
         }
     }
+}
+
+async fn manage_tags(db: Database) -> eyre::Result<()> {
+    let selection = prompt_selection()?;
+    display_accounts(&db, &selection).await?;
+
+    let action = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Tags")
+        .items(&["Back", "Add tag", "Remove tag"])
+        .default(0)
+        .interact()?;
+
+    if action == 0 {
+        return Ok(());
+    }
+
+    let tag: String = dialoguer::Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Tag")
+        .interact_text()?;
+    let tag = vec![tag.trim().to_string()];
+    let db = db.select(&selection).await?;
 
-    // 通过txt 读取数据（地址 一行一个）
-    async fn read_data_from_txt(file_path: &str) -> eyre::Result<Vec<String>> {
-        let content = tokio::fs::read_to_string(file_path).await?;
-        let lines: Vec<String> = content
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(String::from)
-            .collect();
-        Ok(lines)
+    match action {
+        1 => tag_accounts(db, &tag, &[]),
+        _ => tag_accounts(db, &[], &tag),
     }
+}
 
-    let config = Config::read_default().await;
+// 通过txt 读取数据（地址 一行一个）
+pub(super) async fn read_data_from_txt(file_path: &str) -> eyre::Result<Vec<String>> {
+    let content = tokio::fs::read_to_string(file_path).await?;
+    let lines: Vec<String> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect();
+    Ok(lines)
+}
+
+pub async fn menu(config: Config, password: Option<String>) -> eyre::Result<()> {
     let logo = LOGO.blue();
 
     println!("{logo}");
 
-    let aes_key = Password::with_theme(&ColorfulTheme::default())
-        .allow_empty_password(true)
-        .with_prompt("Please enter password")
-        .interact()
-        .unwrap();
+    let aes_key = match password {
+        Some(password) => password,
+        None => Password::with_theme(&ColorfulTheme::default())
+            .allow_empty_password(true)
+            .with_prompt("Please enter password")
+            .interact()
+            .unwrap(),
+    };
     let aes_key = if aes_key.is_empty() {
         None
     } else {
@@ -79,11 +129,19 @@ pub async fn menu() -> eyre::Result<()> {
             "Accounts registration",
             "Proxy wallets stats check from txt",
             "Proxy wallets stats check",
+            "P&L report",
             "USDC deposit",
             "Opposing bets",
             "Sell all open positions",
             "Withdraw",
+            "Redeem resolved positions",
+            "Place limit order",
+            "Placed limit orders",
+            "Open orders",
             "Get proxy address from txt",
+            "Accounts and tags",
+            "Watch markets",
+            "Browse events",
             "Exit",
         ];
 
@@ -109,28 +167,53 @@ pub async fn menu() -> eyre::Result<()> {
                 check_and_display_stats_from_text(data, &config).await?;
             }
             2 => {
-                let db = read_db(aes_key).await?;
+                let selection = prompt_selection()?;
+                let db = read_db(aes_key).await?.select(&selection).await?;
                 check_and_display_stats_from_db(db, &config).await?;
             }
             3 => {
-                let mut db = read_db(aes_key).await?;
+                let db = read_db(aes_key).await?;
+                display_pnl_report(db, &config).await?;
+            }
+            4 => {
+                let selection = prompt_selection()?;
+                let mut db = read_db(aes_key).await?.select(&selection).await?;
                 db.shuffle();
                 deposit_to_accounts(db, &config).await?;
             }
-            4 => {
+            5 => {
                 let mut db = read_db(aes_key).await?;
                 db.shuffle();
                 opposing_bets(db, &config).await?;
             }
-            5 => {
-                let db = read_db(aes_key).await?;
+            6 => {
+                let selection = prompt_selection()?;
+                let db = read_db(aes_key).await?.select(&selection).await?;
                 sell_all_open_positions(db, &config).await?;
             }
-            6 => {
-                let mut db = read_db(aes_key).await?;
+            7 => {
+                let selection = prompt_selection()?;
+                let mut db = read_db(aes_key).await?.select(&selection).await?;
                 withdraw_for_all(&mut db, &config).await?;
             }
-            7 => {
+            8 => {
+                let db = read_db(aes_key).await?;
+                redeem_for_all(db, &config).await?;
+            }
+            9 => {
+                let params = prompt_limit_order_params()?;
+                let db = read_db(aes_key).await?;
+                place_limit_orders(db, &params, &config).await?;
+            }
+            10 => {
+                let db = read_db(aes_key).await?;
+                display_placed_orders(&db);
+            }
+            11 => {
+                let db = read_db(aes_key).await?;
+                manage_open_orders(db).await?;
+            }
+            12 => {
                 let file_path: String = dialoguer::Input::with_theme(&ColorfulTheme::default())
                     .with_prompt("Please enter file path")
                     .default(ADDRESS_FILE_PATH.to_string())
@@ -139,10 +222,24 @@ pub async fn menu() -> eyre::Result<()> {
                 let data = read_data_from_txt(&file_path).await?;
                 get_proxy_address_from_txt(data).await?;
             }
-            8 => {
+            13 => {
+                let db = read_db(aes_key).await?;
+                manage_tags(db).await?;
+            }
+            14 => {
+                watch_markets(&config).await?;
+            }
+            15 => {
+                browse_events_interactive().await?;
+            }
+            16 => {
                 return Ok(());
             }
             _ => tracing::error!("Invalid selection"),
         }
+
+        if dry_run::is_enabled() {
+            dry_run::display_summary();
+        }
     }
 }
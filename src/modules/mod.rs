@@ -1,7 +1,15 @@
+mod accounts;
 mod bets;
+mod browse;
+pub mod cli;
 mod deposit;
-pub mod menu;
+mod fills;
+mod keys;
+mod menu;
+mod orders;
+mod redeem;
 mod registration;
 mod sell;
-mod stats_check;
+pub mod stats_check;
+pub mod watch;
 mod withdraw;
@@ -0,0 +1,321 @@
+use alloy::primitives::Address;
+use chrono::Utc;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use rust_decimal::Decimal;
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::{
+    config::Config,
+    db::{account::Account, database::Database, order::OrderRecord},
+    modules::registration::get_or_create_api_key,
+    polymarket::api::clob::{
+        endpoints::{get_neg_risk, get_tick_size, place_order},
+        order_builder::OrderBuilder,
+        schemas::{OrderRequest, OrderType},
+        typedefs::{CreateOrderOptions, Side, TickSize, UserOrder},
+    },
+    utils::misc::{format_timestamp, pretty_sleep},
+};
+
+// The CLOB rejects GTD orders that expire less than a minute after they reach the matching engine
+const GTD_EXPIRATION_THRESHOLD_SECS: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct LimitOrderParams {
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub expiration: Option<u64>,
+}
+
+impl LimitOrderParams {
+    pub fn new(
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+        expiration_hours: Option<u64>,
+    ) -> Self {
+        let expiration = expiration_hours.filter(|hours| *hours > 0).map(|hours| {
+            Utc::now().timestamp() as u64 + GTD_EXPIRATION_THRESHOLD_SECS + hours * 3600
+        });
+
+        Self {
+            token_id: token_id.to_string(),
+            side,
+            price,
+            size,
+            expiration,
+        }
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        match self.expiration {
+            Some(_) => OrderType::Gtd,
+            None => OrderType::Gtc,
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct OrderRecordEntry {
+    #[tabled(rename = "Proxy Address")]
+    proxy_address: String,
+
+    #[tabled(rename = "Order ID")]
+    order_id: String,
+
+    #[tabled(rename = "Token ID")]
+    token_id: String,
+
+    #[tabled(rename = "Side")]
+    side: Side,
+
+    #[tabled(rename = "Price")]
+    price: Decimal,
+
+    #[tabled(rename = "Size")]
+    size: Decimal,
+
+    #[tabled(rename = "Type")]
+    order_type: OrderType,
+
+    #[tabled(rename = "Expiration")]
+    expiration: String,
+
+    #[tabled(rename = "Placed At")]
+    created_at: String,
+
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+pub fn prompt_limit_order_params() -> eyre::Result<LimitOrderParams> {
+    let theme = ColorfulTheme::default();
+
+    let token_id: String = Input::with_theme(&theme)
+        .with_prompt("Token id")
+        .interact_text()?;
+
+    let side = match Select::with_theme(&theme)
+        .with_prompt("Side")
+        .items(&["Buy", "Sell"])
+        .default(0)
+        .interact()?
+    {
+        0 => Side::Buy,
+        _ => Side::Sell,
+    };
+
+    let price: Decimal = Input::with_theme(&theme)
+        .with_prompt("Price")
+        .validate_with(
+            |price: &Decimal| match *price > Decimal::ZERO && *price < Decimal::ONE {
+                true => Ok(()),
+                false => Err("Price must be between 0 and 1"),
+            },
+        )
+        .interact_text()?;
+
+    let size: Decimal = Input::with_theme(&theme)
+        .with_prompt("Size (shares)")
+        .validate_with(|size: &Decimal| match *size > Decimal::ZERO {
+            true => Ok(()),
+            false => Err("Size must be positive"),
+        })
+        .interact_text()?;
+
+    let expiration_hours: u64 = Input::with_theme(&theme)
+        .with_prompt("Expiration in hours (0 for good-till-cancelled)")
+        .default(0)
+        .interact_text()?;
+
+    Ok(LimitOrderParams::new(
+        &token_id,
+        side,
+        price,
+        size,
+        Some(expiration_hours),
+    ))
+}
+
+pub async fn place_limit_orders(
+    mut db: Database,
+    params: &LimitOrderParams,
+    config: &Config,
+) -> eyre::Result<()> {
+    for index in 0..db.0.len() {
+        let account = &mut db.0[index];
+
+        match place_limit_order(account, params).await {
+            Ok(order) => {
+                account.add_order(order);
+                db.update()?;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "{} | Failed to place a limit order: {e}",
+                    account.proxy_address
+                );
+                continue;
+            }
+        }
+
+        if index + 1 < db.0.len() {
+            pretty_sleep(config.limit_order_delay_range).await;
+        }
+    }
+
+    display_placed_orders(&db);
+
+    Ok(())
+}
+
+async fn place_limit_order(
+    account: &Account,
+    params: &LimitOrderParams,
+) -> eyre::Result<OrderRecord> {
+    let proxy = account.proxy();
+    let proxy_wallet_address = account.get_proxy_address().to_string();
+
+    let min_tick_size = get_tick_size(proxy.as_ref(), &params.token_id).await?;
+    let tick_size = TickSize::from_str(&min_tick_size.normalize().to_string())
+        .ok_or_else(|| eyre::eyre!("Unsupported tick size: {min_tick_size}"))?;
+
+    if params.price < min_tick_size || params.price > Decimal::ONE - min_tick_size {
+        eyre::bail!(
+            "Price {} is out of range [{min_tick_size}, {}]",
+            params.price,
+            Decimal::ONE - min_tick_size
+        );
+    }
+
+    let neg_risk = get_neg_risk(&params.token_id, proxy.as_ref()).await?;
+
+    let mut order = UserOrder::default()
+        .with_token_id(&params.token_id)
+        .with_price(params.price)
+        .with_side(params.side.clone())
+        .with_size(params.size)
+        .with_taker(Address::ZERO.to_string());
+
+    if let Some(expiration) = params.expiration {
+        order.set_expiration(expiration);
+    }
+
+    let order_options = CreateOrderOptions::new(tick_size, Some(neg_risk));
+    let order_builder = OrderBuilder::new(account.signer(), 137, None, Some(&proxy_wallet_address));
+    let signed_order = order_builder
+        .build_signed_order(order, order_options)
+        .await?;
+
+    let api_key = get_or_create_api_key(account).await?;
+    let order_type = params.order_type();
+    let order_request = OrderRequest::new(signed_order, &api_key, Some(order_type));
+
+    let response = place_order(account, order_request).await?;
+    let Some(order_id) = response.order_id.filter(|order_id| !order_id.is_empty()) else {
+        let reason = match response.error_msg.is_empty() {
+            true => "no order id in the response",
+            false => &response.error_msg,
+        };
+        eyre::bail!("Failed to place the limit order: {reason}");
+    };
+
+    tracing::info!(
+        "[{}] {} | Placed a {order_type} limit order with id {order_id}. Price: {}. Size: {}",
+        params.side,
+        account.proxy_address,
+        params.price,
+        params.size,
+    );
+
+    Ok(OrderRecord {
+        order_id,
+        token_id: params.token_id.clone(),
+        side: params.side.clone(),
+        price: params.price,
+        size: params.size,
+        order_type,
+        expiration: params.expiration.unwrap_or(0),
+        created_at: Utc::now().timestamp(),
+        status: response.status,
+    })
+}
+
+pub fn display_placed_orders(db: &Database) {
+    let entries =
+        db.0.iter()
+            .flat_map(|account| {
+                account.get_orders().iter().map(|order| OrderRecordEntry {
+                    proxy_address: account.proxy_address.clone(),
+                    order_id: order.order_id.clone(),
+                    token_id: order.token_id.clone(),
+                    side: order.side.clone(),
+                    price: order.price,
+                    size: order.size,
+                    order_type: order.order_type,
+                    expiration: match order.expiration {
+                        0 => "".to_string(),
+                        expiration => format_timestamp(expiration as i64),
+                    },
+                    created_at: format_timestamp(order.created_at),
+                    status: order
+                        .status
+                        .map(|status| status.to_string())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+    if entries.is_empty() {
+        tracing::info!("No limit orders placed yet");
+        return;
+    }
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::testing::mock_server::{mock_server, random_account};
+
+    const TOKEN_ID: &str =
+        "21742633143463906290569050155826241533067272736897614950488156847949938836455";
+
+    #[tokio::test]
+    async fn test_place_limit_order_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        let params = LimitOrderParams::new(TOKEN_ID, Side::Buy, dec!(0.45), dec!(20), Some(2));
+
+        let record = place_limit_order(&account, &params).await.unwrap();
+
+        assert_eq!(record.token_id, TOKEN_ID);
+        assert_eq!(record.order_type, OrderType::Gtd);
+        assert_eq!(record.expiration, params.expiration.unwrap());
+        assert!(record.order_id.starts_with("0x"));
+
+        let orders = mock.orders(account.get_proxy_address());
+        assert_eq!(orders.len(), 1);
+
+        let request = &orders[0];
+        assert_eq!(request["orderType"], "GTD");
+        assert_eq!(request["owner"], account.get_api_creds().unwrap().api_key);
+        assert_eq!(request["order"]["side"], "BUY");
+        assert_eq!(request["order"]["tokenId"], TOKEN_ID);
+        assert_eq!(request["order"]["makerAmount"], "9000000");
+        assert_eq!(request["order"]["takerAmount"], "20000000");
+        assert_eq!(
+            request["order"]["expiration"],
+            params.expiration.unwrap().to_string()
+        );
+    }
+}
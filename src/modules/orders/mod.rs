@@ -0,0 +1,2 @@
+pub mod limit;
+pub mod open;
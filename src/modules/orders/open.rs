@@ -0,0 +1,199 @@
+use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect, Select};
+use itertools::Itertools;
+use rust_decimal::Decimal;
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::{
+    db::{account::Account, database::Database},
+    polymarket::api::clob::{
+        endpoints::{cancel_all_orders, cancel_order, cancel_orders, get_open_orders},
+        schemas::{CancelOrdersResponseBody, OpenOrder, OrderType},
+        typedefs::Side,
+    },
+    utils::misc::format_timestamp,
+};
+
+#[derive(Tabled)]
+struct OpenOrderEntry {
+    #[tabled(rename = "#")]
+    index: usize,
+
+    #[tabled(rename = "Proxy Address")]
+    proxy_address: String,
+
+    #[tabled(rename = "Order ID")]
+    order_id: String,
+
+    #[tabled(rename = "Outcome")]
+    outcome: String,
+
+    #[tabled(rename = "Side")]
+    side: Side,
+
+    #[tabled(rename = "Price")]
+    price: Decimal,
+
+    #[tabled(rename = "Size")]
+    size: Decimal,
+
+    #[tabled(rename = "Matched")]
+    size_matched: Decimal,
+
+    #[tabled(rename = "Type")]
+    order_type: OrderType,
+
+    #[tabled(rename = "Expiration")]
+    expiration: String,
+
+    #[tabled(rename = "Placed At")]
+    created_at: String,
+}
+
+pub async fn manage_open_orders(db: Database) -> eyre::Result<()> {
+    let mut open_orders: Vec<(&Account, OpenOrder)> = vec![];
+
+    for account in db.0.iter() {
+        if account.get_api_creds().is_none() {
+            tracing::warn!(
+                "{} | Account has no API credentials, skipping",
+                account.proxy_address
+            );
+            continue;
+        }
+
+        match get_open_orders(account).await {
+            Ok(orders) => open_orders.extend(orders.into_iter().map(|order| (account, order))),
+            Err(e) => tracing::error!("{} | Failed to get open orders: {e}", account.proxy_address),
+        }
+    }
+
+    if open_orders.is_empty() {
+        tracing::info!("No open orders found");
+        return Ok(());
+    }
+
+    display_open_orders(&open_orders);
+
+    let theme = ColorfulTheme::default();
+    let action = Select::with_theme(&theme)
+        .with_prompt("Action:")
+        .items(&["Cancel selected orders", "Cancel all orders", "Back"])
+        .default(0)
+        .interact()?;
+
+    match action {
+        0 => {
+            let labels = open_orders
+                .iter()
+                .enumerate()
+                .map(|(index, (account, order))| {
+                    format!(
+                        "#{index} {} | {} {} @ {} | {}",
+                        account.proxy_address,
+                        order.side,
+                        order.original_size,
+                        order.price,
+                        order.id
+                    )
+                })
+                .collect_vec();
+
+            let selection = MultiSelect::with_theme(&theme)
+                .with_prompt("Orders to cancel (space to select):")
+                .items(&labels)
+                .interact()?;
+
+            let orders_by_account = selection
+                .into_iter()
+                .map(|index| &open_orders[index])
+                .into_group_map_by(|(account, _)| account.proxy_address.clone());
+
+            for selected in orders_by_account.values() {
+                let account = selected[0].0;
+                let order_ids = selected
+                    .iter()
+                    .map(|(_, order)| order.id.as_str())
+                    .collect_vec();
+
+                let result = match order_ids.as_slice() {
+                    [order_id] => cancel_order(account, order_id).await,
+                    _ => cancel_orders(account, &order_ids).await,
+                };
+
+                log_cancellation_result(account, result.map_err(Into::into));
+            }
+        }
+        1 => {
+            let confirmed = Confirm::with_theme(&theme)
+                .with_prompt("Cancel every open order on every account?")
+                .default(false)
+                .interact()?;
+
+            if !confirmed {
+                return Ok(());
+            }
+
+            let accounts = open_orders
+                .iter()
+                .map(|(account, _)| *account)
+                .unique_by(|account| account.proxy_address.clone())
+                .collect_vec();
+
+            for account in accounts {
+                log_cancellation_result(
+                    account,
+                    cancel_all_orders(account).await.map_err(Into::into),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn log_cancellation_result(account: &Account, result: eyre::Result<CancelOrdersResponseBody>) {
+    match result {
+        Ok(response) => {
+            for order_id in response.canceled {
+                tracing::info!("{} | Order {order_id} cancelled", account.proxy_address);
+            }
+
+            for (order_id, reason) in response.not_canceled {
+                tracing::warn!(
+                    "{} | Order {order_id} was not cancelled: {reason}",
+                    account.proxy_address
+                );
+            }
+        }
+        Err(e) => tracing::error!("{} | Failed to cancel orders: {e}", account.proxy_address),
+    }
+}
+
+fn display_open_orders(open_orders: &[(&Account, OpenOrder)]) {
+    let entries = open_orders
+        .iter()
+        .enumerate()
+        .map(|(index, (account, order))| OpenOrderEntry {
+            index,
+            proxy_address: account.proxy_address.clone(),
+            order_id: order.id.clone(),
+            outcome: order.outcome.clone(),
+            side: order.side.clone(),
+            price: order.price,
+            size: order.original_size,
+            size_matched: order.size_matched,
+            order_type: order.order_type,
+            expiration: match order.expiration.parse::<i64>().unwrap_or(0) {
+                0 => "".to_string(),
+                expiration => format_timestamp(expiration),
+            },
+            created_at: format_timestamp(order.created_at),
+        })
+        .collect_vec();
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+}
@@ -0,0 +1,195 @@
+use std::{str::FromStr, sync::Arc};
+
+use alloy::{
+    network::Ethereum,
+    primitives::{utils::format_units, B256, U256},
+    providers::{Provider, ProviderBuilder},
+    transports::Transport,
+};
+use alloy_chains::NamedChain;
+use itertools::Itertools;
+use reqwest::Url;
+
+use crate::{
+    config::Config,
+    db::{
+        account::Account,
+        database::Database,
+        storage::{record_transaction, TransactionRecord},
+    },
+    onchain::{client::EvmClient, constants::POLYGON_EXPLORER_TX_BASE_URL},
+    polymarket::api::{
+        relayer::{common::redeem_positions, endpoints::wait_for_transaction_confirmation},
+        typedefs::AmpCookie,
+        user::endpoints::get_user_positions,
+    },
+    utils::{
+        misc::pretty_sleep,
+        notifier::{notify, NotificationKind},
+    },
+};
+
+pub async fn redeem_for_all(db: Database, config: &Config) -> eyre::Result<()> {
+    let provider = Arc::new(
+        ProviderBuilder::new()
+            .with_recommended_fillers()
+            .on_http(Url::parse(&config.polygon_rpc_url)?),
+    );
+
+    let mut redeemed = 0;
+    let mut failed = 0;
+
+    for account in db.0.iter() {
+        match redeem_resolved_positions(account, provider.clone()).await {
+            Ok(0) => {}
+            Ok(count) => {
+                redeemed += count;
+                pretty_sleep(config.redeem_delay_range).await;
+            }
+            Err(e) => {
+                tracing::error!("{} | Redeem failed: {e}", account.proxy_address);
+                failed += 1;
+
+                notify(
+                    NotificationKind::AccountFailed,
+                    "Redeem failed",
+                    format!("{} | {e}", account.proxy_address),
+                )
+                .await;
+            }
+        }
+    }
+
+    if failed == 0 {
+        tracing::info!("No more resolved positions left");
+    }
+
+    notify(
+        NotificationKind::ModuleCompleted,
+        "Redeem finished",
+        format!("{redeemed} markets redeemed, {failed} accounts failed"),
+    )
+    .await;
+
+    if failed > 0 {
+        eyre::bail!(
+            "Redeem failed for {failed} of {} accounts, {redeemed} markets redeemed",
+            db.0.len()
+        );
+    }
+
+    Ok(())
+}
+
+async fn redeem_resolved_positions<P, T>(account: &Account, provider: Arc<P>) -> eyre::Result<usize>
+where
+    P: Provider<T, Ethereum>,
+    T: Transport + Clone,
+{
+    let proxy = account.proxy();
+    let proxy_wallet_address = account.get_proxy_address();
+
+    let resolved_markets = get_user_positions(&account.proxy_address, proxy.as_ref())
+        .await?
+        .into_iter()
+        .filter(|position| position.redeemable)
+        .into_group_map_by(|position| position.condition_id.clone());
+
+    tracing::info!(
+        "{} has {} resolved markets to redeem",
+        account.proxy_address,
+        resolved_markets.len()
+    );
+
+    if resolved_markets.is_empty() {
+        return Ok(0);
+    }
+
+    let evm_client = EvmClient::new(provider, account.get_private_key(), NamedChain::Polygon);
+
+    let mut amp_cookie = AmpCookie::new();
+    let (Some(polymarket_nonce), Some(polymarket_session)) =
+        (&account.polymarket_nonce, &account.polymarket_session)
+    else {
+        eyre::bail!("Account is not registered, run the registration first");
+    };
+    let signer = account.signer();
+
+    for (condition_id, positions) in resolved_markets.iter() {
+        let neg_risk = positions.iter().any(|position| position.negative_risk);
+        let mut amounts = vec![U256::ZERO; 2];
+
+        for position in positions {
+            let token_id = U256::from_str_radix(&position.asset, 10)?;
+            let balance = evm_client
+                .get_conditional_token_balance(token_id, Some(proxy_wallet_address))
+                .await?;
+
+            if let Some(amount) = amounts.get_mut(position.outcome_index) {
+                *amount = balance;
+            }
+        }
+
+        let ui_amount = format_units(amounts.iter().sum::<U256>(), "mwei")?;
+        tracing::info!(
+            "Proxy wallet `{proxy_wallet_address}` redeeming {ui_amount} shares of `{}`",
+            positions[0].title
+        );
+
+        let tx_id = redeem_positions(
+            signer.clone(),
+            &mut amp_cookie,
+            polymarket_nonce,
+            polymarket_session,
+            proxy.as_ref(),
+            B256::from_str(condition_id)?,
+            neg_risk,
+            amounts,
+        )
+        .await?;
+
+        let tx_hash = wait_for_transaction_confirmation(
+            &tx_id,
+            &mut amp_cookie,
+            polymarket_nonce,
+            polymarket_session,
+            proxy.as_ref(),
+            None,
+            None,
+        )
+        .await?;
+
+        record_transaction(TransactionRecord::Relayer {
+            proxy_address: account.proxy_address.clone(),
+            transaction_id: tx_id,
+            tx_hash: Some(tx_hash.clone()),
+            kind: "redeem".to_string(),
+        });
+
+        tracing::info!("Positions redeemed: {POLYGON_EXPLORER_TX_BASE_URL}{tx_hash}");
+    }
+
+    Ok(resolved_markets.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{mock_server, random_account};
+
+    #[tokio::test]
+    async fn test_unregistered_account_fails_without_panicking_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        mock.add_resolved_position(account.get_proxy_address(), "1", 10.0);
+
+        let provider = Arc::new(
+            ProviderBuilder::new().on_http(Url::parse(&mock.config().polygon_rpc_url).unwrap()),
+        );
+        let error = redeem_resolved_positions(&account, provider)
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("not registered"));
+    }
+}
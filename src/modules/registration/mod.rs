@@ -18,6 +18,7 @@ use crate::{
         },
     },
     utils::{
+        dry_run,
         misc::{generate_random_username, pretty_sleep, swap_ip_address},
         poly::sign_enable_trading_message,
     },
@@ -30,9 +31,7 @@ use alloy::{
     transports::Transport,
 };
 use reqwest::{Proxy, StatusCode, Url};
-use std::time::Duration;
 use std::{str::FromStr, sync::Arc};
-use tokio::time::sleep;
 
 const ZERO_BYTES: Bytes = bytes!("");
 
@@ -49,7 +48,7 @@ pub async fn register_accounts(mut db: Database, config: &Config) -> eyre::Resul
         register_account(account, config, provider.clone()).await?;
 
         account.set_is_registered(true);
-        db.update();
+        db.update()?;
 
         pretty_sleep(config.registration_sleep_range).await;
     }
@@ -105,7 +104,13 @@ where
         .await?
         .is_some();
 
-    if !user_exists {
+    if !user_exists && dry_run::is_enabled() {
+        dry_run::record(
+            &account.proxy_address,
+            "Create profile",
+            "random username, default preferences",
+        );
+    } else if !user_exists {
         let profile = create_profile(
             signer.clone(),
             proxy.as_ref(),
@@ -229,6 +234,19 @@ pub async fn create_or_derive_api_key(
     Ok(response)
 }
 
+pub async fn get_or_create_api_key(account: &Account) -> eyre::Result<String> {
+    let maybe_key = account.api_key.read().unwrap().clone();
+
+    if let Some(key) = maybe_key {
+        return Ok(key);
+    }
+
+    let response = create_or_derive_api_key(account.signer(), account.proxy().as_ref()).await?;
+    account.update_credentials(response);
+
+    Ok(account.api_key.read().unwrap().as_ref().unwrap().clone())
+}
+
 pub async fn check_if_proxy_wallet_activated<P, T>(
     provider: Arc<P>,
     proxy_address: Address,
@@ -247,35 +265,42 @@ where
     Ok(exists)
 }
 
-pub async fn retry_check_activation<P, T>(
-    provider: Arc<P>,
-    address: Address,
-    max_attempts: u32,
-) -> eyre::Result<bool>
-where
-    P: Provider<T, Ethereum>,
-    T: Transport + Clone,
-{
-    for attempt in 1..=max_attempts {
-        match check_if_proxy_wallet_activated(provider.clone(), address).await {
-            Ok(result) => return Ok(result),
-            Err(e) => {
-                if attempt == max_attempts {
-                    return Err(eyre::eyre!("Failed after {} attempts: {}", max_attempts, e));
-                }
-
-                tracing::warn!(
-                    "Attempt {}/{} failed for address {}: {}. Retrying...",
-                    attempt,
-                    max_attempts,
-                    address,
-                    e
-                );
-
-                sleep(Duration::from_secs(1)).await;
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{mock_server, random_account};
+
+    #[tokio::test]
+    async fn test_register_account_offline() {
+        let mock = mock_server();
+        let config = mock.config();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .with_recommended_fillers()
+                .on_http(Url::parse(&config.polygon_rpc_url).unwrap()),
+        );
+
+        let mut account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+
+        register_account(&mut account, &config, provider.clone()).await.unwrap();
+
+        assert!(mock.has_profile(account.signer().address()));
+        assert!(mock.is_deployed(proxy_wallet));
+        assert!(mock.is_approved(proxy_wallet));
+        assert!(account.get_api_creds().is_some());
+        assert!(account.polymarket_session.is_some());
+
+        let transaction_types = || {
+            mock.relayer_transactions(proxy_wallet)
+                .iter()
+                .map(|transaction| transaction["type"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(transaction_types(), ["SAFE-CREATE", "SAFE"]);
+
+        // Everything is in place the second time, nothing new is submitted
+        register_account(&mut account, &config, provider).await.unwrap();
+        assert_eq!(transaction_types(), ["SAFE-CREATE", "SAFE"]);
     }
-
-    Err(eyre::eyre!("Unexpected error in retry logic"))
-}
\ No newline at end of file
+}
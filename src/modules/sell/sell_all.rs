@@ -1,28 +1,39 @@
+use std::collections::HashSet;
+
 use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng, Rng};
 
 use crate::{
     config::Config,
     db::{account::Account, database::Database},
+    errors::price_protection::PriceProtectionError,
     modules::bets::opposing::create_and_place_sell_market_order,
     polymarket::api::{
         clob::{
             endpoints::get_tick_size,
-            typedefs::{Side, TickSize},
+            typedefs::{PriceProtection, Side, TickSize},
         },
         user::endpoints::get_user_positions,
     },
-    utils::misc::pretty_sleep,
+    utils::{
+        dry_run,
+        misc::pretty_sleep,
+        notifier::{notify, NotificationKind},
+    },
 };
 
 pub async fn sell_all_open_positions(db: Database, config: &Config) -> eyre::Result<()> {
     let mut accounts = db.0.clone();
     let mut rng = thread_rng();
+    let price_protection = config.price_protection();
+    let mut refused = HashSet::new();
+    // Failed accounts are retried, only their first failure is notified
+    let mut failed = HashSet::new();
 
     while !accounts.is_empty() {
         let index = rng.gen_range(0..accounts.len());
         let account = &accounts[index];
 
-        match sell_random_open_positions(account, &mut rng).await {
+        match sell_random_open_positions(account, &price_protection, &mut refused, &mut rng).await {
             Ok(res) => {
                 if !res {
                     accounts.remove(index);
@@ -31,19 +42,46 @@ pub async fn sell_all_open_positions(db: Database, config: &Config) -> eyre::Res
                 pretty_sleep(config.sell_delay_range).await;
             }
             Err(e) => {
-                tracing::error!("Failed to sell a random position: {e}")
+                tracing::error!("Failed to sell a random position: {e}");
+
+                if failed.insert(account.proxy_address.clone()) {
+                    notify(
+                        NotificationKind::AccountFailed,
+                        "Sell failed",
+                        format!("{} | {e}", account.proxy_address),
+                    )
+                    .await;
+                }
             }
         }
     }
 
     tracing::info!("No more open positions left");
+    notify(
+        NotificationKind::ModuleCompleted,
+        "Sell all finished",
+        format!("No open positions left on {} accounts", db.0.len()),
+    )
+    .await;
 
     Ok(())
 }
 
-async fn sell_random_open_positions(account: &Account, rng: &mut ThreadRng) -> eyre::Result<bool> {
+async fn sell_random_open_positions(
+    account: &Account,
+    price_protection: &PriceProtection,
+    refused: &mut HashSet<(String, String)>,
+    rng: &mut ThreadRng,
+) -> eyre::Result<bool> {
     let proxy = account.proxy();
-    let positions = get_user_positions(&account.proxy_address, proxy.as_ref()).await?;
+    // Positions refused by the price protection stay open, they are skipped for the rest of the run
+    let positions = get_user_positions(&account.proxy_address, proxy.as_ref())
+        .await?
+        .into_iter()
+        .filter(|position| {
+            !refused.contains(&(account.proxy_address.clone(), position.asset.clone()))
+        })
+        .collect::<Vec<_>>();
 
     tracing::info!(
         "{} has {} open positions",
@@ -55,17 +93,81 @@ async fn sell_random_open_positions(account: &Account, rng: &mut ThreadRng) -> e
         return Ok(false);
     }
 
-    let position = positions.choose(rng).unwrap();
-    let tick_size = TickSize::from_str(
-        &get_tick_size(proxy.as_ref(), &position.asset)
-            .await?
-            .to_string(),
-    )
-    .unwrap();
+    // Positions stay open in a dry run, so every one of them is sold in a single pass
+    let positions = match dry_run::is_enabled() {
+        true => positions.iter().collect::<Vec<_>>(),
+        false => vec![positions.choose(rng).unwrap()],
+    };
+
+    for position in positions {
+        let tick_size = TickSize::from_str(
+            &get_tick_size(proxy.as_ref(), &position.asset)
+                .await?
+                .to_string(),
+        )
+        .unwrap();
+
+        let response = match create_and_place_sell_market_order(
+            account,
+            &position.asset,
+            tick_size,
+            price_protection,
+            None,
+            None,
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(e) if e.downcast_ref::<PriceProtectionError>().is_some() => {
+                tracing::warn!(
+                    "{} | Position {} is not sold: {e}",
+                    account.proxy_address,
+                    position.asset
+                );
+                refused.insert((account.proxy_address.clone(), position.asset.clone()));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
 
-    let response = create_and_place_sell_market_order(account, &position.asset, tick_size).await?;
+        response.log_successful_placement(Side::Sell, &account.proxy_address);
+    }
+
+    Ok(!dry_run::is_enabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::{mock_server, random_account};
+
+    const TOKEN_ID: &str =
+        "48331043336612883890938759509493159234755048973500640148014422747788308965732";
 
-    response.log_successful_placement(Side::Sell, &account.proxy_address);
+    #[tokio::test]
+    async fn test_refused_positions_are_skipped_offline() {
+        let mock = mock_server();
+        let account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+        let price_protection = mock.config().price_protection();
 
-    Ok(true)
+        mock.add_position(proxy_wallet, TOKEN_ID, 5000.0);
+
+        let mut refused = HashSet::new();
+        let mut rng = thread_rng();
+
+        let has_positions =
+            sell_random_open_positions(&account, &price_protection, &mut refused, &mut rng)
+                .await
+                .unwrap();
+        assert!(has_positions);
+        assert!(refused.contains(&(account.proxy_address.clone(), TOKEN_ID.to_string())));
+
+        let has_positions =
+            sell_random_open_positions(&account, &price_protection, &mut refused, &mut rng)
+                .await
+                .unwrap();
+        assert!(!has_positions);
+        assert!(mock.orders(proxy_wallet).is_empty());
+    }
 }
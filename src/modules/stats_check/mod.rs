@@ -10,6 +10,7 @@ use itertools::{izip, Itertools};
 use rand::prelude::SliceRandom;
 use rand::thread_rng;
 use reqwest::{Proxy, Url};
+use rust_decimal::Decimal;
 use scraping::{
     scrape_open_positions, scrape_users_open_pos_value, scrape_users_pnl, scrape_users_trade_count,
     scrape_users_volume,
@@ -18,7 +19,9 @@ use serde::Serialize;
 use tabled::{settings::Style, Table, Tabled};
 
 use crate::db::constants::PROXIES_FILE_PATH;
-use crate::modules::registration::{check_if_proxy_wallet_activated, retry_check_activation};
+use crate::db::fills::FillsLedger;
+use crate::modules::fills::sync_fills;
+use crate::modules::stats_check::pnl::{account_pnl, calculate_pnl};
 use crate::modules::stats_check::scraping::scrape_users_last_activity_time;
 use crate::onchain::multicall::multicall_get_owners;
 use crate::utils::files::read_file_lines;
@@ -29,6 +32,7 @@ use crate::{
     onchain::{multicall::multicall_balance_of, types::token::Token},
 };
 
+pub mod pnl;
 mod scraping;
 
 const EXPORT_FILE_PATH: &str = "data/stats.csv";
@@ -53,15 +57,27 @@ struct UserStats {
 
     #[tabled(rename = "Open positions value")]
     #[serde(rename = "Open positions value")]
-    open_positions_value: f64,
+    open_positions_value: Decimal,
 
     #[tabled(rename = "Volume")]
     #[serde(rename = "Volume")]
-    volume: f64,
+    volume: Decimal,
+
+    #[tabled(rename = "Avg entry price")]
+    #[serde(rename = "Avg entry price")]
+    avg_entry_price: String,
 
     #[tabled(rename = "P&L")]
     #[serde(rename = "P&L")]
-    pnl: f64,
+    pnl: Decimal,
+
+    #[tabled(rename = "Realized P&L")]
+    #[serde(rename = "Realized P&L")]
+    realized_pnl: Decimal,
+
+    #[tabled(rename = "Unrealized P&L")]
+    #[serde(rename = "Unrealized P&L")]
+    unrealized_pnl: Decimal,
 
     #[tabled(rename = "Trade count")]
     #[serde(rename = "Trade count")]
@@ -91,12 +107,14 @@ struct AddressInfo {
 
 
 pub async fn check_and_display_stats_from_db(db: Database, config: &Config) -> eyre::Result<()> {
+    let ledger = sync_fills(&db).await?;
+
     let (addresses, proxies): (Vec<Address>, Vec<Option<Proxy>>) =
         db.0.iter()
             .map(|account| (account.get_proxy_address(), account.proxy()))
             .unzip();
 
-    check_and_display_stats(addresses, proxies, config).await?;
+    check_and_display_stats(addresses, proxies, Some(&ledger), config).await?;
     Ok(())
 }
 
@@ -121,13 +139,14 @@ pub async fn check_and_display_stats_from_text(proxy_addresses: Vec<String>, con
         let proxy = Proxy::all(random_proxy)?;
         use_proxies.push(Some(proxy));
     }
-    check_and_display_stats(proxy_addresses, use_proxies, config).await?;
+    check_and_display_stats(proxy_addresses, use_proxies, None, config).await?;
     Ok(())
 }
 
 
 // 这里的address 实际为 proxy address
-pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec<Option<Proxy>>, config: &Config) -> eyre::Result<()> {
+// 有本地成交记录时，成交量和平均入场价格来自本地数据
+pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec<Option<Proxy>>, ledger: Option<&FillsLedger>, config: &Config) -> eyre::Result<()> {
     let provider = Arc::new(
         ProviderBuilder::new()
             .with_recommended_fillers()
@@ -159,6 +178,11 @@ pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec
         scrape_users_last_activity_time(proxy_addresses_string.clone(), proxies.clone())
     );
 
+    let positions = match ledger {
+        Some(ledger) => calculate_pnl(ledger, config.cost_basis_method, proxies.first().cloned().flatten().as_ref()).await,
+        None => vec![],
+    };
+
     let mut stats_entries = vec![];
 
     for (address, balance, owners) in izip!(
@@ -180,19 +204,29 @@ pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec
             .iter()
             .find(|res| &res.0 == address)
             .map(|pos_values| pos_values.1.first().unwrap().value)
-            .unwrap_or(0f64);
+            .unwrap_or_default();
 
-        let user_volume = users_volume_stats
-            .iter()
-            .find(|res| &res.0 == address)
-            .map(|volume| volume.1.first().map_or(0f64, |v| v.amount))
-            .unwrap_or(0f64);
+        let user_volume = match ledger {
+            Some(ledger) => ledger.volume(address),
+            None => users_volume_stats
+                .iter()
+                .find(|res| &res.0 == address)
+                .map(|volume| volume.1.first().map_or(Decimal::ZERO, |v| v.amount))
+                .unwrap_or_default(),
+        };
+
+        let (realized_pnl, unrealized_pnl) = account_pnl(&positions, address);
+
+        let avg_entry_price = ledger
+            .and_then(|ledger| ledger.average_entry_price(address))
+            .map(|price| format!("{:.4}", price))
+            .unwrap_or_default();
 
         let user_pnl = users_pnl_stats
             .iter()
             .find(|res| &res.0 == address)
-            .map(|volume| volume.1.first().map_or(0f64, |v| v.amount))
-            .unwrap_or(0f64);
+            .map(|volume| volume.1.first().map_or(Decimal::ZERO, |v| v.amount))
+            .unwrap_or_default();
 
         let trade_count = users_trade_count_stats
             .iter()
@@ -233,9 +267,12 @@ pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec
             open_positions_count,
             open_positions_value,
             volume: user_volume,
+            avg_entry_price,
             pnl: user_pnl,
+            realized_pnl,
+            unrealized_pnl,
             trade_count,
-            is_registered: wallet_owners.len() > 0,
+            is_registered: !wallet_owners.is_empty(),
             last_activity_time: last_activity_text,
         };
 
@@ -252,14 +289,18 @@ pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec
         .map(|entry| entry.open_positions_count)
         .sum();
 
-    let total_open_positions_value: f64 = stats_entries
+    let total_open_positions_value: Decimal = stats_entries
         .iter()
         .map(|entry| entry.open_positions_value)
         .sum();
 
-    let total_volume: f64 = stats_entries.iter().map(|entry| entry.volume).sum();
+    let total_volume: Decimal = stats_entries.iter().map(|entry| entry.volume).sum();
+
+    let total_pnl: Decimal = stats_entries.iter().map(|entry| entry.pnl).sum();
+
+    let total_realized_pnl: Decimal = stats_entries.iter().map(|entry| entry.realized_pnl).sum();
 
-    let total_pnl: f64 = stats_entries.iter().map(|entry| entry.pnl).sum();
+    let total_unrealized_pnl: Decimal = stats_entries.iter().map(|entry| entry.unrealized_pnl).sum();
 
     let total_trade_count: u64 = stats_entries.iter().map(|entry| entry.trade_count).sum();
 
@@ -272,7 +313,10 @@ pub async fn check_and_display_stats(proxy_addresses: Vec<Address>, proxies: Vec
         open_positions_count: total_open_positions_count,
         open_positions_value: total_open_positions_value,
         volume: total_volume,
+        avg_entry_price: "".to_string(),
         pnl: total_pnl,
+        realized_pnl: total_realized_pnl,
+        unrealized_pnl: total_unrealized_pnl,
         trade_count: total_trade_count,
         is_registered: false,
         last_activity_time: "".to_string(),
@@ -306,7 +350,7 @@ where
 
     writer.flush()?;
 
-    tracing::info!("Stats exported to {}", EXPORT_FILE_PATH);
+    tracing::info!("Stats exported to {}", path);
 
     Ok(())
 }
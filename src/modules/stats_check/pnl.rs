@@ -0,0 +1,385 @@
+use std::collections::{HashMap, VecDeque};
+
+use indexmap::IndexMap;
+use itertools::Itertools;
+use reqwest::Proxy;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tabled::{settings::Style, Table, Tabled};
+
+use crate::{
+    config::Config,
+    db::{
+        database::Database,
+        fills::{Fill, FillsLedger},
+    },
+    modules::fills::sync_fills,
+    polymarket::api::clob::{endpoints::get_order_books, typedefs::Side},
+};
+
+use super::export_stats_to_csv;
+
+const PNL_EXPORT_FILE_PATH: &str = "data/pnl.csv";
+const ORDER_BOOKS_BATCH_SIZE: usize = 20;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CostBasisMethod {
+    #[default]
+    Fifo,
+    Average,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PositionPnl {
+    pub proxy_address: String,
+    pub token_id: String,
+    pub market: String,
+    pub outcome: String,
+    pub position: Decimal,
+    pub cost_basis: Decimal,
+    pub realized: Decimal,
+    pub fees: Decimal,
+    pub mark_price: Option<Decimal>,
+}
+
+impl PositionPnl {
+    pub fn unrealized(&self) -> Decimal {
+        match self.mark_price {
+            Some(mark_price) if self.position > Decimal::ZERO => {
+                self.position * mark_price - self.cost_basis
+            }
+            _ => Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Default)]
+struct CostBasis {
+    lots: VecDeque<(Decimal, Decimal)>,
+    size: Decimal,
+    cost: Decimal,
+}
+
+impl CostBasis {
+    fn buy(&mut self, size: Decimal, price: Decimal) {
+        self.lots.push_back((size, price));
+        self.size += size;
+        self.cost += size * price;
+    }
+
+    // Returns the cost of the sold shares; sells beyond the recorded position have no known basis and are ignored
+    fn sell(&mut self, size: Decimal, method: CostBasisMethod) -> (Decimal, Decimal) {
+        let size = size.min(self.size);
+
+        let cost = match method {
+            CostBasisMethod::Average => match self.size > Decimal::ZERO {
+                true => self.cost / self.size * size,
+                false => Decimal::ZERO,
+            },
+            CostBasisMethod::Fifo => {
+                let mut remaining = size;
+                let mut cost = Decimal::ZERO;
+
+                while remaining > Decimal::ZERO {
+                    let Some((lot_size, lot_price)) = self.lots.front_mut() else {
+                        break;
+                    };
+
+                    let consumed = remaining.min(*lot_size);
+                    cost += consumed * *lot_price;
+                    remaining -= consumed;
+                    *lot_size -= consumed;
+
+                    if *lot_size <= Decimal::ZERO {
+                        self.lots.pop_front();
+                    }
+                }
+
+                cost
+            }
+        };
+
+        self.size -= size;
+        self.cost -= cost;
+
+        (size, cost)
+    }
+}
+
+pub fn replay_fills<'a>(
+    fills: impl IntoIterator<Item = &'a Fill>,
+    method: CostBasisMethod,
+) -> Vec<PositionPnl> {
+    let mut positions: IndexMap<(String, String), (PositionPnl, CostBasis)> = IndexMap::new();
+
+    for fill in fills {
+        let key = (fill.proxy_address.to_lowercase(), fill.token_id.clone());
+        let (pnl, basis) = positions.entry(key).or_insert_with(|| {
+            (
+                PositionPnl {
+                    proxy_address: fill.proxy_address.clone(),
+                    token_id: fill.token_id.clone(),
+                    market: fill.market.clone(),
+                    outcome: fill.outcome.clone(),
+                    ..Default::default()
+                },
+                CostBasis::default(),
+            )
+        });
+
+        match fill.side {
+            Side::Buy => basis.buy(fill.size, fill.price),
+            Side::Sell => {
+                let (sold, cost) = basis.sell(fill.size, method);
+                pnl.realized += sold * fill.price - cost;
+            }
+        }
+
+        pnl.realized -= fill.fee;
+        pnl.fees += fill.fee;
+        pnl.position = basis.size;
+        pnl.cost_basis = basis.cost;
+    }
+
+    positions.into_values().map(|(pnl, _)| pnl).collect()
+}
+
+pub async fn mark_to_market(positions: &mut [PositionPnl], proxy: Option<&Proxy>) {
+    let token_ids = positions
+        .iter()
+        .filter(|position| position.position > Decimal::ZERO)
+        .map(|position| position.token_id.as_str())
+        .unique()
+        .collect_vec();
+
+    let mut mid_prices = HashMap::new();
+
+    for batch in token_ids.chunks(ORDER_BOOKS_BATCH_SIZE) {
+        match get_order_books(batch, proxy).await {
+            Ok(order_books) => {
+                for order_book in order_books {
+                    let best_bid = order_book.bids.iter().map(|order| order.price).max();
+                    let best_ask = order_book.asks.iter().map(|order| order.price).min();
+
+                    let mid_price = match (best_bid, best_ask) {
+                        (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+                        (bid, ask) => bid.or(ask),
+                    };
+
+                    if let Some(mid_price) = mid_price {
+                        mid_prices.insert(order_book.asset_id, mid_price);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Failed to get order books for marking positions: {e}"),
+        }
+    }
+
+    for position in positions.iter_mut() {
+        position.mark_price = mid_prices.get(&position.token_id).copied();
+    }
+}
+
+pub async fn calculate_pnl(
+    ledger: &FillsLedger,
+    method: CostBasisMethod,
+    proxy: Option<&Proxy>,
+) -> Vec<PositionPnl> {
+    let mut positions = replay_fills(&ledger.0, method);
+    mark_to_market(&mut positions, proxy).await;
+
+    positions
+}
+
+pub fn account_pnl(positions: &[PositionPnl], proxy_address: &str) -> (Decimal, Decimal) {
+    positions
+        .iter()
+        .filter(|position| position.proxy_address.eq_ignore_ascii_case(proxy_address))
+        .fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(realized, unrealized), position| {
+                (
+                    realized + position.realized,
+                    unrealized + position.unrealized(),
+                )
+            },
+        )
+}
+
+#[derive(Tabled, Serialize)]
+struct PnlEntry {
+    #[tabled(rename = "Proxy Address")]
+    #[serde(rename = "Proxy Address")]
+    proxy_address: String,
+
+    #[tabled(rename = "Market")]
+    #[serde(rename = "Market")]
+    market: String,
+
+    #[tabled(rename = "Outcome")]
+    #[serde(rename = "Outcome")]
+    outcome: String,
+
+    #[tabled(rename = "Position")]
+    #[serde(rename = "Position")]
+    position: String,
+
+    #[tabled(rename = "Avg cost")]
+    #[serde(rename = "Avg cost")]
+    average_cost: String,
+
+    #[tabled(rename = "Mark price")]
+    #[serde(rename = "Mark price")]
+    mark_price: String,
+
+    #[tabled(rename = "Realized P&L")]
+    #[serde(rename = "Realized P&L")]
+    realized: String,
+
+    #[tabled(rename = "Unrealized P&L")]
+    #[serde(rename = "Unrealized P&L")]
+    unrealized: String,
+}
+
+pub async fn display_pnl_report(db: Database, config: &Config) -> eyre::Result<()> {
+    let ledger = sync_fills(&db).await?;
+    let proxy = db.0.first().and_then(|account| account.proxy());
+
+    let positions = calculate_pnl(&ledger, config.cost_basis_method, proxy.as_ref()).await;
+
+    if positions.is_empty() {
+        tracing::info!("No fills recorded yet");
+        return Ok(());
+    }
+
+    let mut entries = positions
+        .iter()
+        .map(|position| PnlEntry {
+            proxy_address: position.proxy_address.clone(),
+            market: position.market.clone(),
+            outcome: position.outcome.clone(),
+            position: format!("{:.2}", position.position),
+            average_cost: match position.position > Decimal::ZERO {
+                true => format!("{:.4}", position.cost_basis / position.position),
+                false => "".to_string(),
+            },
+            mark_price: position
+                .mark_price
+                .map(|price| format!("{:.4}", price))
+                .unwrap_or_default(),
+            realized: format!("{:.2}", position.realized),
+            unrealized: format!("{:.2}", position.unrealized()),
+        })
+        .collect_vec();
+
+    for account in db.0.iter() {
+        let (realized, unrealized) = account_pnl(&positions, &account.proxy_address);
+
+        entries.push(PnlEntry {
+            proxy_address: account.proxy_address.clone(),
+            market: "Account total".to_string(),
+            outcome: "".to_string(),
+            position: "".to_string(),
+            average_cost: "".to_string(),
+            mark_price: "".to_string(),
+            realized: format!("{:.2}", realized),
+            unrealized: format!("{:.2}", unrealized),
+        });
+    }
+
+    entries.push(PnlEntry {
+        proxy_address: "Total".to_string(),
+        market: "".to_string(),
+        outcome: "".to_string(),
+        position: "".to_string(),
+        average_cost: "".to_string(),
+        mark_price: "".to_string(),
+        realized: format!(
+            "{:.2}",
+            positions.iter().map(|p| p.realized).sum::<Decimal>()
+        ),
+        unrealized: format!(
+            "{:.2}",
+            positions.iter().map(|p| p.unrealized()).sum::<Decimal>()
+        ),
+    });
+
+    let mut table = Table::new(&entries);
+    let table = table.with(Style::modern_rounded());
+
+    println!("{table}");
+
+    export_stats_to_csv(&entries, PNL_EXPORT_FILE_PATH)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn fill(side: Side, price: Decimal, size: Decimal, fee: Decimal) -> Fill {
+        Fill {
+            proxy_address: "0x1111111111111111111111111111111111111111".to_string(),
+            trade_id: String::new(),
+            order_id: String::new(),
+            token_id: "1".to_string(),
+            market: "0xmarket".to_string(),
+            outcome: "Yes".to_string(),
+            side,
+            price,
+            size,
+            fee,
+            tx_hash: String::new(),
+            timestamp: 0,
+        }
+    }
+
+    fn sample_fills() -> Vec<Fill> {
+        vec![
+            fill(Side::Buy, dec!(0.4), dec!(10), dec!(0)),
+            fill(Side::Buy, dec!(0.6), dec!(10), dec!(0)),
+            fill(Side::Sell, dec!(0.7), dec!(10), dec!(0.1)),
+        ]
+    }
+
+    #[test]
+    fn test_fifo_replay() {
+        let mut positions = replay_fills(&sample_fills(), CostBasisMethod::Fifo);
+        let position = &mut positions[0];
+
+        assert_eq!(position.realized, dec!(2.9));
+        assert_eq!(position.position, dec!(10));
+        assert_eq!(position.cost_basis, dec!(6));
+
+        position.mark_price = Some(dec!(0.5));
+        assert_eq!(position.unrealized(), dec!(-1));
+    }
+
+    #[test]
+    fn test_average_cost_replay() {
+        let mut positions = replay_fills(&sample_fills(), CostBasisMethod::Average);
+        let position = &mut positions[0];
+
+        assert_eq!(position.realized, dec!(1.9));
+        assert_eq!(position.cost_basis, dec!(5));
+
+        position.mark_price = Some(dec!(0.5));
+        assert_eq!(position.unrealized(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_oversell_is_capped_to_known_position() {
+        let fills = vec![
+            fill(Side::Buy, dec!(0.5), dec!(5), dec!(0)),
+            fill(Side::Sell, dec!(0.8), dec!(8), dec!(0)),
+        ];
+        let positions = replay_fills(&fills, CostBasisMethod::Fifo);
+
+        assert_eq!(positions[0].realized, dec!(1.5));
+        assert_eq!(positions[0].position, Decimal::ZERO);
+    }
+}
@@ -1,13 +1,7 @@
-use alloy::network::Ethereum;
-use alloy::primitives::Address;
-use alloy::providers::Provider;
-use alloy::transports::Transport;
 use reqwest::Proxy;
-use std::sync::Arc;
 use std::{future::Future, pin::Pin};
 use tokio::task::JoinSet;
 
-use crate::modules::registration::check_if_proxy_wallet_activated;
 use crate::polymarket::api::user::endpoints::get_user_last_activity_time;
 use crate::polymarket::api::user::schemas::UserActivityTime;
 use crate::{
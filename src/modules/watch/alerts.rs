@@ -0,0 +1,343 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::polymarket::api::clob::schemas::OrderBookData;
+
+// One `WATCHLIST` entry, either an event slug or a single token id with the thresholds to alert on
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+pub struct WatchEntry {
+    pub event_slug: Option<String>,
+    pub token_id: Option<String>,
+    pub price_above: Option<Decimal>,
+    pub price_below: Option<Decimal>,
+    pub max_spread: Option<Decimal>,
+    pub volume_spike: Option<Decimal>,
+}
+
+impl WatchEntry {
+    pub fn has_thresholds(&self) -> bool {
+        self.price_above.is_some()
+            || self.price_below.is_some()
+            || self.max_spread.is_some()
+            || self.volume_spike.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertKind {
+    PriceAbove {
+        price: Decimal,
+        threshold: Decimal,
+    },
+    PriceBelow {
+        price: Decimal,
+        threshold: Decimal,
+    },
+    SpreadWidened {
+        spread: Decimal,
+        max_spread: Decimal,
+    },
+    VolumeSpike {
+        volume: Decimal,
+        average: Decimal,
+        multiplier: Decimal,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub label: String,
+    pub token_id: String,
+    pub kind: AlertKind,
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertKind::PriceAbove { price, threshold } => {
+                write!(f, "mid price {price} is above {threshold}")
+            }
+            AlertKind::PriceBelow { price, threshold } => {
+                write!(f, "mid price {price} is below {threshold}")
+            }
+            AlertKind::SpreadWidened { spread, max_spread } => {
+                write!(f, "spread {spread} is wider than {max_spread}")
+            }
+            AlertKind::VolumeSpike {
+                volume,
+                average,
+                multiplier,
+            } => write!(
+                f,
+                "traded {volume} USDC, over {multiplier}x the average of {average} USDC"
+            ),
+        }
+    }
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.label, self.kind)
+    }
+}
+
+// What a token looked like at one check, volume is the USDC traded since the stream connected
+#[derive(Debug, Clone, Default)]
+pub struct MarketSnapshot {
+    pub mid: Option<Decimal>,
+    pub spread: Option<Decimal>,
+    pub traded_volume: Decimal,
+}
+
+impl MarketSnapshot {
+    pub fn new(book: Option<&OrderBookData>, traded_volume: Decimal) -> Self {
+        // Levels are not kept sorted once live updates are applied
+        let best_bid = book.and_then(|book| book.bids.iter().map(|order| order.price).max());
+        let best_ask = book.and_then(|book| book.asks.iter().map(|order| order.price).min());
+
+        let (mid, spread) = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => (Some((bid + ask) / Decimal::TWO), Some(ask - bid)),
+            _ => (None, None),
+        };
+
+        Self {
+            mid,
+            spread,
+            traded_volume,
+        }
+    }
+}
+
+// A watched token with the state needed to alert once per threshold crossing
+pub struct Watcher {
+    pub label: String,
+    pub token_id: String,
+    entry: WatchEntry,
+    above: bool,
+    below: bool,
+    wide: bool,
+    last_traded_volume: Option<Decimal>,
+    interval_volumes: VecDeque<Decimal>,
+    volume_window: usize,
+}
+
+impl Watcher {
+    pub fn new(label: String, token_id: String, entry: WatchEntry, volume_window: usize) -> Self {
+        Self {
+            label,
+            token_id,
+            entry,
+            above: false,
+            below: false,
+            wide: false,
+            last_traded_volume: None,
+            interval_volumes: VecDeque::with_capacity(volume_window),
+            volume_window,
+        }
+    }
+
+    // Price and spread alerts fire when a threshold is crossed and again only after the market
+    // has moved back, volume spikes compare the last interval with the average of the window
+    pub fn update(&mut self, snapshot: &MarketSnapshot) -> Vec<Alert> {
+        let mut alerts = vec![];
+
+        if let Some(price) = snapshot.mid {
+            if let Some(threshold) = self.entry.price_above {
+                if crossed(&mut self.above, price > threshold) {
+                    alerts.push(AlertKind::PriceAbove { price, threshold });
+                }
+            }
+
+            if let Some(threshold) = self.entry.price_below {
+                if crossed(&mut self.below, price < threshold) {
+                    alerts.push(AlertKind::PriceBelow { price, threshold });
+                }
+            }
+        }
+
+        if let (Some(spread), Some(max_spread)) = (snapshot.spread, self.entry.max_spread) {
+            if crossed(&mut self.wide, spread > max_spread) {
+                alerts.push(AlertKind::SpreadWidened { spread, max_spread });
+            }
+        }
+
+        if let Some(alert) = self.update_volume(snapshot.traded_volume) {
+            alerts.push(alert);
+        }
+
+        alerts
+            .into_iter()
+            .map(|kind| Alert {
+                label: self.label.clone(),
+                token_id: self.token_id.clone(),
+                kind,
+            })
+            .collect()
+    }
+
+    fn update_volume(&mut self, traded_volume: Decimal) -> Option<AlertKind> {
+        let last = self.last_traded_volume.replace(traded_volume)?;
+
+        // The counter restarts when the stream reconnects
+        let volume = match traded_volume >= last {
+            true => traded_volume - last,
+            false => traded_volume,
+        };
+
+        let alert = self.entry.volume_spike.and_then(|multiplier| {
+            if self.interval_volumes.is_empty() {
+                return None;
+            }
+
+            let average = self.interval_volumes.iter().sum::<Decimal>()
+                / Decimal::from(self.interval_volumes.len());
+
+            (average > Decimal::ZERO && volume > average * multiplier).then(|| {
+                AlertKind::VolumeSpike {
+                    volume,
+                    average: average.round_dp(2),
+                    multiplier,
+                }
+            })
+        });
+
+        if self.interval_volumes.len() == self.volume_window {
+            self.interval_volumes.pop_front();
+        }
+        self.interval_volumes.push_back(volume);
+
+        alert
+    }
+}
+
+fn crossed(active: &mut bool, condition: bool) -> bool {
+    let crossed = condition && !*active;
+    *active = condition;
+    crossed
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    const ORDER_BOOK: &str = include_str!("../../testing/fixtures/order_book.json");
+
+    fn snapshot(mid: Decimal, spread: Decimal, traded_volume: Decimal) -> MarketSnapshot {
+        MarketSnapshot {
+            mid: Some(mid),
+            spread: Some(spread),
+            traded_volume,
+        }
+    }
+
+    fn kinds(alerts: Vec<Alert>) -> Vec<AlertKind> {
+        alerts.into_iter().map(|alert| alert.kind).collect()
+    }
+
+    #[test]
+    fn test_snapshot_from_unsorted_book() {
+        let book = serde_json::from_str::<OrderBookData>(ORDER_BOOK).unwrap();
+
+        let snapshot = MarketSnapshot::new(Some(&book), dec!(12));
+        assert_eq!(snapshot.mid, Some(dec!(0.53)));
+        assert_eq!(snapshot.spread, Some(dec!(0.02)));
+
+        let empty = MarketSnapshot::new(None, Decimal::ZERO);
+        assert_eq!(empty.mid, None);
+    }
+
+    #[test]
+    fn test_price_and_spread_alerts_fire_once_per_crossing() {
+        let entry = WatchEntry {
+            token_id: Some("1".to_string()),
+            price_above: Some(dec!(0.6)),
+            price_below: Some(dec!(0.4)),
+            max_spread: Some(dec!(0.03)),
+            ..Default::default()
+        };
+        let mut watcher = Watcher::new("Test".to_string(), "1".to_string(), entry, 10);
+
+        assert!(watcher
+            .update(&snapshot(dec!(0.5), dec!(0.01), dec!(0)))
+            .is_empty());
+        assert_eq!(
+            kinds(watcher.update(&snapshot(dec!(0.62), dec!(0.05), dec!(0)))),
+            [
+                AlertKind::PriceAbove {
+                    price: dec!(0.62),
+                    threshold: dec!(0.6)
+                },
+                AlertKind::SpreadWidened {
+                    spread: dec!(0.05),
+                    max_spread: dec!(0.03)
+                }
+            ]
+        );
+        assert!(watcher
+            .update(&snapshot(dec!(0.65), dec!(0.04), dec!(0)))
+            .is_empty());
+
+        // Missing books keep the state until the market is seen again
+        assert!(watcher.update(&MarketSnapshot::default()).is_empty());
+        assert!(watcher
+            .update(&snapshot(dec!(0.61), dec!(0.01), dec!(0)))
+            .is_empty());
+
+        assert_eq!(
+            kinds(watcher.update(&snapshot(dec!(0.35), dec!(0.04), dec!(0)))),
+            [
+                AlertKind::PriceBelow {
+                    price: dec!(0.35),
+                    threshold: dec!(0.4)
+                },
+                AlertKind::SpreadWidened {
+                    spread: dec!(0.04),
+                    max_spread: dec!(0.03)
+                }
+            ]
+        );
+        assert_eq!(
+            kinds(watcher.update(&snapshot(dec!(0.7), dec!(0.01), dec!(0)))),
+            [AlertKind::PriceAbove {
+                price: dec!(0.7),
+                threshold: dec!(0.6)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_volume_spike_against_window_average() {
+        let entry = WatchEntry {
+            token_id: Some("1".to_string()),
+            volume_spike: Some(dec!(3)),
+            ..Default::default()
+        };
+        let mut watcher = Watcher::new("Test".to_string(), "1".to_string(), entry, 2);
+        let mut update =
+            |traded_volume| kinds(watcher.update(&snapshot(dec!(0.5), dec!(0.01), traded_volume)));
+
+        // Intervals of 0, 100 and 50 USDC
+        assert!(update(dec!(1000)).is_empty());
+        assert!(update(dec!(1000)).is_empty());
+        assert!(update(dec!(1100)).is_empty());
+        assert!(update(dec!(1150)).is_empty());
+
+        // The window holds the last two intervals, 75 on average
+        assert_eq!(
+            update(dec!(1400)),
+            [AlertKind::VolumeSpike {
+                volume: dec!(250),
+                average: dec!(75),
+                multiplier: dec!(3)
+            }]
+        );
+
+        // A reconnect restarts the counter, the new total is the interval volume
+        assert!(update(dec!(30)).is_empty());
+    }
+}
@@ -0,0 +1,174 @@
+pub mod alerts;
+
+use std::{collections::HashMap, time::Duration};
+
+use colored::Colorize;
+use itertools::Itertools;
+
+use crate::{
+    config::Config,
+    polymarket::api::{
+        clob::{
+            endpoints::get_order_books, schemas::OrderBookData, websocket::market::MarketStream,
+        },
+        events::endpoints::get_event_by_slug,
+    },
+    utils::notifier::{notify, NotificationKind},
+};
+use alerts::{Alert, MarketSnapshot, WatchEntry, Watcher};
+
+// Follows the `WATCHLIST` markets until interrupted, books come from the market channel and are
+// fetched over REST while the stream has none
+pub async fn watch_markets(config: &Config) -> eyre::Result<()> {
+    if config.watchlist.is_empty() {
+        eyre::bail!("WATCHLIST is empty, add event slugs or token ids to watch");
+    }
+
+    let mut watchers = resolve_watchers(&config.watchlist, config.watch_volume_window).await?;
+    let token_ids = watchers
+        .iter()
+        .map(|watcher| watcher.token_id.clone())
+        .unique()
+        .collect_vec();
+
+    tracing::info!(
+        "Watching {} markets every {} seconds",
+        watchers.len(),
+        config.watch_interval
+    );
+
+    let market_stream = MarketStream::subscribe(token_ids);
+    let mut interval = tokio::time::interval(Duration::from_secs(config.watch_interval));
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("Watch stopped");
+                return Ok(());
+            }
+        }
+
+        check_markets(&mut watchers, &market_stream).await;
+    }
+}
+
+async fn resolve_watchers(
+    entries: &[WatchEntry],
+    volume_window: usize,
+) -> eyre::Result<Vec<Watcher>> {
+    let mut watchers = vec![];
+
+    for entry in entries {
+        if let Some(token_id) = &entry.token_id {
+            watchers.push(Watcher::new(
+                format!("Token {token_id}"),
+                token_id.clone(),
+                entry.clone(),
+                volume_window,
+            ));
+            continue;
+        }
+
+        let Some(slug) = &entry.event_slug else {
+            continue;
+        };
+
+        let event = get_event_by_slug(slug, None)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Event `{slug}` was not found"))?;
+
+        // The first outcome of every market, the second one mirrors it
+        for market in event.markets.iter().filter(|market| market.active) {
+            let (Some(outcome), Some(token_id)) =
+                (market.outcomes.first(), market.clob_token_ids.first())
+            else {
+                continue;
+            };
+
+            let label = match event.markets.len() {
+                1 => format!("{} [{}]", event.title, outcome),
+                _ => format!("{} | {} [{}]", event.title, market.question, outcome),
+            };
+
+            watchers.push(Watcher::new(
+                label,
+                token_id.clone(),
+                entry.clone(),
+                volume_window,
+            ));
+        }
+    }
+
+    Ok(watchers)
+}
+
+async fn check_markets(watchers: &mut [Watcher], market_stream: &MarketStream) {
+    let books = latest_order_books(watchers, market_stream).await;
+
+    for watcher in watchers {
+        let snapshot = MarketSnapshot::new(
+            books.get(&watcher.token_id),
+            market_stream.traded_volume(&watcher.token_id),
+        );
+
+        match (snapshot.mid, snapshot.spread) {
+            (Some(mid), Some(spread)) => tracing::info!(
+                "{} | mid {mid} | spread {spread} | traded {} USDC",
+                watcher.label,
+                snapshot.traded_volume
+            ),
+            _ => tracing::warn!("{} | order book is empty or unavailable", watcher.label),
+        }
+
+        for alert in watcher.update(&snapshot) {
+            send_alert(&alert).await;
+        }
+    }
+}
+
+async fn latest_order_books(
+    watchers: &[Watcher],
+    market_stream: &MarketStream,
+) -> HashMap<String, OrderBookData> {
+    let mut books = HashMap::new();
+    let mut missing = vec![];
+
+    for token_id in watchers
+        .iter()
+        .map(|watcher| watcher.token_id.as_str())
+        .unique()
+    {
+        match market_stream.book(token_id) {
+            Some(book) => {
+                books.insert(token_id.to_string(), book);
+            }
+            None => missing.push(token_id),
+        }
+    }
+
+    if !missing.is_empty() {
+        match get_order_books(&missing, None).await {
+            Ok(fetched) => books.extend(
+                fetched
+                    .into_iter()
+                    .map(|book| (book.asset_id.clone(), book)),
+            ),
+            Err(e) => tracing::warn!("Failed to fetch {} order books: {e}", missing.len()),
+        }
+    }
+
+    books
+}
+
+async fn send_alert(alert: &Alert) {
+    println!("{}", format!("⚠ {alert}").yellow());
+    tracing::warn!("Alert | {alert}");
+
+    notify(
+        NotificationKind::WatchAlert,
+        format!("Watch alert: {}", alert.label),
+        format!("{} (token {})", alert.kind, alert.token_id),
+    )
+    .await;
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use alloy::{
     network::Ethereum,
@@ -12,10 +12,17 @@ use reqwest::Url;
 
 use crate::{
     config::Config,
-    db::{account::Account, database::Database},
+    db::{
+        account::Account,
+        database::Database,
+        storage::{record_transaction, TransactionRecord},
+    },
     onchain::{client::EvmClient, constants::POLYGON_EXPLORER_TX_BASE_URL, types::token::Token},
     polymarket::api::{relayer::common::withdraw_usdc, typedefs::AmpCookie},
-    utils::misc::pretty_sleep,
+    utils::{
+        misc::pretty_sleep,
+        notifier::{notify, NotificationKind},
+    },
 };
 
 pub async fn withdraw_for_all(db: &mut Database, config: &Config) -> eyre::Result<()> {
@@ -27,6 +34,10 @@ pub async fn withdraw_for_all(db: &mut Database, config: &Config) -> eyre::Resul
             .on_http(Url::parse(&config.polygon_rpc_url)?),
     );
 
+    let mut withdrawn = 0;
+    // Failed withdrawals are retried, only the first failure of an account is notified
+    let mut failed = HashSet::new();
+
     while !db.0.is_empty() {
         let index = rng.gen_range(0..db.0.len());
         let account = &db.0[index];
@@ -34,14 +45,31 @@ pub async fn withdraw_for_all(db: &mut Database, config: &Config) -> eyre::Resul
         match withdraw_full_balance(account, provider.clone()).await {
             Ok(_) => {
                 db.0.remove(index);
+                withdrawn += 1;
                 pretty_sleep(config.withdraw_delay_range).await;
             }
             Err(e) => {
-                tracing::error!("Withdrawal failed: {e}")
+                tracing::error!("Withdrawal failed: {e}");
+
+                if failed.insert(account.proxy_address.clone()) {
+                    notify(
+                        NotificationKind::AccountFailed,
+                        "Withdrawal failed",
+                        format!("{} | {e}", account.proxy_address),
+                    )
+                    .await;
+                }
             }
         }
     }
 
+    notify(
+        NotificationKind::ModuleCompleted,
+        "Withdraw finished",
+        format!("{withdrawn} proxy wallets withdrawn"),
+    )
+    .await;
+
     Ok(())
 }
 
@@ -79,7 +107,66 @@ where
     )
     .await?;
 
+    record_transaction(TransactionRecord::Withdrawal {
+        proxy_address: account.proxy_address.clone(),
+        recipient: to.to_string(),
+        amount: Token::USDCE.to_amount(balance)?,
+        transaction_id: tx_hash.clone(),
+    });
+
     tracing::info!("USDC.e withdrawn: {POLYGON_EXPLORER_TX_BASE_URL}{tx_hash}");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, U256},
+        sol_types::SolCall,
+    };
+
+    use super::*;
+    use crate::{
+        onchain::client::IERC20::transferCall,
+        polymarket::api::relayer::constants::UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
+        testing::mock_server::{mock_server, random_account},
+    };
+
+    #[tokio::test]
+    async fn test_withdraw_full_balance_offline() {
+        let mock = mock_server();
+        let config = mock.config();
+        let provider = Arc::new(
+            ProviderBuilder::new()
+                .with_recommended_fillers()
+                .on_http(Url::parse(&config.polygon_rpc_url).unwrap()),
+        );
+
+        let mut account = random_account();
+        let proxy_wallet = account.get_proxy_address();
+        let balance = U256::from(12_345_678);
+        mock.sign_in(&mut account);
+        mock.set_usdc_balance(proxy_wallet, balance);
+
+        withdraw_full_balance(&account, provider).await.unwrap();
+
+        let transactions = mock.relayer_transactions(proxy_wallet);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0]["type"], "SAFE");
+        assert_eq!(transactions[0]["nonce"], "0");
+        assert_eq!(
+            transactions[0]["to"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()
+                .unwrap(),
+            UCHILD_ERC20_PROXY_CONTRACT_ADDRESS
+        );
+
+        let data = const_hex::decode(transactions[0]["data"].as_str().unwrap()).unwrap();
+        let transfer = transferCall::abi_decode(&data, true).unwrap();
+        assert_eq!(transfer.to, account.get_recipient_address());
+        assert_eq!(transfer.amount, balance);
+    }
+}
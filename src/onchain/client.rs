@@ -1,4 +1,10 @@
 use super::types::token::Token;
+use crate::{
+    polymarket::api::relayer::{
+        constants::CONDITIONAL_TOKENS_CONTRACT_ADDRESS, tx_builder::describe_calls,
+    },
+    utils::{dry_run, poly::get_proxy_wallet_address},
+};
 use alloy::{
     network::{Ethereum, EthereumWallet, TransactionBuilder},
     primitives::{Address, Bytes, U256},
@@ -24,6 +30,12 @@ sol! {
         function approve(address spender, uint256 amount) external returns (bool);
         function transferFrom(address from, address to, uint256 amount) external returns (bool);
     }
+
+    #[sol(rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    contract IERC1155 {
+        function balanceOf(address account, uint256 id) external view returns (uint256);
+    }
 }
 
 pub struct EvmClient<P, T>
@@ -93,6 +105,24 @@ where
         Ok(balance)
     }
 
+    pub async fn get_conditional_token_balance(
+        &self,
+        token_id: U256,
+        wallet_address: Option<Address>,
+    ) -> eyre::Result<U256> {
+        let address = wallet_address.unwrap_or(self.address());
+
+        let contract_instance =
+            IERC1155::new(CONDITIONAL_TOKENS_CONTRACT_ADDRESS, self.provider.clone());
+        let balance = contract_instance
+            .balanceOf(address, token_id)
+            .call()
+            .await?
+            ._0;
+
+        Ok(balance)
+    }
+
     pub async fn send_transaction(
         &self,
         to: Address,
@@ -115,6 +145,8 @@ where
             .with_chain_id(self.chain as u64)
             .with_from(self.address());
 
+        let calls = describe_calls(to, input.as_ref().map_or(&[], |data| data.as_ref()));
+
         if let Some(data) = input {
             tx_request.set_input(data);
         }
@@ -122,6 +154,22 @@ where
         let gas_limit = self.provider.estimate_gas(&tx_request).await?;
         tx_request.set_gas_limit(gas_limit);
 
+        // Simulated against the latest block, nothing is signed or broadcast
+        if dry_run::is_enabled() {
+            self.provider.call(&tx_request).await?;
+            dry_run::record(
+                get_proxy_wallet_address(Arc::new(self.signer.clone())),
+                "Transaction",
+                format!(
+                    "from {}, value {value}, gas {gas_limit}: {}",
+                    self.address(),
+                    calls.join("; ")
+                ),
+            );
+
+            return Ok(true);
+        }
+
         let signed_transaction = tx_request.build(&self.wallet).await?;
         let pending_tx = self.provider.send_tx_envelope(signed_transaction).await?;
         let receipt = pending_tx.get_receipt().await?;
@@ -155,3 +203,67 @@ where
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::{onchain::constants::USDCE_CONTRACT_ADDRESS, testing::anvil::LocalChain};
+
+    #[tokio::test]
+    #[ignore = "needs anvil"]
+    async fn test_get_token_balance() {
+        let chain = LocalChain::spawn().await;
+        let client = chain.client();
+        let owner = address!("00000000000000000000000000000000000000aa");
+
+        chain
+            .set_token_balance(USDCE_CONTRACT_ADDRESS, owner, U256::from(1_500_000))
+            .await;
+
+        let balance = client
+            .get_token_balance(&Token::USDCE, Some(owner))
+            .await
+            .unwrap();
+        assert_eq!(balance, U256::from(1_500_000));
+
+        let balance = client.get_token_balance(&Token::USDCE, None).await.unwrap();
+        assert_eq!(balance, U256::ZERO);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs anvil"]
+    async fn test_transfer() {
+        let chain = LocalChain::spawn().await;
+        let client = chain.client();
+        let recipient = address!("00000000000000000000000000000000000000bb");
+
+        chain
+            .set_token_balance(USDCE_CONTRACT_ADDRESS, chain.address(), U256::from(1_000))
+            .await;
+
+        let success = client
+            .transfer(recipient, U256::from(400), &Token::USDCE)
+            .await
+            .unwrap();
+        assert!(success);
+
+        let sender_balance = client.get_token_balance(&Token::USDCE, None).await.unwrap();
+        let recipient_balance = client
+            .get_token_balance(&Token::USDCE, Some(recipient))
+            .await
+            .unwrap();
+        assert_eq!(sender_balance, U256::from(600));
+        assert_eq!(recipient_balance, U256::from(400));
+
+        // The token reverts, so gas estimation fails before anything is sent
+        let result = client
+            .transfer(recipient, U256::from(601), &Token::USDCE)
+            .await;
+        assert!(result.is_err());
+
+        let sender_balance = client.get_token_balance(&Token::USDCE, None).await.unwrap();
+        assert_eq!(sender_balance, U256::from(600));
+    }
+}
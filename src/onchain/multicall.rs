@@ -6,7 +6,7 @@ use alloy::{
     primitives::{address, Address, U256},
     providers::Provider,
     sol,
-    sol_types::{SolCall, SolValue},
+    sol_types::SolCall,
     transports::Transport,
 };
 use itertools::Itertools;
@@ -17,11 +17,9 @@ use super::{
     types::token::Token,
 };
 use crate::onchain::multicall::GnosisSafeL2::getOwnersCall;
-use crate::{
-    onchain::constants::EXPECTED_CHECK_APPROVALS_RESULT,
-    polymarket::api::relayer::constants::{
-        CONDITIONAL_TOKENS_CONTRACT_ADDRESS, UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
-    },
+use crate::polymarket::api::relayer::constants::{
+    CONDITIONAL_TOKENS_CONTRACT_ADDRESS, NEG_RISK_ADAPTER_CONTRACT_ADDRESS,
+    UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
 };
 
 sol! {
@@ -58,8 +56,6 @@ sol! {
 const CTF_EXCHANGE_CONTRACT_ADDRESS: Address = address!("4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E");
 const NEG_RISK_CTF_EXCHANGE_CONTRACT_ADDRESS: Address =
     address!("C5d563A36AE78145C45a50134d48A1215220f80a");
-const NEG_RISK_ADAPTER_CONTRACT_ADDRESS: Address =
-    address!("d91E80cF2E7be2e162c6513ceD06f1dD0dA35296");
 
 pub async fn multicall_balance_of<P, T>(
     addresses: &[Address],
@@ -186,17 +182,127 @@ where
         })
         .collect_vec();
 
-    let result = multicall_instance
+    // Every allowance has to be non-zero and every operator approved, `isApprovedForAll` decodes
+    // as 0 or 1. A partially approved wallet still needs the whole approval bundle
+    let approved = multicall_instance
         .aggregate3(calls)
         .call()
         .await?
         .returnData
-        .into_iter()
-        .flat_map(|res| res.abi_encode())
-        .collect_vec();
+        .iter()
+        .all(|res| {
+            <sol! { uint256 }>::abi_decode(&res.returnData, false)
+                .is_ok_and(|value| value > U256::ZERO)
+        });
+
+    Ok(approved)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+    use crate::{onchain::constants::USDCE_CONTRACT_ADDRESS, testing::anvil::LocalChain};
+
+    const WALLET: Address = address!("00000000000000000000000000000000000000aa");
+
+    #[tokio::test]
+    #[ignore = "needs anvil"]
+    async fn test_multicall_balance_of() {
+        let chain = LocalChain::spawn().await;
+        let wallets = [
+            address!("00000000000000000000000000000000000000a1"),
+            address!("00000000000000000000000000000000000000a2"),
+            address!("00000000000000000000000000000000000000a3"),
+        ];
+
+        chain
+            .set_token_balance(USDCE_CONTRACT_ADDRESS, wallets[0], U256::from(10))
+            .await;
+        chain
+            .set_token_balance(USDCE_CONTRACT_ADDRESS, wallets[2], U256::from(30_000_000))
+            .await;
+
+        let balances = multicall_balance_of(&wallets, Token::USDCE, chain.provider.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(balances, vec![U256::from(10), U256::ZERO, U256::from(30_000_000)]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs anvil"]
+    async fn test_multicall_get_owners() {
+        let chain = LocalChain::spawn().await;
+        let safes = [
+            address!("00000000000000000000000000000000000005a1"),
+            address!("00000000000000000000000000000000000005a2"),
+        ];
+        let owners = [
+            address!("00000000000000000000000000000000000000b1"),
+            address!("00000000000000000000000000000000000000b2"),
+        ];
+
+        chain.deploy_safe(safes[0], &owners[..1]).await;
+        chain.deploy_safe(safes[1], &owners).await;
+
+        let result = multicall_get_owners(&safes, chain.provider.clone()).await.unwrap();
+
+        assert_eq!(result, vec![owners[..1].to_vec(), owners.to_vec()]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs anvil"]
+    async fn test_check_token_approvals() {
+        let chain = LocalChain::spawn().await;
+        let allowances = [
+            CONDITIONAL_TOKENS_CONTRACT_ADDRESS,
+            CTF_EXCHANGE_CONTRACT_ADDRESS,
+            NEG_RISK_CTF_EXCHANGE_CONTRACT_ADDRESS,
+            NEG_RISK_ADAPTER_CONTRACT_ADDRESS,
+        ];
+        let operators = [
+            CTF_EXCHANGE_CONTRACT_ADDRESS,
+            NEG_RISK_CTF_EXCHANGE_CONTRACT_ADDRESS,
+            NEG_RISK_ADAPTER_CONTRACT_ADDRESS,
+        ];
+
+        let approved = check_token_approvals(chain.provider.clone(), WALLET).await.unwrap();
+        assert!(!approved);
+
+        for spender in allowances {
+            chain
+                .set_approval(UCHILD_ERC20_PROXY_CONTRACT_ADDRESS, WALLET, spender, U256::MAX)
+                .await;
+        }
+
+        // USDC allowances alone leave the conditional tokens unapproved
+        let approved = check_token_approvals(chain.provider.clone(), WALLET).await.unwrap();
+        assert!(!approved);
+
+        for operator in &operators[..2] {
+            chain
+                .set_approval(CONDITIONAL_TOKENS_CONTRACT_ADDRESS, WALLET, *operator, U256::from(1))
+                .await;
+        }
+
+        let approved = check_token_approvals(chain.provider.clone(), WALLET).await.unwrap();
+        assert!(!approved);
+
+        chain
+            .set_approval(CONDITIONAL_TOKENS_CONTRACT_ADDRESS, WALLET, operators[2], U256::from(1))
+            .await;
+
+        let approved = check_token_approvals(chain.provider.clone(), WALLET).await.unwrap();
+        assert!(approved);
+
+        // A spent allowance needs the approvals again
+        chain
+            .set_approval(UCHILD_ERC20_PROXY_CONTRACT_ADDRESS, WALLET, allowances[1], U256::ZERO)
+            .await;
 
-    match result != EXPECTED_CHECK_APPROVALS_RESULT {
-        true => Ok(true),
-        false => Ok(false),
+        let approved = check_token_approvals(chain.provider.clone(), WALLET).await.unwrap();
+        assert!(!approved);
     }
 }
@@ -1,8 +1,12 @@
 use std::fmt::Display;
 
 use alloy::primitives::{Address, U256};
+use rust_decimal::Decimal;
 
-use crate::onchain::constants::USDCE_CONTRACT_ADDRESS;
+use crate::{
+    onchain::constants::USDCE_CONTRACT_ADDRESS,
+    utils::decimal::{from_units, to_units},
+};
 
 pub struct Token {
     pub contract_address: Address,
@@ -19,8 +23,12 @@ impl Token {
         is_erc20: true,
     };
 
-    pub fn to_wei(&self, amount: f64) -> U256 {
-        U256::from(amount * 10f64.powi(self.decimals as i32))
+    pub fn to_wei(&self, amount: Decimal) -> eyre::Result<U256> {
+        to_units(amount, self.decimals as u32)
+    }
+
+    pub fn to_amount(&self, value: U256) -> eyre::Result<Decimal> {
+        from_units(value, self.decimals as u32)
     }
 }
 
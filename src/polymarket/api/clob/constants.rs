@@ -78,5 +78,8 @@ pub fn get_contract_config(chain_id: u64) -> eyre::Result<&'static ContractConfi
     }
 }
 
+// USDC and the conditional tokens both use 6 decimals
+pub const COLLATERAL_TOKEN_DECIMALS: u32 = 6;
+
 pub const PROTOCOL_NAME: &str = "Polymarket CTF Exchange";
 pub const PROTOCOL_VERSION: &str = "1";
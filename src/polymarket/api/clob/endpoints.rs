@@ -3,6 +3,8 @@ use std::{collections::HashMap, sync::Arc};
 use alloy::signers::Signer;
 use itertools::Itertools;
 use reqwest::{Method, Proxy, StatusCode};
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     db::account::Account,
@@ -10,15 +12,23 @@ use crate::{
     polymarket::api::{
         clob::schemas::OrderBookData,
         typedefs::{HeaderMapSerializeable, LayerOneClobAuthHeaders, LayerTwoClobAuthHeaders},
+        urls::{api_url, Api},
+    },
+    utils::{
+        dry_run::{self, DRY_RUN_ID},
+        fetch::{send_http_request_with_retries, RequestParams},
     },
-    utils::fetch::{send_http_request_with_retries, RequestParams},
 };
 
 use super::schemas::{
-    ClobApiKeyResponseBody, GetTickSizeResponseBody, NegRiskResponseBody, OrderRequest,
-    PlaceOrderResponseBody, TokenId,
+    CancelOrderRequestBody, CancelOrdersResponseBody, ClobApiKeyResponseBody,
+    GetTickSizeResponseBody, NegRiskResponseBody, OpenOrder, OrderRequest, PaginatedResponseBody,
+    PlaceOrderResponseBody, TokenId, Trade,
 };
 
+const INITIAL_CURSOR: &str = "MA==";
+const END_CURSOR: &str = "LTE=";
+
 pub async fn derive_api_key<S>(
     signer: Arc<S>,
     proxy: Option<&Proxy>,
@@ -33,7 +43,7 @@ where
     query_args.insert("geo_block_token", "");
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/auth/derive-api-key",
+        url: &api_url(Api::Clob, "/auth/derive-api-key"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -62,6 +72,16 @@ pub async fn create_api_key<S>(
 where
     S: Signer + Send + Sync,
 {
+    if dry_run::is_enabled() {
+        dry_run::record(signer.address(), "Create API key", "POST /auth/api-key");
+
+        return Ok(ClobApiKeyResponseBody {
+            api_key: DRY_RUN_ID.to_string(),
+            secret: DRY_RUN_ID.to_string(),
+            passphrase: DRY_RUN_ID.to_string(),
+        });
+    }
+
     let headers = LayerOneClobAuthHeaders::new(signer.clone())
         .await
         .to_headermap();
@@ -69,7 +89,7 @@ where
     query_args.insert("geo_block_token", "");
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/auth/api-key",
+        url: &api_url(Api::Clob, "/auth/api-key"),
         method: Method::POST,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -88,15 +108,14 @@ where
     Ok(response.body.unwrap())
 }
 
-#[allow(unused)]
-pub async fn get_tick_size(proxy: Option<&Proxy>, token_id: &str) -> Result<f64, CustomError> {
+pub async fn get_tick_size(proxy: Option<&Proxy>, token_id: &str) -> Result<Decimal, CustomError> {
     let query_args = [("token_id", token_id), ("geo_block_token", "")]
         .iter()
         .map(|(arg, value)| (*arg, *value))
         .collect();
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/tick-size",
+        url: &api_url(Api::Clob, "/tick-size"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -115,7 +134,6 @@ pub async fn get_tick_size(proxy: Option<&Proxy>, token_id: &str) -> Result<f64,
     Ok(response.body.unwrap().minimum_tick_size)
 }
 
-#[allow(unused)]
 pub async fn get_order_books(
     token_ids: &[&str],
     proxy: Option<&Proxy>,
@@ -126,7 +144,7 @@ pub async fn get_order_books(
         .collect_vec();
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/books",
+        url: &api_url(Api::Clob, "/books"),
         method: Method::POST,
         body: Some(ids),
         query_args: None,
@@ -153,7 +171,7 @@ pub async fn get_order_book(
     query_args.insert("token_id", token_id);
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/book",
+        url: &api_url(Api::Clob, "/book"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -177,7 +195,7 @@ pub async fn get_neg_risk(token_id: &str, proxy: Option<&Proxy>) -> Result<bool,
     query_args.insert("token_id", token_id);
 
     let request_params = RequestParams {
-        url: "https://clob.polymarket.com/neg-risk",
+        url: &api_url(Api::Clob, "/neg-risk"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -200,6 +218,23 @@ pub async fn place_order(
     account: &Account,
     order: OrderRequest,
 ) -> Result<PlaceOrderResponseBody, CustomError> {
+    if dry_run::is_enabled() {
+        println!("{}", serde_json::to_string_pretty(&order).unwrap());
+        dry_run::record(
+            &account.proxy_address,
+            "Place order",
+            format!(
+                "{} token {}, maker amount {}, taker amount {}",
+                order.order.side,
+                order.order.token_id,
+                order.order.maker_amount,
+                order.order.taker_amount
+            ),
+        );
+
+        return Ok(PlaceOrderResponseBody::dry_run(DRY_RUN_ID));
+    }
+
     let mut query_args = HashMap::new();
     query_args.insert("geo_block_token", "");
 
@@ -216,7 +251,7 @@ pub async fn place_order(
     .to_headermap();
 
     let request_params = RequestParams {
-        url: &format!("https://clob.polymarket.com{path}"),
+        url: &api_url(Api::Clob, path),
         method,
         body: Some(order),
         query_args: Some(query_args),
@@ -239,3 +274,142 @@ pub async fn place_order(
         false => Err(CustomError::ClobApiError(body.error_msg)),
     }
 }
+
+pub async fn get_open_orders(account: &Account) -> Result<Vec<OpenOrder>, CustomError> {
+    get_paginated_data(account, "/data/orders", &[]).await
+}
+
+pub async fn get_trades(account: &Account) -> Result<Vec<Trade>, CustomError> {
+    get_paginated_data(
+        account,
+        "/data/trades",
+        &[("maker_address", &account.proxy_address)],
+    )
+    .await
+}
+
+async fn get_paginated_data<T: DeserializeOwned>(
+    account: &Account,
+    path: &str,
+    query: &[(&str, &str)],
+) -> Result<Vec<T>, CustomError> {
+    let method = Method::GET;
+
+    let mut items = vec![];
+    let mut next_cursor = INITIAL_CURSOR.to_string();
+
+    while next_cursor != END_CURSOR {
+        let headers = LayerTwoClobAuthHeaders::new(
+            &account.signer().address().to_string(),
+            account.get_api_creds().unwrap(),
+            method.as_str(),
+            path,
+            None,
+            None,
+        )
+        .to_headermap();
+
+        let query_args = query
+            .iter()
+            .copied()
+            .chain([("next_cursor", next_cursor.as_str())])
+            .collect();
+
+        let request_params = RequestParams {
+            url: &api_url(Api::Clob, path),
+            method: method.clone(),
+            body: None::<serde_json::Value>,
+            query_args: Some(query_args),
+        };
+
+        let response = send_http_request_with_retries::<PaginatedResponseBody<T>>(
+            &request_params,
+            Some(&headers),
+            account.proxy().as_ref(),
+            None,
+            None,
+            |_| true,
+        )
+        .await?;
+
+        let body = response.body.unwrap();
+
+        items.extend(body.data);
+        next_cursor = body.next_cursor;
+    }
+
+    Ok(items)
+}
+
+pub async fn cancel_order(
+    account: &Account,
+    order_id: &str,
+) -> Result<CancelOrdersResponseBody, CustomError> {
+    let body = CancelOrderRequestBody { order_id };
+
+    send_cancel_request(account, "/order", Some(body)).await
+}
+
+pub async fn cancel_orders(
+    account: &Account,
+    order_ids: &[&str],
+) -> Result<CancelOrdersResponseBody, CustomError> {
+    send_cancel_request(account, "/orders", Some(order_ids)).await
+}
+
+pub async fn cancel_all_orders(account: &Account) -> Result<CancelOrdersResponseBody, CustomError> {
+    send_cancel_request(account, "/cancel-all", None::<serde_json::Value>).await
+}
+
+async fn send_cancel_request<B: Serialize>(
+    account: &Account,
+    path: &str,
+    body: Option<B>,
+) -> Result<CancelOrdersResponseBody, CustomError> {
+    let method = Method::DELETE;
+    let serialized_body = body
+        .as_ref()
+        .map(|body| serde_json::to_string(body).unwrap());
+
+    if dry_run::is_enabled() {
+        dry_run::record(
+            &account.proxy_address,
+            "Cancel orders",
+            format!("DELETE {path} {}", serialized_body.unwrap_or_default()),
+        );
+
+        return Ok(CancelOrdersResponseBody {
+            canceled: vec![],
+            not_canceled: HashMap::new(),
+        });
+    }
+
+    let headers = LayerTwoClobAuthHeaders::new(
+        &account.signer().address().to_string(),
+        account.get_api_creds().unwrap(),
+        method.as_str(),
+        path,
+        serialized_body.as_deref(),
+        None,
+    )
+    .to_headermap();
+
+    let request_params = RequestParams {
+        url: &api_url(Api::Clob, path),
+        method,
+        body,
+        query_args: None,
+    };
+
+    let response = send_http_request_with_retries::<CancelOrdersResponseBody>(
+        &request_params,
+        Some(&headers),
+        account.proxy().as_ref(),
+        None,
+        None,
+        |_| true,
+    )
+    .await?;
+
+    Ok(response.body.unwrap())
+}
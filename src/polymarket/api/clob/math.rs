@@ -1,7 +1,13 @@
+use std::cmp::Reverse;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
 use super::{
     schemas::{AccumulatedOrder, Order, OrderBookData},
-    typedefs::Side,
+    typedefs::{PriceProtection, Side},
 };
+use crate::errors::price_protection::PriceProtectionError;
 
 pub trait ClobPrecision {
     fn round_normal(self, decimals: u32) -> Self;
@@ -13,46 +19,27 @@ pub trait ClobPrecision {
     fn decimal_places(self) -> u32;
 }
 
-impl ClobPrecision for f64 {
+// The clob client rounds binary floats, with exact decimals no representation error can push a
+// value across a rounding boundary
+impl ClobPrecision for Decimal {
     fn round_normal(self, decimals: u32) -> Self {
-        if self.decimal_places() <= decimals {
-            return self;
-        }
-        let factor = 10_f64.powi(decimals as i32);
-        (self * factor).round() / factor
+        self.round_dp_with_strategy(decimals, RoundingStrategy::MidpointAwayFromZero)
     }
 
     fn round_down(self, decimals: u32) -> Self {
-        if self.decimal_places() <= decimals {
-            return self;
-        }
-        let factor = 10_f64.powi(decimals as i32);
-        (self * factor).floor() / factor
+        self.round_dp_with_strategy(decimals, RoundingStrategy::ToNegativeInfinity)
     }
 
     fn round_up(self, decimals: u32) -> Self {
-        if self.decimal_places() <= decimals {
-            return self;
-        }
-        let factor = 10_f64.powi(decimals as i32);
-        (self * factor).ceil() / factor
+        self.round_dp_with_strategy(decimals, RoundingStrategy::ToPositiveInfinity)
     }
 
     fn decimal_places(self) -> u32 {
-        if self.fract() == 0.0 {
-            return 0;
-        }
-
-        let num_str = self.to_string();
-        if let Some(pos) = num_str.find('.') {
-            return (num_str.len() - pos - 1) as u32;
-        }
-
-        0
+        self.normalize().scale()
     }
 }
 
-pub fn adjust_amount(mut amount: f64, allowed_decimals: u32) -> f64 {
+pub fn adjust_amount(mut amount: Decimal, allowed_decimals: u32) -> Decimal {
     if amount.decimal_places() > allowed_decimals {
         amount = amount.round_up(allowed_decimals + 4);
         if amount.decimal_places() > allowed_decimals {
@@ -65,10 +52,10 @@ pub fn adjust_amount(mut amount: f64, allowed_decimals: u32) -> f64 {
 fn sort_orders(side: Side, orders: &mut [Order]) -> Vec<Order> {
     match side {
         Side::Buy => {
-            orders.sort_by(|a, b| b.price.partial_cmp(&a.price).unwrap());
+            orders.sort_by_key(|order| Reverse(order.price));
         }
         Side::Sell => {
-            orders.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+            orders.sort_by_key(|order| order.price);
         }
     }
     orders.to_vec()
@@ -78,16 +65,17 @@ fn calculate_accumulated_values(orders: &[Order]) -> Vec<AccumulatedOrder> {
     let mut accumulated_orders = vec![];
 
     for current_order in orders.iter() {
-        if current_order.size > 0.0 {
+        if current_order.size > Decimal::ZERO {
             let value = (current_order.size * current_order.price).round_normal(2);
 
             let previous_order = accumulated_orders.last();
 
             let net_value = (value
-                + previous_order.map_or(0.0, |prev: &AccumulatedOrder| prev.net_value))
+                + previous_order.map_or(Decimal::ZERO, |prev: &AccumulatedOrder| prev.net_value))
+            .round_normal(2);
+            let net_size = (current_order.size
+                + previous_order.map_or(Decimal::ZERO, |prev| prev.net_size))
             .round_normal(2);
-            let net_size = (current_order.size + previous_order.map_or(0.0, |prev| prev.net_size))
-                .round_normal(2);
 
             accumulated_orders.push(AccumulatedOrder {
                 price: current_order.price,
@@ -102,38 +90,204 @@ fn calculate_accumulated_values(orders: &[Order]) -> Vec<AccumulatedOrder> {
     accumulated_orders
 }
 
-fn find_buy_price(asks: &[AccumulatedOrder], unit_amount: f64, slippage: f64) -> f64 {
-    let adjusted_value = unit_amount * (1.0 + slippage / 100.0);
+// Depth is measured in USDC for buys and in shares for sells, the fill price is the price of the
+// level where the accumulated depth first covers the amount
+fn find_fill_price(
+    side: &Side,
+    levels: &[AccumulatedOrder],
+    amount: Decimal,
+) -> Result<Decimal, PriceProtectionError> {
+    let depth = |order: &AccumulatedOrder| match side {
+        Side::Buy => order.net_value,
+        Side::Sell => order.net_size,
+    };
 
-    asks.iter()
-        .find(|order| order.net_value >= adjusted_value)
-        .map_or(0.0, |order| order.price)
-}
-
-fn find_sell_price(bids: &[AccumulatedOrder], usdc_amount: f64, slippage: f64) -> f64 {
-    let adjusted_size = usdc_amount * (1.0 + slippage / 100.0);
+    let Some(deepest) = levels.last() else {
+        return Err(PriceProtectionError::EmptyBook {
+            side: side.clone(),
+            amount,
+        });
+    };
 
-    bids.iter()
-        .find(|order| order.net_size >= adjusted_size)
-        .map_or(0.0, |order| order.price)
+    levels
+        .iter()
+        .find(|order| depth(order) >= amount)
+        .map(|order| order.price)
+        .ok_or_else(|| PriceProtectionError::InsufficientDepth {
+            side: side.clone(),
+            amount,
+            available: depth(deepest),
+        })
 }
 
 pub fn calculate_market_price(
     side: Side,
     mut book_data: OrderBookData,
-    unit_amount: f64,
-    slippage: Option<f64>,
-) -> f64 {
-    let slippage = slippage.unwrap_or(0.1);
+    amount: Decimal,
+    protection: &PriceProtection,
+) -> Result<Decimal, PriceProtectionError> {
+    let levels = match side {
+        Side::Buy => calculate_accumulated_values(&sort_orders(Side::Sell, &mut book_data.asks)),
+        Side::Sell => calculate_accumulated_values(&sort_orders(Side::Buy, &mut book_data.bids)),
+    };
 
-    let sorted_asks = sort_orders(Side::Sell, &mut book_data.asks);
-    let accumulated_asks = calculate_accumulated_values(&sorted_asks);
+    let price = find_fill_price(&side, &levels, amount)?;
 
-    let sorted_bids = sort_orders(Side::Buy, &mut book_data.bids);
-    let accumulated_bids = calculate_accumulated_values(&sorted_bids);
+    let best_price = levels[0].price;
+    let slippage_bps = ((price - best_price).abs() / best_price * dec!(10000)).round_up(2);
+    if slippage_bps > Decimal::from(protection.max_slippage_bps) {
+        return Err(PriceProtectionError::SlippageExceeded {
+            side,
+            amount,
+            price,
+            best_price,
+            slippage_bps,
+            max_slippage_bps: protection.max_slippage_bps,
+        });
+    }
 
-    match side {
-        Side::Buy => find_buy_price(&accumulated_asks, unit_amount, slippage),
-        Side::Sell => find_sell_price(&accumulated_bids, unit_amount, slippage),
+    let (limit, beyond_limit) = match side {
+        Side::Buy => (protection.max_buy_price, price > protection.max_buy_price),
+        Side::Sell => (protection.min_sell_price, price < protection.min_sell_price),
+    };
+    if beyond_limit {
+        return Err(PriceProtectionError::WorstPriceExceeded {
+            side,
+            amount,
+            price,
+            limit,
+        });
+    }
+
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDER_BOOK: &str = include_str!("../../../testing/fixtures/order_book.json");
+
+    const PROTECTION: PriceProtection = PriceProtection {
+        max_slippage_bps: 500,
+        max_buy_price: dec!(0.99),
+        min_sell_price: dec!(0.01),
+    };
+
+    fn order_book() -> OrderBookData {
+        serde_json::from_str(ORDER_BOOK).unwrap()
+    }
+
+    #[test]
+    fn test_round_normal() {
+        assert_eq!(dec!(0.565).round_normal(2), dec!(0.57));
+        assert_eq!(dec!(0.564).round_normal(2), dec!(0.56));
+        assert_eq!(dec!(0.46).round_normal(1), dec!(0.5));
+        assert_eq!(dec!(0.5123).round_normal(4), dec!(0.5123));
+    }
+
+    #[test]
+    fn test_decimal_places() {
+        assert_eq!(dec!(0.50).decimal_places(), 1);
+        assert_eq!(dec!(21.04).decimal_places(), 2);
+        assert_eq!(dec!(7.000).decimal_places(), 0);
+    }
+
+    #[test]
+    fn test_adjust_amount() {
+        assert_eq!(adjust_amount(dec!(10.55) * dec!(0.57), 4), dec!(6.0135));
+        assert_eq!(adjust_amount(dec!(33.33) * dec!(0.123), 5), dec!(4.09959));
+        assert_eq!(adjust_amount(dec!(10.12) / dec!(0.33), 4), dec!(30.6666));
+        assert_eq!(adjust_amount(dec!(7) / dec!(0.077), 5), dec!(90.90909));
+    }
+
+    #[test]
+    fn test_market_price_within_limits() {
+        let price = |side, amount| calculate_market_price(side, order_book(), amount, &PROTECTION);
+
+        assert_eq!(price(Side::Buy, dec!(50)), Ok(dec!(0.54)));
+        assert_eq!(price(Side::Buy, dec!(200)), Ok(dec!(0.55)));
+        assert_eq!(price(Side::Sell, dec!(85.25)), Ok(dec!(0.52)));
+        assert_eq!(price(Side::Sell, dec!(100)), Ok(dec!(0.51)));
+    }
+
+    #[test]
+    fn test_market_price_refuses_thin_books() {
+        assert_eq!(
+            calculate_market_price(Side::Buy, order_book(), dec!(2000), &PROTECTION),
+            Err(PriceProtectionError::InsufficientDepth {
+                side: Side::Buy,
+                amount: dec!(2000),
+                available: dec!(1501.06),
+            })
+        );
+        assert_eq!(
+            calculate_market_price(Side::Sell, order_book(), dec!(2000), &PROTECTION),
+            Err(PriceProtectionError::InsufficientDepth {
+                side: Side::Sell,
+                amount: dec!(2000),
+                available: dec!(1915.75),
+            })
+        );
+
+        let mut empty_book = order_book();
+        empty_book.asks.clear();
+        assert_eq!(
+            calculate_market_price(Side::Buy, empty_book, dec!(1), &PROTECTION),
+            Err(PriceProtectionError::EmptyBook {
+                side: Side::Buy,
+                amount: dec!(1),
+            })
+        );
+    }
+
+    #[test]
+    fn test_market_price_refuses_excessive_slippage() {
+        assert_eq!(
+            calculate_market_price(Side::Buy, order_book(), dec!(500), &PROTECTION),
+            Err(PriceProtectionError::SlippageExceeded {
+                side: Side::Buy,
+                amount: dec!(500),
+                price: dec!(0.58),
+                best_price: dec!(0.54),
+                slippage_bps: dec!(740.75),
+                max_slippage_bps: 500,
+            })
+        );
+
+        let tight = PriceProtection {
+            max_slippage_bps: 0,
+            ..PROTECTION
+        };
+        assert!(calculate_market_price(Side::Sell, order_book(), dec!(50), &tight).is_ok());
+        assert!(calculate_market_price(Side::Sell, order_book(), dec!(100), &tight).is_err());
+    }
+
+    #[test]
+    fn test_market_price_refuses_worst_price() {
+        let protection = PriceProtection {
+            max_buy_price: dec!(0.545),
+            min_sell_price: dec!(0.515),
+            ..PROTECTION
+        };
+
+        assert_eq!(
+            calculate_market_price(Side::Buy, order_book(), dec!(200), &protection),
+            Err(PriceProtectionError::WorstPriceExceeded {
+                side: Side::Buy,
+                amount: dec!(200),
+                price: dec!(0.55),
+                limit: dec!(0.545),
+            })
+        );
+        assert_eq!(
+            calculate_market_price(Side::Sell, order_book(), dec!(100), &protection),
+            Err(PriceProtectionError::WorstPriceExceeded {
+                side: Side::Sell,
+                amount: dec!(100),
+                price: dec!(0.51),
+                limit: dec!(0.515),
+            })
+        );
     }
 }
@@ -4,3 +4,4 @@ pub mod math;
 pub mod order_builder;
 pub mod schemas;
 pub mod typedefs;
+pub mod websocket;
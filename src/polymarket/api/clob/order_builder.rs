@@ -1,15 +1,21 @@
-use std::{ops::Div, str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc};
 
 use alloy::{
     dyn_abi::Eip712Domain,
-    primitives::{utils::parse_units, Address},
+    primitives::{Address, PrimitiveSignature},
     signers::Signer,
     sol_types::eip712_domain,
 };
 use chrono::Utc;
 use rand::Rng;
+use rust_decimal::Decimal;
 
-use crate::polymarket::api::clob::constants::{PROTOCOL_NAME, PROTOCOL_VERSION};
+use crate::{
+    polymarket::api::clob::constants::{
+        COLLATERAL_TOKEN_DECIMALS, PROTOCOL_NAME, PROTOCOL_VERSION,
+    },
+    utils::decimal::to_units,
+};
 
 use super::{
     constants::{get_contract_config, RoundingConfig, MATIC_CONTRACTS, ROUNDING_CONFIG},
@@ -28,6 +34,7 @@ where
     chain_id: u64,
     signature_type: Option<SignatureType>,
     funder_address: Option<&'a str>,
+    salt: Option<u128>,
 }
 
 impl<'a, S> OrderBuilder<'a, S>
@@ -45,9 +52,17 @@ where
             chain_id,
             signature_type,
             funder_address,
+            salt: None,
         }
     }
 
+    // Signs every order with the same salt so the signatures are reproducible
+    #[cfg(test)]
+    pub fn with_salt(mut self, salt: u128) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
     pub async fn build_signed_order(
         &self,
         user_order: UserOrder,
@@ -68,7 +83,7 @@ where
             self.signature_type,
             &user_order,
             &ROUNDING_CONFIG[options.tick_size.as_str()],
-        );
+        )?;
 
         let exchange_contract = match options.neg_risk.unwrap_or(false) {
             true => contract_config.neg_risk_exchange,
@@ -99,7 +114,7 @@ where
             self.signature_type,
             user_market_order,
             &ROUNDING_CONFIG[options.tick_size.as_str()],
-        );
+        )?;
 
         let exchange_contract = match options.neg_risk.unwrap_or(false) {
             true => contract_config.neg_risk_exchange,
@@ -119,6 +134,7 @@ where
         let order_domain = self.get_order_domain(verifying_contract);
 
         let order_signature = self.signer.sign_typed_data(&order, &order_domain).await?;
+        let order_signature = PrimitiveSignature::try_from(&order_signature.as_bytes()[..])?;
 
         SignedOrder::new(order, order_signature)
     }
@@ -148,6 +164,10 @@ where
     }
 
     fn generate_salt(&self) -> String {
+        if let Some(salt) = self.salt {
+            return salt.to_string();
+        }
+
         let now = Utc::now().timestamp_millis() as u128;
 
         let random_value: u128 = rand::thread_rng().gen_range(0..now);
@@ -171,20 +191,16 @@ where
         signature_type: Option<SignatureType>,
         user_market_order: UserMarketOrder,
         round_config: &RoundingConfig,
-    ) -> OrderData {
-        let price = user_market_order.price.unwrap_or(1f64);
+    ) -> eyre::Result<OrderData> {
+        let price = user_market_order.price.unwrap_or(Decimal::ONE);
 
         let BuyOrderRawAmounts {
             raw_maker_amount,
             raw_taker_amount,
         } = self.get_market_buy_order_raw_amounts(user_market_order.amount, price, round_config);
 
-        let maker_amount = parse_units(&raw_maker_amount.to_string(), "MWEI")
-            .unwrap()
-            .to_string();
-        let taker_amount = parse_units(&raw_taker_amount.to_string(), "MWEI")
-            .unwrap()
-            .to_string();
+        let maker_amount = to_units(raw_maker_amount, COLLATERAL_TOKEN_DECIMALS)?.to_string();
+        let taker_amount = to_units(raw_taker_amount, COLLATERAL_TOKEN_DECIMALS)?.to_string();
 
         let taker = match user_market_order.taker.clone() {
             Some(taker) => taker,
@@ -201,7 +217,7 @@ where
             None => "0".to_string(),
         };
 
-        OrderData {
+        Ok(OrderData {
             maker: maker.to_string(),
             taker,
             token_id: user_market_order.token_id,
@@ -213,19 +229,19 @@ where
             signer: Some(signer.to_string()),
             expiration: Some("0".to_string()),
             signature_type,
-        }
+        })
     }
 
     fn get_market_buy_order_raw_amounts(
         &self,
-        amount: f64,
-        price: f64,
+        amount: Decimal,
+        price: Decimal,
         round_config: &RoundingConfig,
     ) -> BuyOrderRawAmounts {
         let raw_maker_amount = amount.round_down(round_config.size);
         let raw_price = price.round_down(round_config.price);
 
-        let raw_taker_amount = adjust_amount(raw_maker_amount.div(raw_price), round_config.amount);
+        let raw_taker_amount = adjust_amount(raw_maker_amount / raw_price, round_config.amount);
 
         BuyOrderRawAmounts::new(raw_maker_amount, raw_taker_amount)
     }
@@ -237,7 +253,7 @@ where
         signature_type: Option<SignatureType>,
         user_order: &UserOrder,
         round_config: &RoundingConfig,
-    ) -> OrderData {
+    ) -> eyre::Result<OrderData> {
         let OrderRawAmounts {
             side,
             raw_maker_amount,
@@ -249,12 +265,8 @@ where
             round_config,
         );
 
-        let maker_amount = parse_units(&raw_maker_amount.to_string(), "MWEI")
-            .unwrap()
-            .to_string();
-        let taker_amount = parse_units(&raw_taker_amount.to_string(), "MWEI")
-            .unwrap()
-            .to_string();
+        let maker_amount = to_units(raw_maker_amount, COLLATERAL_TOKEN_DECIMALS)?.to_string();
+        let taker_amount = to_units(raw_taker_amount, COLLATERAL_TOKEN_DECIMALS)?.to_string();
 
         let taker = match user_order.taker.clone() {
             Some(taker) => taker,
@@ -276,7 +288,7 @@ where
             None => "0".to_string(),
         };
 
-        OrderData {
+        Ok(OrderData {
             maker: maker.to_string(),
             taker,
             token_id: user_order.token_id.clone(),
@@ -288,14 +300,14 @@ where
             signer: Some(signer.to_string()),
             expiration: Some(expiration),
             signature_type,
-        }
+        })
     }
 
     fn get_order_raw_amounts(
         &self,
         side: &Side,
-        size: f64,
-        price: f64,
+        size: Decimal,
+        price: Decimal,
         round_config: &RoundingConfig,
     ) -> OrderRawAmounts {
         let raw_price = price.round_normal(round_config.price);
@@ -317,3 +329,221 @@ where
         OrderRawAmounts::new(side, raw_maker_amount, raw_taker_amount)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{B256, U256},
+        signers::local::PrivateKeySigner,
+        sol_types::SolStruct,
+    };
+    use proptest::prelude::*;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::polymarket::api::clob::typedefs::TickSize;
+
+    const ORDER_VECTORS: &str = include_str!("../../../testing/fixtures/order_vectors.json");
+
+    #[derive(Deserialize)]
+    struct OrderVectors {
+        private_key: String,
+        chain_id: u64,
+        token_id: String,
+        vectors: Vec<OrderVector>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct OrderVector {
+        kind: String,
+        tick_size: String,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+        neg_risk: bool,
+        signature_type: u8,
+        salt: String,
+        maker: Address,
+        maker_amount: String,
+        taker_amount: String,
+        struct_hash: B256,
+        signature: String,
+    }
+
+    fn struct_hash(order: &SignedOrder) -> B256 {
+        let order = Order {
+            salt: U256::from(order.salt),
+            maker: order.maker.parse().unwrap(),
+            signer: order.signer.parse().unwrap(),
+            taker: order.taker.parse().unwrap(),
+            tokenId: order.token_id.parse().unwrap(),
+            makerAmount: order.maker_amount.parse().unwrap(),
+            takerAmount: order.taker_amount.parse().unwrap(),
+            expiration: order.expiration.parse().unwrap(),
+            nonce: order.nonce.parse().unwrap(),
+            feeRateBps: order.fee_rate_bps.parse().unwrap(),
+            side: order.side.clone() as u8,
+            signatureType: order.signature_type as u8,
+        };
+
+        order.eip712_hash_struct()
+    }
+
+    #[tokio::test]
+    async fn test_signed_orders_match_reference_vectors() {
+        let OrderVectors {
+            private_key,
+            chain_id,
+            token_id,
+            vectors,
+        } = serde_json::from_str(ORDER_VECTORS).unwrap();
+        let signer = Arc::new(PrivateKeySigner::from_str(&private_key).unwrap());
+
+        for vector in vectors {
+            let signature_type = SignatureType::try_from(vector.signature_type).unwrap();
+            let maker = vector.maker.to_string();
+            let funder = match signature_type {
+                SignatureType::Eoa => None,
+                _ => Some(maker.as_str()),
+            };
+
+            let order_builder =
+                OrderBuilder::new(signer.clone(), chain_id, Some(signature_type), funder)
+                    .with_salt(vector.salt.parse().unwrap());
+            let options = CreateOrderOptions::new(
+                TickSize::from_str(&vector.tick_size).unwrap(),
+                Some(vector.neg_risk),
+            );
+
+            let order = match vector.kind.as_str() {
+                "limit" => {
+                    let user_order = UserOrder::default()
+                        .with_token_id(&token_id)
+                        .with_price(vector.price)
+                        .with_size(vector.size)
+                        .with_side(vector.side.clone());
+
+                    order_builder.build_signed_order(user_order, options).await
+                }
+                "market_buy" => {
+                    let user_market_order = UserMarketOrder::new(
+                        token_id.clone(),
+                        vector.size,
+                        Some(vector.price),
+                        None,
+                        None,
+                        None,
+                    );
+
+                    order_builder
+                        .build_signed_market_buy_order(user_market_order, options)
+                        .await
+                }
+                kind => panic!("Unknown vector kind: {kind}"),
+            }
+            .unwrap();
+
+            assert_eq!(order.maker, maker, "{vector:?}");
+            assert_eq!(order.signer, signer.address().to_string(), "{vector:?}");
+            assert_eq!(order.side, vector.side, "{vector:?}");
+            assert_eq!(order.signature_type, signature_type, "{vector:?}");
+            assert_eq!(order.maker_amount, vector.maker_amount, "{vector:?}");
+            assert_eq!(order.taker_amount, vector.taker_amount, "{vector:?}");
+            assert_eq!(struct_hash(&order), vector.struct_hash, "{vector:?}");
+            assert_eq!(order.signature, vector.signature, "{vector:?}");
+        }
+    }
+
+    fn order_builder() -> OrderBuilder<'static, PrivateKeySigner> {
+        OrderBuilder::new(Arc::new(PrivateKeySigner::random()), 137, None, None)
+    }
+
+    fn tick_size() -> impl Strategy<Value = TickSize> {
+        prop_oneof![
+            Just(TickSize::OneTenth),
+            Just(TickSize::OneHundredth),
+            Just(TickSize::OneThousandth),
+            Just(TickSize::TenThousandth),
+        ]
+    }
+
+    fn side() -> impl Strategy<Value = Side> {
+        prop_oneof![Just(Side::Buy), Just(Side::Sell)]
+    }
+
+    proptest! {
+        // A price on the tick grid times a size in cents always fits the amount precision, so the
+        // on-chain amounts are exact products with nothing rounded away
+        #[test]
+        fn test_limit_order_amounts_are_exact(
+            tick_size in tick_size(),
+            side in side(),
+            ticks in 1u64..10_000,
+            cents in 1u64..100_000_000,
+        ) {
+            let round_config = &ROUNDING_CONFIG[tick_size.as_str()];
+            let ticks = ticks % 10u64.pow(round_config.price) + 1;
+            let price = Decimal::new(ticks as i64, round_config.price);
+            let size = Decimal::new(cents as i64, 2);
+
+            let user_order = UserOrder::default()
+                .with_token_id("1")
+                .with_price(price)
+                .with_size(size)
+                .with_side(side.clone());
+            let signer = order_builder().signer.address().to_string();
+            let order_data = order_builder()
+                .build_order_creation_args(&signer, &signer, None, &user_order, round_config)
+                .unwrap();
+
+            let shares = U256::from(cents) * U256::from(10_000);
+            let collateral = U256::from(ticks * cents)
+                * U256::from(10u64.pow(COLLATERAL_TOKEN_DECIMALS - 2 - round_config.price));
+            let (maker_amount, taker_amount) = match side {
+                Side::Buy => (collateral, shares),
+                Side::Sell => (shares, collateral),
+            };
+
+            prop_assert_eq!(order_data.maker_amount, maker_amount.to_string());
+            prop_assert_eq!(order_data.taker_amount, taker_amount.to_string());
+        }
+
+        // The spent collateral is exact, the received shares are the quotient cut to the amount
+        // precision
+        #[test]
+        fn test_market_buy_amounts_are_exact(
+            tick_size in tick_size(),
+            ticks in 1u64..10_000,
+            cents in 1u64..100_000_000,
+        ) {
+            let round_config = &ROUNDING_CONFIG[tick_size.as_str()];
+            let ticks = ticks % 10u64.pow(round_config.price) + 1;
+            let price = Decimal::new(ticks as i64, round_config.price);
+            let amount = Decimal::new(cents as i64, 2);
+
+            let user_market_order =
+                UserMarketOrder::new("1".to_string(), amount, Some(price), None, None, None);
+            let signer = order_builder().signer.address().to_string();
+            let order_data = order_builder()
+                .build_market_buy_order_creation_args(
+                    &signer,
+                    &signer,
+                    None,
+                    user_market_order,
+                    round_config,
+                )
+                .unwrap();
+
+            let maker_amount = U256::from_str(&order_data.maker_amount).unwrap();
+            let taker_amount = U256::from_str(&order_data.taker_amount).unwrap();
+            let shares = Decimal::from_str(&order_data.taker_amount).unwrap()
+                / Decimal::from(10u64.pow(COLLATERAL_TOKEN_DECIMALS));
+            let step = Decimal::new(1, round_config.amount);
+
+            prop_assert_eq!(maker_amount, U256::from(cents) * U256::from(10_000));
+            prop_assert!(taker_amount > U256::ZERO);
+            prop_assert!(shares.decimal_places() <= round_config.amount);
+            prop_assert!((shares - amount / price).abs() < step);
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{collections::HashMap, fmt::Display};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
 use crate::onchain::constants::POLYGON_EXPLORER_TX_BASE_URL;
 
@@ -14,7 +17,7 @@ pub struct ClobApiKeyResponseBody {
 
 #[derive(Deserialize, Debug)]
 pub struct GetTickSizeResponseBody {
-    pub minimum_tick_size: f64,
+    pub minimum_tick_size: Decimal,
 }
 
 #[derive(Serialize, Debug)]
@@ -26,29 +29,163 @@ pub struct TokenId<'a> {
 #[derive(Debug, Deserialize, Clone)]
 pub struct OrderBookData {
     market: String,
-    asset_id: String,
+    pub asset_id: String,
     timestamp: String,
     hash: String,
+    #[serde(alias = "buys")]
     pub bids: Vec<Order>,
+    #[serde(alias = "sells")]
     pub asks: Vec<Order>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Order {
-    #[serde(deserialize_with = "string_to_f64")]
-    pub price: f64,
-    #[serde(deserialize_with = "string_to_f64")]
-    pub size: f64,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+impl OrderBookData {
+    // Applies a level update from the market channel, a zero size removes the level
+    pub fn apply_price_change(&mut self, change: &PriceChange, timestamp: &str) {
+        let levels = match change.side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+
+        levels.retain(|order| order.price != change.price);
+        if change.size > Decimal::ZERO {
+            levels.push(Order {
+                price: change.price,
+                size: change.size,
+            });
+        }
+
+        self.timestamp = timestamp.to_string();
+        if let Some(hash) = &change.hash {
+            self.hash = hash.clone();
+        }
+    }
+}
+
+// A price level update, the asset id and hash are only set per change in the batched format
+#[derive(Debug, Deserialize, Clone)]
+pub struct PriceChange {
+    pub asset_id: Option<String>,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub side: Side,
+    pub hash: Option<String>,
+}
+
+#[allow(unused)]
+#[derive(Debug, Deserialize, Clone)]
+pub struct LastTradePrice {
+    pub asset_id: String,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub side: Side,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OrderEventType {
+    Placement,
+    Update,
+    Cancellation,
+}
+
+#[allow(unused)]
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserOrderEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: OrderEventType,
+    pub market: String,
+    pub asset_id: String,
+    pub outcome: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub original_size: Decimal,
+    pub size_matched: Decimal,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TradeStatus {
+    Matched,
+    Mined,
+    Confirmed,
+    Retrying,
+    Failed,
+}
+
+// `owner` is the API key of the account the maker order belongs to
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserMakerOrder {
+    pub order_id: String,
+    pub owner: String,
+    pub asset_id: String,
+    pub outcome: String,
+    pub matched_amount: Decimal,
+    pub price: Decimal,
+    pub side: Option<Side>,
+    pub fee_rate_bps: Option<Decimal>,
+}
+
+#[allow(unused)]
+#[derive(Debug, Deserialize, Clone)]
+pub struct UserTradeEvent {
+    pub id: String,
+    pub taker_order_id: String,
+    pub market: String,
+    pub asset_id: String,
+    pub outcome: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub status: TradeStatus,
+    pub fee_rate_bps: Option<Decimal>,
+    #[serde(default)]
+    pub transaction_hash: String,
+    #[serde(alias = "match_time")]
+    pub matchtime: String,
+    pub maker_orders: Vec<UserMakerOrder>,
+}
+
+impl UserTradeEvent {
+    // Our maker orders in this trade, empty when we are the taker
+    pub fn own_maker_orders<'a>(
+        &'a self,
+        api_key: &'a str,
+    ) -> impl Iterator<Item = &'a UserMakerOrder> {
+        self.maker_orders
+            .iter()
+            .filter(move |order| order.owner == api_key)
+    }
+
+    pub fn filled_size(&self, order_id: &str) -> Decimal {
+        match self.taker_order_id == order_id {
+            true => self.size,
+            false => self
+                .maker_orders
+                .iter()
+                .filter(|order| order.order_id == order_id)
+                .map(|order| order.matched_amount)
+                .sum(),
+        }
+    }
 }
 
 #[allow(unused)]
 #[derive(Debug, Clone)]
 pub struct AccumulatedOrder {
-    pub price: f64,
-    pub size: f64,
-    pub value: f64,
-    pub net_value: f64,
-    pub net_size: f64,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub value: Decimal,
+    pub net_value: Decimal,
+    pub net_size: Decimal,
 }
 
 #[derive(Deserialize)]
@@ -56,14 +193,6 @@ pub struct NegRiskResponseBody {
     pub neg_risk: bool,
 }
 
-fn string_to_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    s.parse::<f64>().map_err(de::Error::custom)
-}
-
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct OrderRequest {
@@ -73,7 +202,7 @@ pub struct OrderRequest {
 }
 
 #[allow(unused)]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OrderType {
     Fok,
@@ -81,6 +210,16 @@ pub enum OrderType {
     Gtd,
 }
 
+impl Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fok => write!(f, "FOK"),
+            Self::Gtc => write!(f, "GTC"),
+            Self::Gtd => write!(f, "GTD"),
+        }
+    }
+}
+
 impl OrderRequest {
     pub fn new(signed_order: SignedOrder, owner: &str, order_type: Option<OrderType>) -> Self {
         let order_type = order_type.unwrap_or(OrderType::Fok);
@@ -108,6 +247,19 @@ pub struct PlaceOrderResponseBody {
 }
 
 impl PlaceOrderResponseBody {
+    // Returned instead of posting the order in dry-run mode
+    pub fn dry_run(order_id: &str) -> Self {
+        Self {
+            error_msg: String::new(),
+            order_id: Some(order_id.to_string()),
+            taking_amount: None,
+            making_amount: None,
+            status: None,
+            transactions_hashes: None,
+            success: Some(true),
+        }
+    }
+
     pub fn get_tx_hash(&self) -> String {
         format!(
             "{}{}",
@@ -132,7 +284,7 @@ impl PlaceOrderResponseBody {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
     Live,
@@ -140,3 +292,89 @@ pub enum OrderStatus {
     Delayed,
     Unmatched,
 }
+
+impl Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Live => write!(f, "live"),
+            Self::Matched => write!(f, "matched"),
+            Self::Delayed => write!(f, "delayed"),
+            Self::Unmatched => write!(f, "unmatched"),
+        }
+    }
+}
+
+#[allow(unused)]
+#[derive(Deserialize, Debug, Clone)]
+pub struct OpenOrder {
+    pub id: String,
+    pub status: String,
+    pub market: String,
+    pub asset_id: String,
+    pub side: Side,
+    pub original_size: Decimal,
+    pub size_matched: Decimal,
+    pub price: Decimal,
+    pub outcome: String,
+    pub expiration: String,
+    pub order_type: OrderType,
+    pub created_at: i64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PaginatedResponseBody<T> {
+    pub data: Vec<T>,
+    pub next_cursor: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CancelOrderRequestBody<'a> {
+    #[serde(rename = "orderID")]
+    pub order_id: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CancelOrdersResponseBody {
+    pub canceled: Vec<String>,
+    pub not_canceled: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TraderSide {
+    Taker,
+    Maker,
+}
+
+#[allow(unused)]
+#[derive(Deserialize, Debug, Clone)]
+pub struct MakerOrder {
+    pub order_id: String,
+    pub maker_address: String,
+    pub matched_amount: Decimal,
+    pub price: Decimal,
+    pub fee_rate_bps: Decimal,
+    pub asset_id: String,
+    pub outcome: String,
+    pub side: Side,
+}
+
+#[allow(unused)]
+#[derive(Deserialize, Debug, Clone)]
+pub struct Trade {
+    pub id: String,
+    pub taker_order_id: String,
+    pub market: String,
+    pub asset_id: String,
+    pub side: Side,
+    pub size: Decimal,
+    pub fee_rate_bps: Decimal,
+    pub price: Decimal,
+    pub status: TradeStatus,
+    pub match_time: String,
+    pub outcome: String,
+    pub maker_address: String,
+    pub transaction_hash: String,
+    pub trader_side: TraderSide,
+    pub maker_orders: Vec<MakerOrder>,
+}
@@ -1,17 +1,19 @@
 use std::{fmt::Display, str::FromStr};
 
 use alloy::{
-    primitives::{Address, Signature, U256},
+    primitives::{Address, PrimitiveSignature, U256},
     sol,
 };
 use eyre::bail;
-use serde::Serialize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use serde_repr::Serialize_repr;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[repr(u8)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Side {
+    #[default]
     Buy = 0,
     Sell = 1,
 }
@@ -25,12 +27,6 @@ impl Display for Side {
     }
 }
 
-impl Default for Side {
-    fn default() -> Self {
-        Self::Buy
-    }
-}
-
 impl TryFrom<u8> for Side {
     type Error = eyre::Report;
 
@@ -135,7 +131,7 @@ pub struct SignedOrder {
 }
 
 impl SignedOrder {
-    pub fn new(order: Order, signature: Signature) -> eyre::Result<Self> {
+    pub fn new(order: Order, signature: PrimitiveSignature) -> eyre::Result<Self> {
         Ok(Self {
             salt: order.salt.try_into().unwrap(),
             maker: order.maker.to_string(),
@@ -156,12 +152,12 @@ impl SignedOrder {
 
 pub struct OrderRawAmounts {
     pub side: Side,
-    pub raw_maker_amount: f64,
-    pub raw_taker_amount: f64,
+    pub raw_maker_amount: Decimal,
+    pub raw_taker_amount: Decimal,
 }
 
 impl OrderRawAmounts {
-    pub fn new(side: &Side, raw_maker_amount: f64, raw_taker_amount: f64) -> Self {
+    pub fn new(side: &Side, raw_maker_amount: Decimal, raw_taker_amount: Decimal) -> Self {
         Self {
             side: side.clone(),
             raw_maker_amount,
@@ -171,12 +167,12 @@ impl OrderRawAmounts {
 }
 
 pub struct BuyOrderRawAmounts {
-    pub raw_maker_amount: f64,
-    pub raw_taker_amount: f64,
+    pub raw_maker_amount: Decimal,
+    pub raw_taker_amount: Decimal,
 }
 
 impl BuyOrderRawAmounts {
-    pub fn new(raw_maker_amount: f64, raw_taker_amount: f64) -> Self {
+    pub fn new(raw_maker_amount: Decimal, raw_taker_amount: Decimal) -> Self {
         Self {
             raw_maker_amount,
             raw_taker_amount,
@@ -231,8 +227,8 @@ impl CreateOrderOptions {
 #[derive(Default)]
 pub struct UserOrder {
     pub token_id: String,
-    pub price: f64,
-    pub size: f64,
+    pub price: Decimal,
+    pub size: Decimal,
     pub side: Side,
     pub fee_rate_bps: Option<f64>,
     pub nonce: Option<u64>,
@@ -251,20 +247,20 @@ impl UserOrder {
         self
     }
 
-    pub fn set_price(&mut self, price: f64) {
+    pub fn set_price(&mut self, price: Decimal) {
         self.price = price;
     }
 
-    pub fn with_price(mut self, price: f64) -> Self {
+    pub fn with_price(mut self, price: Decimal) -> Self {
         self.set_price(price);
         self
     }
 
-    pub fn set_size(&mut self, size: f64) {
+    pub fn set_size(&mut self, size: Decimal) {
         self.size = size;
     }
 
-    pub fn with_size(mut self, size: f64) -> Self {
+    pub fn with_size(mut self, size: Decimal) -> Self {
         self.set_size(size);
         self
     }
@@ -317,8 +313,8 @@ impl UserOrder {
 
 pub struct UserMarketOrder {
     pub token_id: String,
-    pub price: Option<f64>,
-    pub amount: f64,
+    pub price: Option<Decimal>,
+    pub amount: Decimal,
     pub fee_rate_bps: Option<f64>,
     pub nonce: Option<u64>,
     pub taker: Option<String>,
@@ -327,8 +323,8 @@ pub struct UserMarketOrder {
 impl UserMarketOrder {
     pub fn new(
         token_id: String,
-        amount: f64,
-        price: Option<f64>,
+        amount: Decimal,
+        price: Option<Decimal>,
         fee_rate_bps: Option<f64>,
         nonce: Option<u64>,
         taker: Option<String>,
@@ -343,3 +339,12 @@ impl UserMarketOrder {
         }
     }
 }
+
+// Limits a market order has to fill within, slippage is measured from the best price on the
+// opposite side of the book
+#[derive(Debug, Clone, Copy)]
+pub struct PriceProtection {
+    pub max_slippage_bps: u32,
+    pub max_buy_price: Decimal,
+    pub min_sell_price: Decimal,
+}
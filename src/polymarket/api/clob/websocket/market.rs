@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{sync::Notify, task::JoinHandle};
+
+use super::{parse_events, run_subscription, StreamHandler};
+use crate::polymarket::api::{
+    clob::schemas::{LastTradePrice, OrderBookData, PriceChange},
+    urls::{api_url, Api},
+};
+
+#[derive(Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+enum MarketEvent {
+    Book(OrderBookData),
+    PriceChange {
+        asset_id: Option<String>,
+        timestamp: String,
+        hash: Option<String>,
+        #[serde(default, alias = "price_changes")]
+        changes: Vec<PriceChange>,
+    },
+    LastTradePrice(LastTradePrice),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Default)]
+struct MarketState {
+    books: HashMap<String, OrderBookData>,
+    last_trades: HashMap<String, LastTradePrice>,
+    // USDC traded since the connection was opened
+    traded_volumes: HashMap<String, Decimal>,
+}
+
+struct Shared {
+    token_ids: Vec<String>,
+    state: RwLock<MarketState>,
+    updated: Notify,
+}
+
+// Live order books of the subscribed tokens from the market channel. The connection is kept in a
+// background task that reconnects and resubscribes, books are dropped while disconnected so
+// readers never price against a stale book
+pub struct MarketStream {
+    shared: Arc<Shared>,
+    handle: JoinHandle<()>,
+}
+
+impl MarketStream {
+    pub fn subscribe(token_ids: Vec<String>) -> Self {
+        Self::subscribe_to(api_url(Api::ClobWs, "/market"), token_ids)
+    }
+
+    fn subscribe_to(url: String, token_ids: Vec<String>) -> Self {
+        let subscription = json!({ "assets_ids": token_ids, "type": "market" });
+        let shared = Arc::new(Shared {
+            token_ids,
+            state: RwLock::default(),
+            updated: Notify::new(),
+        });
+        let handle = tokio::spawn(run_subscription(url, subscription, shared.clone()));
+
+        Self { shared, handle }
+    }
+
+    pub fn book(&self, token_id: &str) -> Option<OrderBookData> {
+        self.shared
+            .state
+            .read()
+            .unwrap()
+            .books
+            .get(token_id)
+            .cloned()
+    }
+
+    #[allow(unused)]
+    pub fn last_trade(&self, token_id: &str) -> Option<LastTradePrice> {
+        self.shared
+            .state
+            .read()
+            .unwrap()
+            .last_trades
+            .get(token_id)
+            .cloned()
+    }
+
+    // Restarts from zero after a reconnect
+    pub fn traded_volume(&self, token_id: &str) -> Decimal {
+        self.shared
+            .state
+            .read()
+            .unwrap()
+            .traded_volumes
+            .get(token_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Waits for the first snapshot of a book, `None` if it doesn't arrive in time
+    pub async fn wait_for_book(&self, token_id: &str, timeout: Duration) -> Option<OrderBookData> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let updated = self.shared.updated.notified();
+                tokio::pin!(updated);
+                updated.as_mut().enable();
+
+                if let Some(book) = self.book(token_id) {
+                    return book;
+                }
+
+                updated.await;
+            }
+        })
+        .await
+        .ok()
+    }
+}
+
+impl Drop for MarketStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl StreamHandler for Shared {
+    fn on_message(&self, text: &str) {
+        let mut state = self.state.write().unwrap();
+
+        for event in parse_events(text) {
+            match serde_json::from_value::<MarketEvent>(event) {
+                Ok(MarketEvent::Book(book)) => {
+                    if self.token_ids.contains(&book.asset_id) {
+                        state.books.insert(book.asset_id.clone(), book);
+                    }
+                }
+                Ok(MarketEvent::PriceChange {
+                    asset_id,
+                    timestamp,
+                    hash,
+                    changes,
+                }) => {
+                    for mut change in changes {
+                        change.hash = change.hash.or(hash.clone());
+
+                        let Some(asset_id) = change.asset_id.as_ref().or(asset_id.as_ref()) else {
+                            continue;
+                        };
+
+                        // Changes before the first snapshot are covered by the snapshot
+                        if let Some(book) = state.books.get_mut(asset_id) {
+                            book.apply_price_change(&change, &timestamp);
+                        }
+                    }
+                }
+                Ok(MarketEvent::LastTradePrice(trade)) => {
+                    if self.token_ids.contains(&trade.asset_id) {
+                        *state
+                            .traded_volumes
+                            .entry(trade.asset_id.clone())
+                            .or_default() += trade.price * trade.size;
+                        state.last_trades.insert(trade.asset_id.clone(), trade);
+                    }
+                }
+                Ok(MarketEvent::Other) => {}
+                Err(e) => tracing::debug!("Failed to parse a market stream event: {e}"),
+            }
+        }
+
+        drop(state);
+        self.updated.notify_waiters();
+    }
+
+    fn on_disconnect(&self) {
+        *self.state.write().unwrap() = MarketState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        polymarket::api::clob::{schemas::Order, typedefs::Side},
+        testing::stream_server::stream_server,
+    };
+
+    const TOKEN_ID: &str =
+        "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+    const ORDER_BOOK: &str = include_str!("../../../../testing/fixtures/order_book.json");
+
+    fn book_event(asset_id: &str, bids: Value) -> Value {
+        let mut book = serde_json::from_str::<Value>(ORDER_BOOK).unwrap();
+        book["event_type"] = json!("book");
+        book["asset_id"] = json!(asset_id);
+        book["bids"] = bids;
+        book
+    }
+
+    #[tokio::test]
+    async fn test_market_stream_applies_events() {
+        let trade = json!({
+            "event_type": "last_trade_price",
+            "asset_id": TOKEN_ID,
+            "market": "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1",
+            "price": "0.52",
+            "size": "40",
+            "side": "SELL",
+            "fee_rate_bps": "0",
+            "timestamp": "1728897699",
+        });
+        let changes = json!({
+            "event_type": "price_change",
+            "asset_id": TOKEN_ID,
+            "timestamp": "1728897698",
+            "hash": "9a7b",
+            "changes": [
+                { "price": "0.52", "size": "0", "side": "BUY" },
+                { "price": "0.53", "size": "12.5", "side": "BUY" },
+                { "price": "0.54", "size": "50", "side": "SELL" },
+            ],
+        });
+        let unknown = json!({ "event_type": "tick_size_change", "asset_id": TOKEN_ID });
+        let other_token = book_event("1", json!([]));
+
+        let first_session = vec![
+            json!([
+                book_event(TOKEN_ID, json!([{ "price": "0.52", "size": "85.25" }])),
+                other_token
+            ]),
+            changes,
+            unknown,
+            trade,
+        ];
+        let (url, mut subscriptions) = stream_server(vec![first_session]).await;
+
+        let stream = MarketStream::subscribe_to(url, vec![TOKEN_ID.to_string()]);
+
+        let subscription = subscriptions.recv().await.unwrap();
+        assert_eq!(
+            subscription,
+            json!({ "assets_ids": [TOKEN_ID], "type": "market" })
+        );
+
+        stream
+            .wait_for_book(TOKEN_ID, Duration::from_secs(5))
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stream.last_trade(TOKEN_ID).is_none() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let state = stream.shared.state.read().unwrap();
+        assert!(!state.books.contains_key("1"));
+
+        assert_eq!(state.traded_volumes[TOKEN_ID], dec!(20.8));
+
+        let trade = &state.last_trades[TOKEN_ID];
+        assert_eq!(trade.price, dec!(0.52));
+        assert_eq!(trade.side, Side::Sell);
+
+        let book = &state.books[TOKEN_ID];
+        let levels = |orders: &[Order]| {
+            let mut levels = orders
+                .iter()
+                .map(|order| (order.price, order.size))
+                .collect::<Vec<_>>();
+            levels.sort();
+            levels
+        };
+        assert_eq!(levels(&book.bids), [(dec!(0.53), dec!(12.5))]);
+        assert_eq!(
+            levels(&book.asks),
+            [
+                (dec!(0.54), dec!(50)),
+                (dec!(0.55), dec!(420)),
+                (dec!(0.58), dec!(2100))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_market_stream_resubscribes_after_disconnect() {
+        let first_book = book_event(TOKEN_ID, json!([{ "price": "0.52", "size": "85.25" }]));
+        let second_book = book_event(TOKEN_ID, json!([{ "price": "0.49", "size": "300" }]));
+        let (url, mut subscriptions) =
+            stream_server(vec![vec![first_book], vec![second_book]]).await;
+
+        let stream = MarketStream::subscribe_to(url, vec![TOKEN_ID.to_string()]);
+
+        for _ in 0..2 {
+            assert_eq!(
+                subscriptions.recv().await.unwrap()["assets_ids"],
+                json!([TOKEN_ID])
+            );
+        }
+
+        let book = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match stream.wait_for_book(TOKEN_ID, Duration::from_secs(5)).await {
+                    Some(book) if book.bids[0].price == dec!(0.49) => return book,
+                    _ => tokio::time::sleep(Duration::from_millis(20)).await,
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(book.bids[0].size, dec!(300));
+    }
+}
@@ -0,0 +1,94 @@
+use std::{sync::Arc, time::Duration};
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::time::Instant;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+pub mod market;
+pub mod user;
+
+// The server drops connections that don't ping every 10 seconds
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+const STALE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+trait StreamHandler: Send + Sync + 'static {
+    // Called once the subscription is sent on a new connection
+    fn on_connect(&self) {}
+
+    fn on_message(&self, text: &str);
+
+    fn on_disconnect(&self);
+}
+
+// Keeps a channel subscription alive, the subscription is sent again after every reconnect
+async fn run_subscription<H: StreamHandler>(url: String, subscription: Value, handler: Arc<H>) {
+    let mut reconnect_delay = MIN_RECONNECT_DELAY;
+
+    loop {
+        match stream_messages(&url, &subscription, handler.as_ref(), &mut reconnect_delay).await {
+            Ok(()) => tracing::warn!("Stream {url} closed by the server"),
+            Err(e) => tracing::warn!("Stream {url} failed: {e}"),
+        }
+
+        handler.on_disconnect();
+
+        tracing::info!(
+            "Reconnecting to {url} in {} seconds",
+            reconnect_delay.as_secs()
+        );
+        tokio::time::sleep(reconnect_delay).await;
+        reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+async fn stream_messages(
+    url: &str,
+    subscription: &Value,
+    handler: &impl StreamHandler,
+    reconnect_delay: &mut Duration,
+) -> eyre::Result<()> {
+    let (mut ws, _) = connect_async(url).await?;
+    *reconnect_delay = MIN_RECONNECT_DELAY;
+
+    ws.send(Message::Text(subscription.to_string())).await?;
+    handler.on_connect();
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    let mut last_message = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = ping.tick() => {
+                if last_message.elapsed() > STALE_CONNECTION_TIMEOUT {
+                    eyre::bail!("No messages for {} seconds", STALE_CONNECTION_TIMEOUT.as_secs());
+                }
+
+                ws.send(Message::Text("PING".to_string())).await?;
+            }
+            message = ws.next() => {
+                last_message = Instant::now();
+
+                match message.transpose()? {
+                    Some(Message::Text(text)) if text != "PONG" => handler.on_message(&text),
+                    Some(Message::Close(_)) | None => return Ok(()),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+}
+
+// Messages carry a single event or an array of them
+fn parse_events(text: &str) -> Vec<Value> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(events)) => events,
+        Ok(event) => vec![event],
+        Err(e) => {
+            tracing::debug!("Unexpected stream message `{text}`: {e}");
+            vec![]
+        }
+    }
+}
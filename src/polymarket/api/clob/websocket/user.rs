@@ -0,0 +1,389 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{
+    sync::{broadcast, Notify},
+    task::JoinHandle,
+};
+
+use super::{parse_events, run_subscription, StreamHandler};
+use crate::{
+    db::account::Account,
+    polymarket::api::{
+        clob::schemas::{OrderEventType, TradeStatus, UserOrderEvent, UserTradeEvent},
+        urls::{api_url, Api},
+    },
+};
+
+const EVENTS_CAPACITY: usize = 256;
+
+#[derive(Deserialize)]
+#[serde(tag = "event_type", rename_all = "snake_case")]
+enum RawUserEvent {
+    Order(UserOrderEvent),
+    Trade(UserTradeEvent),
+    #[serde(other)]
+    Other,
+}
+
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub enum UserEvent {
+    OrderPlaced(UserOrderEvent),
+    OrderUpdated(UserOrderEvent),
+    OrderCancelled(UserOrderEvent),
+    Trade(UserTradeEvent),
+}
+
+#[derive(Default)]
+struct UserState {
+    connected: bool,
+    // The latest status of every trade by the ids of the orders it matched
+    trades: HashMap<String, UserTradeEvent>,
+    cancelled: HashSet<String>,
+}
+
+struct Shared {
+    events: broadcast::Sender<UserEvent>,
+    state: RwLock<UserState>,
+    updated: Notify,
+}
+
+// Order and trade events of one account from the authenticated user channel
+pub struct UserStream {
+    pub proxy_address: String,
+    pub api_key: String,
+    shared: Arc<Shared>,
+    handle: JoinHandle<()>,
+}
+
+impl UserStream {
+    pub fn subscribe(account: &Account) -> eyre::Result<Self> {
+        Self::subscribe_to(api_url(Api::ClobWs, "/user"), account)
+    }
+
+    fn subscribe_to(url: String, account: &Account) -> eyre::Result<Self> {
+        let creds = account.get_api_creds().ok_or_else(|| {
+            eyre::eyre!(
+                "{} | Account has no API credentials for the user channel",
+                account.proxy_address
+            )
+        })?;
+
+        let subscription = json!({
+            "auth": {
+                "apiKey": creds.api_key,
+                "secret": creds.api_secret,
+                "passphrase": creds.api_passphrase,
+            },
+            "markets": [],
+            "type": "user",
+        });
+        let shared = Arc::new(Shared {
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+            state: RwLock::default(),
+            updated: Notify::new(),
+        });
+        let handle = tokio::spawn(run_subscription(url, subscription, shared.clone()));
+
+        Ok(Self {
+            proxy_address: account.proxy_address.clone(),
+            api_key: creds.api_key,
+            shared,
+            handle,
+        })
+    }
+
+    // Events received after this call
+    pub fn events(&self) -> broadcast::Receiver<UserEvent> {
+        self.shared.events.subscribe()
+    }
+
+    // Orders placed before the subscription is sent are only seen if they trade later
+    pub async fn wait_until_connected(&self, timeout: Duration) -> bool {
+        self.wait_for(timeout, |state| state.connected.then_some(()))
+            .await
+            .is_some()
+    }
+
+    pub async fn wait_for_fill(
+        &self,
+        order_id: &str,
+        timeout: Duration,
+    ) -> eyre::Result<UserTradeEvent> {
+        let result = self
+            .wait_for(timeout, |state| match state.trades.get(order_id) {
+                Some(trade) if trade.status == TradeStatus::Failed => Some(Err(eyre::eyre!(
+                    "Trade {} of order {order_id} failed",
+                    trade.id
+                ))),
+                Some(trade) => Some(Ok(trade.clone())),
+                None if state.cancelled.contains(order_id) => Some(Err(eyre::eyre!(
+                    "Order {order_id} was cancelled before it filled"
+                ))),
+                None => None,
+            })
+            .await;
+
+        result.unwrap_or_else(|| {
+            Err(eyre::eyre!(
+                "Timeout while waiting for order {order_id} to fill"
+            ))
+        })
+    }
+
+    async fn wait_for<T>(
+        &self,
+        timeout: Duration,
+        check: impl Fn(&UserState) -> Option<T>,
+    ) -> Option<T> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let updated = self.shared.updated.notified();
+                tokio::pin!(updated);
+                updated.as_mut().enable();
+
+                if let Some(value) = check(&self.shared.state.read().unwrap()) {
+                    return value;
+                }
+
+                updated.await;
+            }
+        })
+        .await
+        .ok()
+    }
+}
+
+impl Drop for UserStream {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl Shared {
+    fn set_connected(&self, connected: bool) {
+        self.state.write().unwrap().connected = connected;
+        self.updated.notify_waiters();
+    }
+}
+
+impl StreamHandler for Shared {
+    fn on_connect(&self) {
+        self.set_connected(true);
+    }
+
+    fn on_message(&self, text: &str) {
+        let mut events = vec![];
+
+        for event in parse_events(text) {
+            match serde_json::from_value::<RawUserEvent>(event) {
+                Ok(RawUserEvent::Order(order)) => events.push(match order.event_type {
+                    OrderEventType::Placement => UserEvent::OrderPlaced(order),
+                    OrderEventType::Update => UserEvent::OrderUpdated(order),
+                    OrderEventType::Cancellation => UserEvent::OrderCancelled(order),
+                }),
+                Ok(RawUserEvent::Trade(trade)) => events.push(UserEvent::Trade(trade)),
+                Ok(RawUserEvent::Other) => {}
+                Err(e) => tracing::debug!("Failed to parse a user stream event: {e}"),
+            }
+        }
+
+        let mut state = self.state.write().unwrap();
+        for event in &events {
+            match event {
+                UserEvent::OrderCancelled(order) => {
+                    state.cancelled.insert(order.id.clone());
+                }
+                UserEvent::Trade(trade) => {
+                    let order_ids = trade
+                        .maker_orders
+                        .iter()
+                        .map(|order| &order.order_id)
+                        .chain([&trade.taker_order_id]);
+
+                    for order_id in order_ids {
+                        state.trades.insert(order_id.clone(), trade.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+        drop(state);
+
+        for event in events {
+            let _ = self.events.send(event);
+        }
+        self.updated.notify_waiters();
+    }
+
+    fn on_disconnect(&self) {
+        self.set_connected(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        polymarket::api::clob::schemas::ClobApiKeyResponseBody,
+        testing::{mock_server::random_account, stream_server::stream_server},
+    };
+
+    const TAKER_ORDER_ID: &str =
+        "0x8d0f5c1a9c5e3b0e4f5a6c7d8e9f0a1b2c3d4e5f60718293a4b5c6d7e8f90a1b";
+    const MAKER_ORDER_ID: &str =
+        "0x1f2e3d4c5b6a79881726354453627180f9e8d7c6b5a4938271605f4e3d2c1b0a";
+    const TOKEN_ID: &str =
+        "48331043336612883890938759509493159234755048973500640148014422747788308965732";
+
+    fn order_event(order_id: &str, event_type: &str) -> Value {
+        json!({
+            "event_type": "order",
+            "type": event_type,
+            "id": order_id,
+            "owner": "9180014b-33c8-9240-a14b-bdca11c0a465",
+            "market": "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1",
+            "asset_id": TOKEN_ID,
+            "outcome": "Yes",
+            "side": "BUY",
+            "price": "0.54",
+            "original_size": "20",
+            "size_matched": "0",
+            "timestamp": "1728897700",
+        })
+    }
+
+    fn trade_event(status: &str) -> Value {
+        json!({
+            "event_type": "trade",
+            "type": "TRADE",
+            "id": "28c4d2eb-bbea-40e7-a9f0-b2fdb56b2c2e",
+            "taker_order_id": TAKER_ORDER_ID,
+            "market": "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1",
+            "asset_id": TOKEN_ID,
+            "outcome": "Yes",
+            "side": "BUY",
+            "price": "0.54",
+            "size": "18.5",
+            "status": status,
+            "matchtime": "1728897701",
+            "maker_orders": [{
+                "order_id": MAKER_ORDER_ID,
+                "owner": "3e1d4a2c-6f7b-4e0a-9c8d-1b2a3c4d5e6f",
+                "asset_id": TOKEN_ID,
+                "outcome": "Yes",
+                "matched_amount": "18.5",
+                "price": "0.54",
+            }],
+        })
+    }
+
+    fn stream_with_creds(url: String) -> UserStream {
+        let account = random_account();
+        account.update_credentials(ClobApiKeyResponseBody {
+            api_key: "9180014b-33c8-9240-a14b-bdca11c0a465".to_string(),
+            secret: "c2VjcmV0".to_string(),
+            passphrase: "passphrase".to_string(),
+        });
+
+        UserStream::subscribe_to(url, &account).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_stream_emits_typed_events() {
+        let session = vec![
+            order_event(TAKER_ORDER_ID, "PLACEMENT"),
+            json!([
+                trade_event("MATCHED"),
+                order_event(TAKER_ORDER_ID, "UPDATE")
+            ]),
+            order_event(MAKER_ORDER_ID, "CANCELLATION"),
+        ];
+        let (url, mut subscriptions) = stream_server(vec![session]).await;
+
+        let stream = stream_with_creds(url);
+        let mut events = stream.events();
+
+        let subscription = subscriptions.recv().await.unwrap();
+        assert_eq!(subscription["type"], "user");
+        assert_eq!(
+            subscription["auth"],
+            json!({
+                "apiKey": "9180014b-33c8-9240-a14b-bdca11c0a465",
+                "secret": "c2VjcmV0",
+                "passphrase": "passphrase",
+            })
+        );
+
+        let mut received = vec![];
+        for _ in 0..4 {
+            received.push(events.recv().await.unwrap());
+        }
+
+        assert!(
+            matches!(&received[0], UserEvent::OrderPlaced(order) if order.id == TAKER_ORDER_ID)
+        );
+        let UserEvent::Trade(trade) = &received[1] else {
+            panic!("expected a trade, got {:?}", received[1]);
+        };
+        assert_eq!(trade.status, TradeStatus::Matched);
+        assert_eq!(trade.filled_size(TAKER_ORDER_ID), dec!(18.5));
+        assert!(matches!(&received[2], UserEvent::OrderUpdated(_)));
+        assert!(
+            matches!(&received[3], UserEvent::OrderCancelled(order) if order.id == MAKER_ORDER_ID)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_fill() {
+        let session = vec![trade_event("MATCHED")];
+        let (url, _subscriptions) = stream_server(vec![session]).await;
+
+        let stream = stream_with_creds(url);
+
+        let trade = stream
+            .wait_for_fill(TAKER_ORDER_ID, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(trade.size, dec!(18.5));
+
+        let error = stream
+            .wait_for_fill("0x0", Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Timeout"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_fill_reports_failures() {
+        let session = vec![
+            trade_event("FAILED"),
+            order_event("0xcancelled", "CANCELLATION"),
+        ];
+        let (url, _subscriptions) = stream_server(vec![session]).await;
+
+        let stream = stream_with_creds(url);
+        assert!(stream.wait_until_connected(Duration::from_secs(5)).await);
+
+        let error = stream
+            .wait_for_fill(TAKER_ORDER_ID, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("failed"));
+
+        let error = stream
+            .wait_for_fill("0xcancelled", Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cancelled"));
+    }
+}
@@ -1,48 +1,391 @@
+use chrono::NaiveDate;
 use reqwest::{Method, Proxy};
+use serde::Deserialize;
 
 use crate::{
     errors::custom::CustomError,
-    polymarket::api::events::schemas::Event,
+    polymarket::api::{
+        events::schemas::Event,
+        urls::{api_url, Api},
+    },
     utils::fetch::{send_http_request_with_retries, RequestParams},
 };
 
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventStatus {
+    #[default]
+    Active,
+    Closed,
+    All,
+}
+
+// Filters of the `/events` listing, `text` switches to the public search which only filters by
+// status, the remaining filters are then applied to its results
+#[derive(Debug, Clone)]
+pub struct EventsQuery {
+    pub text: Option<String>,
+    pub tag: Option<String>,
+    pub slug: Option<String>,
+    pub end_date_min: Option<NaiveDate>,
+    pub end_date_max: Option<NaiveDate>,
+    pub status: EventStatus,
+    pub include_archived: bool,
+    pub order: Option<String>,
+    pub ascending: bool,
+    pub limit: u64,
+}
+
+impl Default for EventsQuery {
+    // The most traded open events first
+    fn default() -> Self {
+        Self {
+            text: None,
+            tag: None,
+            slug: None,
+            end_date_min: None,
+            end_date_max: None,
+            status: EventStatus::Active,
+            include_archived: false,
+            order: Some("volume24hr".to_string()),
+            ascending: false,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl EventsQuery {
+    fn query_args(&self, offset: u64) -> Vec<(&'static str, String)> {
+        let mut args = vec![
+            ("limit", self.limit.to_string()),
+            ("offset", offset.to_string()),
+        ];
+
+        match self.status {
+            EventStatus::Active => {
+                args.push(("active", "true".to_string()));
+                args.push(("closed", "false".to_string()));
+            }
+            EventStatus::Closed => args.push(("closed", "true".to_string())),
+            EventStatus::All => {}
+        }
+
+        if !self.include_archived {
+            args.push(("archived", "false".to_string()));
+        }
+
+        if let Some(order) = &self.order {
+            args.push(("order", order.clone()));
+            args.push(("ascending", self.ascending.to_string()));
+        }
+
+        if let Some(tag) = &self.tag {
+            args.push(("tag_slug", tag.clone()));
+        }
+
+        if let Some(slug) = &self.slug {
+            args.push(("slug", slug.clone()));
+        }
+
+        if let Some(date) = self.end_date_min {
+            args.push(("end_date_min", format!("{date}T00:00:00Z")));
+        }
+
+        if let Some(date) = self.end_date_max {
+            args.push(("end_date_max", format!("{date}T23:59:59Z")));
+        }
+
+        args
+    }
+
+    fn search_args(&self, text: &str, offset: u64) -> Vec<(&'static str, String)> {
+        let mut args = vec![
+            ("q", text.to_string()),
+            ("limit_per_type", self.limit.to_string()),
+            ("page", (offset / self.limit.max(1) + 1).to_string()),
+            ("search_tags", "false".to_string()),
+            ("search_profiles", "false".to_string()),
+        ];
+
+        match self.status {
+            EventStatus::Active => args.push(("events_status", "active".to_string())),
+            EventStatus::Closed => args.push(("events_status", "closed".to_string())),
+            EventStatus::All => {}
+        }
+
+        args
+    }
+
+    // Filters the search endpoint can't apply itself
+    fn matches(&self, event: &Event) -> bool {
+        let tag_matches = self.tag.as_ref().is_none_or(|tag| {
+            event
+                .tags
+                .iter()
+                .any(|event_tag| event_tag.slug.as_ref() == Some(tag))
+        });
+        let slug_matches = self.slug.as_ref().is_none_or(|slug| &event.slug == slug);
+        let end_date = event.end_date.map(|date| date.date_naive());
+        let after_min = self
+            .end_date_min
+            .is_none_or(|min| end_date.is_some_and(|date| date >= min));
+        let before_max = self
+            .end_date_max
+            .is_none_or(|max| end_date.is_some_and(|date| date <= max));
+
+        tag_matches
+            && slug_matches
+            && after_min
+            && before_max
+            && (self.include_archived || !event.archived)
+    }
+
+    // The listing is already filtered by the API
+    fn filter_page(&self, events: Vec<Event>) -> Vec<Event> {
+        match self.text {
+            Some(_) => events
+                .into_iter()
+                .filter(|event| self.matches(event))
+                .collect(),
+            None => events,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponseBody {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
 pub async fn get_events(
-    limit: Option<u64>,
+    query: &EventsQuery,
     offset: u64,
     proxy: Option<&Proxy>,
 ) -> Result<Vec<Event>, CustomError> {
-    let offset = offset.to_string();
-    let limit = limit.unwrap_or(20).to_string();
-
-    let query_args = [
-        ("limit", limit.as_str()),
-        ("active", "true"),
-        ("archived", "false"),
-        ("closed", "false"),
-        ("order", "volume24hr"),
-        ("ascending", "false"),
-        ("offset", offset.as_str()),
-    ]
-    .iter()
-    .map(|(arg, value)| (*arg, *value))
-    .collect();
+    let events = fetch_events(query, offset, proxy).await?;
+
+    Ok(query.filter_page(events))
+}
+
+// One page as the API returns it, search results still have to go through `matches`
+async fn fetch_events(
+    query: &EventsQuery,
+    offset: u64,
+    proxy: Option<&Proxy>,
+) -> Result<Vec<Event>, CustomError> {
+    if let Some(text) = &query.text {
+        let args = query.search_args(text, offset);
+
+        return Ok(
+            send_gamma_request::<SearchResponseBody>("/public-search", &args, proxy)
+                .await?
+                .map(|body| body.events)
+                .unwrap_or_default(),
+        );
+    }
+
+    let args = query.query_args(offset);
+
+    Ok(send_gamma_request::<Vec<Event>>("/events", &args, proxy)
+        .await?
+        .unwrap_or_default())
+}
+
+pub async fn get_event_by_slug(
+    slug: &str,
+    proxy: Option<&Proxy>,
+) -> Result<Option<Event>, CustomError> {
+    let query = EventsQuery {
+        slug: Some(slug.to_string()),
+        status: EventStatus::All,
+        include_archived: true,
+        order: None,
+        ..Default::default()
+    };
+
+    Ok(get_events(&query, 0, proxy).await?.into_iter().next())
+}
+
+async fn send_gamma_request<R: serde::de::DeserializeOwned>(
+    path: &str,
+    args: &[(&'static str, String)],
+    proxy: Option<&Proxy>,
+) -> Result<Option<R>, CustomError> {
+    let query_args = args
+        .iter()
+        .map(|(arg, value)| (*arg, value.as_str()))
+        .collect();
 
     let request_params = RequestParams {
-        url: "https://gamma-api.polymarket.com/events",
+        url: &api_url(Api::Gamma, path),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
     };
 
-    let response = send_http_request_with_retries::<Vec<Event>>(
-        &request_params,
-        None,
-        proxy,
-        None,
-        None,
-        |_| true,
-    )
-    .await?;
-
-    Ok(response.body.unwrap())
+    let response =
+        send_http_request_with_retries::<R>(&request_params, None, proxy, None, None, |_| true)
+            .await?;
+
+    Ok(response.body)
+}
+
+// Walks a query page by page, a short page ends it
+pub struct EventPages<'a> {
+    query: EventsQuery,
+    proxy: Option<&'a Proxy>,
+    offset: u64,
+    done: bool,
+}
+
+impl<'a> EventPages<'a> {
+    // Pages are numbered from 0
+    pub fn new(query: EventsQuery, first_page: u64, proxy: Option<&'a Proxy>) -> Self {
+        Self {
+            offset: first_page * query.limit,
+            query,
+            proxy,
+            done: false,
+        }
+    }
+
+    // The page the next call returns
+    pub fn page_number(&self) -> u64 {
+        self.offset / self.query.limit.max(1)
+    }
+
+    // Search pages with no result left after filtering are skipped, the page number moves past
+    // them
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Event>>, CustomError> {
+        while !self.done {
+            let events = fetch_events(&self.query, self.offset, self.proxy).await?;
+
+            // Ended by the unfiltered page, only an empty search page ends the search results
+            self.done = match self.query.text {
+                Some(_) => events.is_empty(),
+                None => (events.len() as u64) < self.query.limit,
+            };
+            self.offset += self.query.limit;
+
+            let events = self.query.filter_page(events);
+
+            if !events.is_empty() {
+                return Ok(Some(events));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mock_server::mock_server;
+
+    const EVENTS: &str = include_str!("../../../testing/fixtures/gamma_events.json");
+
+    fn args(args: Vec<(&'static str, String)>) -> Vec<String> {
+        args.into_iter()
+            .map(|(arg, value)| format!("{arg}={value}"))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_query_matches_trending_listing() {
+        assert_eq!(
+            args(EventsQuery::default().query_args(40)),
+            [
+                "limit=20",
+                "offset=40",
+                "active=true",
+                "closed=false",
+                "archived=false",
+                "order=volume24hr",
+                "ascending=false"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let query = EventsQuery {
+            tag: Some("economy".to_string()),
+            end_date_min: NaiveDate::from_ymd_opt(2024, 12, 1),
+            end_date_max: NaiveDate::from_ymd_opt(2024, 12, 31),
+            status: EventStatus::All,
+            include_archived: true,
+            order: None,
+            limit: 50,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args(query.query_args(0)),
+            [
+                "limit=50",
+                "offset=0",
+                "tag_slug=economy",
+                "end_date_min=2024-12-01T00:00:00Z",
+                "end_date_max=2024-12-31T23:59:59Z"
+            ]
+        );
+
+        let events = serde_json::from_str::<Vec<Event>>(EVENTS).unwrap();
+        let matching = events
+            .iter()
+            .filter(|event| query.matches(event))
+            .map(|event| event.slug.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(matching, ["fed-decision-in-december"]);
+    }
+
+    #[test]
+    fn test_search_args() {
+        let query = EventsQuery {
+            status: EventStatus::Closed,
+            limit: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            args(query.search_args("hurricanes", 20)),
+            [
+                "q=hurricanes",
+                "limit_per_type=10",
+                "page=3",
+                "search_tags=false",
+                "search_profiles=false",
+                "events_status=closed"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_continue_past_filtered_out_page_offline() {
+        let mock = mock_server();
+        let events = serde_json::from_str::<Vec<serde_json::Value>>(EVENTS).unwrap();
+        // Page 1 only has the untagged event, page 2 the one with the tag
+        mock.add_search_event(events[1].clone());
+        mock.add_search_event(events[0].clone());
+
+        let query = EventsQuery {
+            text: Some("rates".to_string()),
+            tag: Some("economy".to_string()),
+            limit: 1,
+            ..Default::default()
+        };
+
+        assert!(get_events(&query, 0, None).await.unwrap().is_empty());
+
+        let mut pages = EventPages::new(query, 0, None);
+        let page = pages.next_page().await.unwrap().unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].slug, "fed-decision-in-december");
+        assert_eq!(pages.page_number(), 2);
+
+        assert!(pages.next_page().await.unwrap().is_none());
+    }
 }
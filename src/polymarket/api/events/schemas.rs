@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{de, Deserialize, Deserializer};
 
 #[allow(unused)]
@@ -9,32 +10,133 @@ pub struct Event {
     pub id: String,
     pub slug: String,
     pub title: String,
-    pub volume: f64,
+    pub description: Option<String>,
+    pub resolution_source: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub volume: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub volume_24hr: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub liquidity: Option<f64>,
+    #[serde(default)]
     pub markets: Vec<Market>,
     pub neg_risk: Option<bool>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
 }
 
+// Outcome and token lists are kept as sent, the order modules only trade binary markets
 #[allow(unused)]
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Market {
     pub id: String,
     pub question: String,
-    pub active: bool,
+    pub slug: Option<String>,
+    pub condition_id: Option<String>,
     #[serde(rename = "questionID")]
     pub question_id: Option<String>,
-    #[serde(deserialize_with = "deserialize_into_string_array")]
-    pub outcomes: [String; 2],
+    pub description: Option<String>,
+    pub resolution_source: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active: bool,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    pub outcomes: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_outcome_prices")]
-    pub outcome_prices: Option<[f64; 2]>,
+    pub outcome_prices: Option<Vec<f64>>,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    pub clob_token_ids: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub volume: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub volume_24hr: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub liquidity: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub best_bid: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub best_ask: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_number")]
+    pub last_trade_price: Option<f64>,
+    #[serde(default)]
     pub rewards_max_spread: f64,
-    #[serde(default, deserialize_with = "deserialize_into_string_array")]
-    pub clob_token_ids: [String; 2],
+    #[serde(default)]
     pub spread: f64,
+    #[serde(default = "default_tick_size")]
     pub order_price_min_tick_size: f64,
+    pub neg_risk: Option<bool>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+#[allow(unused)]
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tag {
+    pub label: Option<String>,
+    pub slug: Option<String>,
+}
+
+fn default_tick_size() -> f64 {
+    0.01
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f64),
+    String(String),
+}
+
+// Gamma sends most amounts as numbers on events and as strings on markets
+fn deserialize_number<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        Some(NumberOrString::Number(number)) => Ok(Some(number)),
+        Some(NumberOrString::String(s)) if !s.is_empty() => {
+            s.parse().map(Some).map_err(de::Error::custom)
+        }
+        _ => Ok(None),
+    }
 }
 
-fn deserialize_outcome_prices<'de, D>(deserializer: D) -> Result<Option<[f64; 2]>, D::Error>
+// Dates are RFC 3339 timestamps, a few older markets only have the day
+fn deserialize_date<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(&s) {
+        return Ok(Some(date.to_utc()));
+    }
+
+    Ok(NaiveDate::parse_from_str(&s, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc()))
+}
+
+fn deserialize_outcome_prices<'de, D>(deserializer: D) -> Result<Option<Vec<f64>>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -45,49 +147,60 @@ where
             let vec_str: Vec<String> =
                 serde_json::from_str(&s).map_err(|err| de::Error::custom(err.to_string()))?;
 
-            if vec_str.len() != 2 {
-                return Err(de::Error::invalid_length(
-                    vec_str.len(),
-                    &"expected an array of length 2",
-                ));
-            }
-
-            let mut vec_f64 = [0.0; 2];
-            for (i, val_str) in vec_str.iter().enumerate() {
-                vec_f64[i] = val_str
-                    .parse::<f64>()
-                    .map_err(|e| de::Error::custom(e.to_string()))?;
-            }
-
-            Ok(Some(vec_f64))
+            let prices = vec_str
+                .iter()
+                .map(|val_str| {
+                    val_str
+                        .parse::<f64>()
+                        .map_err(|e| de::Error::custom(e.to_string()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(prices))
         }
         None => Ok(None),
     }
 }
 
-fn deserialize_into_string_array<'de, D>(deserializer: D) -> Result<[String; 2], D::Error>
+// Lists are sent as JSON encoded strings
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: String = Deserialize::deserialize(deserializer)?;
-
-    let vec: Vec<String> =
-        serde_json::from_str(&s).map_err(|err| de::Error::custom(err.to_string()))?;
-
-    if vec.len() != 2 {
-        return Err(de::Error::invalid_length(
-            vec.len(),
-            &"expected an array of length 2",
-        ));
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => serde_json::from_str(&s).map_err(|err| de::Error::custom(err.to_string())),
+        None => Ok(vec![]),
     }
-
-    Ok([vec[0].clone(), vec[1].clone()])
 }
 
 impl Event {
     pub fn get_url(&self) -> String {
         format!("https://polymarket.com/event/{}", self.slug)
     }
+
+    pub fn status(&self) -> &'static str {
+        status(self.active, self.closed, self.archived)
+    }
+}
+
+impl Market {
+    // Two outcomes with a token each, the only kind the order modules trade
+    pub fn is_binary(&self) -> bool {
+        self.outcomes.len() == 2 && self.clob_token_ids.len() == 2
+    }
+
+    pub fn status(&self) -> &'static str {
+        status(self.active, self.closed, self.archived)
+    }
+}
+
+fn status(active: bool, closed: bool, archived: bool) -> &'static str {
+    match (active, closed, archived) {
+        (_, _, true) => "archived",
+        (_, true, _) => "closed",
+        (true, _, _) => "active",
+        _ => "inactive",
+    }
 }
 
 impl Display for Event {
@@ -95,3 +208,51 @@ impl Display for Event {
         write!(f, "{} - {}", self.title, self.get_url(),)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const EVENTS: &str = include_str!("../../../testing/fixtures/gamma_events.json");
+
+    #[test]
+    fn test_events_deserialization() {
+        let events = serde_json::from_str::<Vec<Event>>(EVENTS).unwrap();
+        assert_eq!(events.len(), 2);
+
+        let event = &events[0];
+        assert_eq!(event.volume, Some(1843207.52));
+        assert_eq!(event.tags[0].slug.as_deref(), Some("economy"));
+        assert_eq!(
+            event.end_date,
+            Some(Utc.with_ymd_and_hms(2024, 12, 18, 12, 0, 0).unwrap())
+        );
+
+        let market = &event.markets[0];
+        assert!(market.is_binary());
+        assert_eq!(market.outcome_prices, Some(vec![0.615, 0.385]));
+        assert_eq!(market.volume, Some(1843207.52));
+        assert_eq!(market.liquidity, Some(95310.4));
+        assert_eq!(
+            market.condition_id.as_deref(),
+            Some("0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1")
+        );
+
+        let event = &events[1];
+        assert_eq!(event.status(), "closed");
+        assert_eq!(event.description, None);
+        assert_eq!(event.markets.len(), 3);
+
+        let market = &event.markets[2];
+        assert!(!market.is_binary());
+        assert_eq!(market.outcomes, ["Under 2", "2-4", "Over 4"]);
+        assert_eq!(
+            market.end_date,
+            Some(Utc.with_ymd_and_hms(2024, 10, 31, 0, 0, 0).unwrap())
+        );
+        assert_eq!(market.volume, None);
+        assert_eq!(market.order_price_min_tick_size, 0.01);
+    }
+}
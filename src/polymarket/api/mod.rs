@@ -3,4 +3,5 @@ pub mod clob;
 pub mod events;
 pub mod relayer;
 pub mod typedefs;
+pub mod urls;
 pub mod user;
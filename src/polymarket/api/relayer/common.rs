@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use alloy::{
-    primitives::{bytes, Address, U256},
+    primitives::{bytes, Address, B256, U256},
     signers::Signer,
     sol_types::SolCall,
 };
@@ -17,12 +17,15 @@ use crate::{
 use super::{
     constants::{
         CONDITIONAL_TOKENS_CONTRACT_ADDRESS, MULTISEND_CONTRACT_ADDRESS,
-        UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
+        NEG_RISK_ADAPTER_CONTRACT_ADDRESS, UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
     },
     endpoints::{get_nonce, send_relayer_transaction},
     schemas::RelayerRequestBody,
     signature_params::{RelayerRequestType, SignatureParams},
-    tx_builder::{get_multisend_calldata, get_packed_signature, RelayerTransaction},
+    tx_builder::{
+        get_multisend_calldata, get_packed_signature, IConditionalTokens, INegRiskAdapter,
+        RelayerTransaction,
+    },
 };
 
 pub fn get_approve_bundle() -> Vec<RelayerTransaction> {
@@ -191,3 +194,87 @@ pub async fn approve_tokens<S: Signer + Send + Sync>(
 
     Ok(transaction_response.transaction_id)
 }
+
+pub fn get_redeem_calldata(
+    condition_id: B256,
+    neg_risk: bool,
+    amounts: Vec<U256>,
+) -> (Address, Vec<u8>) {
+    match neg_risk {
+        true => (
+            NEG_RISK_ADAPTER_CONTRACT_ADDRESS,
+            INegRiskAdapter::redeemPositionsCall {
+                conditionId: condition_id,
+                amounts,
+            }
+            .abi_encode(),
+        ),
+        false => (
+            CONDITIONAL_TOKENS_CONTRACT_ADDRESS,
+            IConditionalTokens::redeemPositionsCall {
+                collateralToken: UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
+                parentCollectionId: B256::ZERO,
+                conditionId: condition_id,
+                indexSets: vec![U256::from(1), U256::from(2)],
+            }
+            .abi_encode(),
+        ),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn redeem_positions<S: Signer + Send + Sync>(
+    signer: Arc<S>,
+    amp_cookie: &mut AmpCookie,
+    polymarket_nonce: &str,
+    polymarket_session: &str,
+    proxy: Option<&Proxy>,
+    condition_id: B256,
+    neg_risk: bool,
+    amounts: Vec<U256>,
+) -> Result<String, CustomError> {
+    let nonce = get_nonce(
+        signer.address(),
+        proxy,
+        amp_cookie,
+        polymarket_nonce,
+        polymarket_session,
+    )
+    .await?;
+
+    let (to, data) = get_redeem_calldata(condition_id, neg_risk, amounts);
+    let packed_signature =
+        get_packed_signature(signer.clone(), 0, U256::from(nonce), data.clone(), to).await?;
+
+    let data_hex = const_hex::encode_prefixed(data);
+    let nonce_str = nonce.to_string();
+
+    let signature_params = SignatureParams::default()
+        .with_gas_price()
+        .with_operation("0")
+        .with_safe_txn_gas()
+        .with_base_gas()
+        .with_gas_token()
+        .with_refund_receiver();
+
+    let body = RelayerRequestBody::default()
+        .with_from(signer.address())
+        .with_to(to)
+        .with_proxy_wallet(get_proxy_wallet_address(signer))
+        .with_data(&data_hex)
+        .with_nonce(&nonce_str)
+        .with_signature(&packed_signature)
+        .with_signature_params(signature_params)
+        .with_type(RelayerRequestType::Safe);
+
+    let transaction_response = send_relayer_transaction(
+        proxy,
+        body,
+        amp_cookie,
+        polymarket_nonce,
+        polymarket_session,
+    )
+    .await?;
+
+    Ok(transaction_response.transaction_id)
+}
@@ -1,8 +1,14 @@
 use crate::{
     errors::custom::CustomError,
-    polymarket::api::typedefs::AmpCookie,
+    onchain::constants::POLYGON_EXPLORER_TX_BASE_URL,
+    polymarket::api::{
+        typedefs::AmpCookie,
+        urls::{api_url, Api},
+    },
     utils::{
+        dry_run::{self, DRY_RUN_ID},
         fetch::{send_http_request_with_retries, RequestParams},
+        notifier::{notify, NotificationKind},
         poly::build_poly_headers,
     },
 };
@@ -31,7 +37,7 @@ pub async fn get_transaction_status(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://relayer-v2.polymarket.com/transaction",
+        url: &api_url(Api::Relayer, "/transaction"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -64,6 +70,10 @@ pub async fn wait_for_transaction_confirmation(
     timeout_duration: Option<Duration>,
     poll_interval: Option<Duration>,
 ) -> Result<String, CustomError> {
+    if dry_run::is_enabled() && transaction_id == DRY_RUN_ID {
+        return Ok(DRY_RUN_ID.to_string());
+    }
+
     let timeout_duration = timeout_duration.unwrap_or(Duration::from_secs(100));
     let poll_interval = poll_interval.unwrap_or(Duration::from_secs(5));
 
@@ -89,12 +99,23 @@ pub async fn wait_for_transaction_confirmation(
         }
     };
 
-    match timeout(timeout_duration, polling_future).await {
-        Ok(result) => result,
-        Err(_) => Err(CustomError::Timeout(
-            "Transaction not mined within timeout".to_string(),
-        )),
-    }
+    let transaction_hash = match timeout(timeout_duration, polling_future).await {
+        Ok(result) => result?,
+        Err(_) => {
+            return Err(CustomError::Timeout(
+                "Transaction not mined within timeout".to_string(),
+            ))
+        }
+    };
+
+    notify(
+        NotificationKind::TransactionConfirmed,
+        "Relayer transaction mined",
+        format!("{transaction_id} | {POLYGON_EXPLORER_TX_BASE_URL}{transaction_hash}"),
+    )
+    .await;
+
+    Ok(transaction_hash)
 }
 
 pub async fn get_nonce(
@@ -113,7 +134,7 @@ pub async fn get_nonce(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://relayer-v2.polymarket.com/nonce",
+        url: &api_url(Api::Relayer, "/nonce"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -141,10 +162,20 @@ pub async fn send_relayer_transaction<'a>(
     polymarket_nonce: &str,
     polymarket_session: &str,
 ) -> Result<RelayerResponseBody, CustomError> {
+    if dry_run::is_enabled() {
+        dry_run::record(body.proxy_wallet(), "Relayer transaction", body.describe());
+
+        return Ok(RelayerResponseBody {
+            transaction_id: DRY_RUN_ID.to_string(),
+            transaction_hash: DRY_RUN_ID.to_string(),
+            state: DRY_RUN_ID.to_string(),
+        });
+    }
+
     let headers = build_poly_headers(amp_cookie, polymarket_nonce, polymarket_session);
 
     let request_params = RequestParams {
-        url: "https://relayer-v2.polymarket.com/submit",
+        url: &api_url(Api::Relayer, "/submit"),
         method: Method::POST,
         body: Some(body),
         query_args: None,
@@ -3,4 +3,4 @@ pub mod constants;
 pub mod endpoints;
 pub mod schemas;
 mod signature_params;
-mod tx_builder;
+pub mod tx_builder;
@@ -1,7 +1,10 @@
 use alloy::primitives::Address;
 use serde::{Deserialize, Serialize};
 
-use super::signature_params::{RelayerRequestType, SignatureParams};
+use super::{
+    signature_params::{RelayerRequestType, SignatureParams},
+    tx_builder::describe_calls,
+};
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Default, Debug)]
@@ -90,6 +93,28 @@ impl<'a> RelayerRequestBody<'a> {
         self.set_type(req_type);
         self
     }
+
+    pub fn proxy_wallet(&self) -> &str {
+        &self.proxy_wallet
+    }
+
+    pub fn describe(&self) -> String {
+        match self.type_ {
+            RelayerRequestType::SafeCreate => "Deploy the Safe proxy wallet".to_string(),
+            RelayerRequestType::Safe => {
+                let calls = match (self.to.parse::<Address>(), const_hex::decode(self.data)) {
+                    (Ok(to), Ok(data)) => describe_calls(to, &data),
+                    _ => vec![format!("{}.call({})", self.to, self.data)],
+                };
+
+                format!(
+                    "Safe nonce {}: {}",
+                    self.nonce.unwrap_or("-"),
+                    calls.join("; ")
+                )
+            }
+        }
+    }
 }
 
 #[allow(unused)]
@@ -2,16 +2,33 @@ use std::sync::Arc;
 
 use alloy::{
     dyn_abi::SolType,
-    primitives::{Address, Bytes, U256},
-    signers::{Signature, Signer},
+    primitives::{Address, Bytes, PrimitiveSignature, U256},
+    signers::Signer,
     sol,
     sol_types::{eip712_domain, SolCall, SolStruct, SolValue},
 };
 
-use crate::utils::poly::get_proxy_wallet_address;
+use crate::{
+    onchain::client::IERC20::{approveCall, transferCall},
+    utils::poly::get_proxy_wallet_address,
+};
+
+use super::constants::{
+    CONDITIONAL_TOKENS_CONTRACT_ADDRESS, MULTISEND_CONTRACT_ADDRESS,
+    NEG_RISK_ADAPTER_CONTRACT_ADDRESS, UCHILD_ERC20_PROXY_CONTRACT_ADDRESS,
+};
 
 sol! {
     function multiSend(bytes bytes) external payable;
+    function setApprovalForAll(address operator, bool approved) external;
+
+    interface IConditionalTokens {
+        function redeemPositions(address collateralToken, bytes32 parentCollectionId, bytes32 conditionId, uint256[] indexSets) external;
+    }
+
+    interface INegRiskAdapter {
+        function redeemPositions(bytes32 conditionId, uint256[] amounts) external;
+    }
 
     #[derive(Debug)]
     struct SafeTx {
@@ -101,6 +118,7 @@ where
     let message = transaction.eip712_signing_hash(&domain);
 
     let signature = signer.sign_message(message.as_slice()).await?;
+    let signature = PrimitiveSignature::try_from(&signature.as_bytes()[..])?;
     let parity = get_v_incremented(&signature);
 
     let packed_signature = <sol! {(uint256, uint256, uint8)}>::abi_encode_packed(&(
@@ -112,14 +130,131 @@ where
     Ok(const_hex::encode_prefixed(packed_signature))
 }
 
-fn get_v_incremented(signature: &Signature) -> u8 {
-    let mut v = signature.v().to_u64();
+fn contract_name(address: Address) -> String {
+    match address {
+        UCHILD_ERC20_PROXY_CONTRACT_ADDRESS => "USDC.e".to_string(),
+        CONDITIONAL_TOKENS_CONTRACT_ADDRESS => "ConditionalTokens".to_string(),
+        NEG_RISK_ADAPTER_CONTRACT_ADDRESS => "NegRiskAdapter".to_string(),
+        MULTISEND_CONTRACT_ADDRESS => "MultiSend".to_string(),
+        address => address.to_string(),
+    }
+}
+
+// Human readable contract calls, multisend batches are expanded into their inner calls
+pub fn describe_calls(to: Address, data: &[u8]) -> Vec<String> {
+    let contract = contract_name(to);
+
+    if let Ok(call) = multiSendCall::abi_decode(data, true) {
+        return decode_multisend_transactions(&call.bytes)
+            .map(|transactions| {
+                transactions
+                    .into_iter()
+                    .flat_map(|tx| describe_calls(tx.to, &tx.data))
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![format!("{contract}.multiSend(<malformed batch>)")]);
+    }
+
+    let call = if let Ok(call) = transferCall::abi_decode(data, true) {
+        format!("transfer(to: {}, amount: {})", call.to, call.amount)
+    } else if let Ok(call) = approveCall::abi_decode(data, true) {
+        let amount = match call.amount == U256::MAX {
+            true => "unlimited".to_string(),
+            false => call.amount.to_string(),
+        };
+        format!(
+            "approve(spender: {}, amount: {amount})",
+            contract_name(call.spender)
+        )
+    } else if let Ok(call) = setApprovalForAllCall::abi_decode(data, true) {
+        format!(
+            "setApprovalForAll(operator: {}, approved: {})",
+            contract_name(call.operator),
+            call.approved
+        )
+    } else if let Ok(call) = IConditionalTokens::redeemPositionsCall::abi_decode(data, true) {
+        format!(
+            "redeemPositions(conditionId: {}, indexSets: {:?})",
+            call.conditionId,
+            call.indexSets
+                .iter()
+                .map(U256::to_string)
+                .collect::<Vec<_>>()
+        )
+    } else if let Ok(call) = INegRiskAdapter::redeemPositionsCall::abi_decode(data, true) {
+        format!(
+            "redeemPositions(conditionId: {}, amounts: {:?})",
+            call.conditionId,
+            call.amounts.iter().map(U256::to_string).collect::<Vec<_>>()
+        )
+    } else {
+        format!("call({})", const_hex::encode_prefixed(data))
+    };
+
+    vec![format!("{contract}.{call}")]
+}
+
+// Reverses `get_multisend_calldata`: packed (operation, to, value, dataLen, data) entries
+fn decode_multisend_transactions(mut bytes: &[u8]) -> Option<Vec<RelayerTransaction>> {
+    let mut transactions = vec![];
 
-    match v {
-        0 | 1 => v += 31,
-        27 | 28 => v += 4,
-        _ => unreachable!(),
+    while !bytes.is_empty() {
+        let header = bytes.get(..85)?;
+        let to = Address::from_slice(&header[1..21]);
+        let value = U256::from_be_slice(&header[21..53]);
+        let data_len = usize::try_from(U256::from_be_slice(&header[53..85])).ok()?;
+        let data = bytes.get(85..85 + data_len)?;
+
+        transactions.push(RelayerTransaction::new(
+            header[0],
+            to,
+            value,
+            Bytes::copy_from_slice(data),
+        ));
+        bytes = &bytes[85 + data_len..];
     }
 
-    v as u8
+    Some(transactions)
+}
+
+// The Safe marks `eth_sign` signatures with v of 31 or 32
+fn get_v_incremented(signature: &PrimitiveSignature) -> u8 {
+    31 + signature.v() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::api::relayer::common::get_approve_bundle;
+
+    #[test]
+    fn test_describe_calls() {
+        let calldata = get_multisend_calldata(get_approve_bundle());
+        let calls = describe_calls(MULTISEND_CONTRACT_ADDRESS, &calldata);
+
+        assert_eq!(calls.len(), 7);
+        assert_eq!(
+            calls[0],
+            "USDC.e.approve(spender: ConditionalTokens, amount: unlimited)"
+        );
+        assert_eq!(
+            calls[2],
+            "ConditionalTokens.setApprovalForAll(operator: 0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E, approved: true)"
+        );
+
+        let to = Address::repeat_byte(1);
+        let transfer = transferCall {
+            to,
+            amount: U256::from(5),
+        }
+        .abi_encode();
+        assert_eq!(
+            describe_calls(UCHILD_ERC20_PROXY_CONTRACT_ADDRESS, &transfer),
+            [format!("USDC.e.transfer(to: {to}, amount: 5)")]
+        );
+        assert_eq!(
+            describe_calls(to, &[0xab, 0xcd]),
+            [format!("{to}.call(0xabcd)")]
+        );
+    }
 }
@@ -0,0 +1,72 @@
+use std::sync::RwLock;
+
+pub const GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+pub const CLOB_API_URL: &str = "https://clob.polymarket.com";
+pub const DATA_API_URL: &str = "https://data-api.polymarket.com";
+pub const LB_API_URL: &str = "https://lb-api.polymarket.com";
+pub const RELAYER_API_URL: &str = "https://relayer-v2.polymarket.com";
+pub const CLOB_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws";
+
+static API_URLS: RwLock<Option<ApiUrls>> = RwLock::new(None);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Api {
+    Gamma,
+    Clob,
+    Data,
+    Leaderboard,
+    Relayer,
+    ClobWs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiUrls {
+    pub gamma: String,
+    pub clob: String,
+    pub data: String,
+    pub leaderboard: String,
+    pub relayer: String,
+    pub clob_ws: String,
+}
+
+impl Default for ApiUrls {
+    fn default() -> Self {
+        Self {
+            gamma: GAMMA_API_URL.to_string(),
+            clob: CLOB_API_URL.to_string(),
+            data: DATA_API_URL.to_string(),
+            leaderboard: LB_API_URL.to_string(),
+            relayer: RELAYER_API_URL.to_string(),
+            clob_ws: CLOB_WS_URL.to_string(),
+        }
+    }
+}
+
+impl ApiUrls {
+    fn base(&self, api: Api) -> &str {
+        match api {
+            Api::Gamma => &self.gamma,
+            Api::Clob => &self.clob,
+            Api::Data => &self.data,
+            Api::Leaderboard => &self.leaderboard,
+            Api::Relayer => &self.relayer,
+            Api::ClobWs => &self.clob_ws,
+        }
+    }
+}
+
+// Replaces the production base URLs for the rest of the process
+pub fn set_api_urls(urls: ApiUrls) {
+    *API_URLS.write().unwrap() = Some(urls);
+}
+
+// `path` is appended as is and has to start with `/`
+pub fn api_url(api: Api, path: &str) -> String {
+    let urls = API_URLS.read().unwrap();
+    let base = match urls.as_ref() {
+        Some(urls) => urls.base(api).to_string(),
+        None => ApiUrls::default().base(api).to_string(),
+    };
+
+    format!("{}{path}", base.trim_end_matches('/'))
+}
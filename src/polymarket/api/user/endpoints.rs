@@ -8,7 +8,10 @@ use reqwest::{
 
 use crate::{
     errors::custom::CustomError,
-    polymarket::api::typedefs::AmpCookie,
+    polymarket::api::{
+        typedefs::AmpCookie,
+        urls::{api_url, Api},
+    },
     utils::{
         fetch::{send_http_request_with_retries, RequestParams},
         poly::{build_cookie_header, build_poly_headers, parse_cookies},
@@ -19,7 +22,7 @@ use super::schemas::{CreateUserRequestBody, CreateUserResponseBody, GetAuthNonce
 
 pub async fn get_auth_nonce(proxy: Option<&Proxy>) -> Result<(String, String), CustomError> {
     let request_params = RequestParams {
-        url: "https://gamma-api.polymarket.com/nonce",
+        url: &api_url(Api::Gamma, "/nonce"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: None,
@@ -67,7 +70,7 @@ pub async fn login(
         .collect::<HeaderMap>();
 
     let request_params = RequestParams {
-        url: "https://gamma-api.polymarket.com/login",
+        url: &api_url(Api::Gamma, "/login"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: None,
@@ -105,7 +108,7 @@ pub async fn create_profile<S: Signer>(
     let body = CreateUserRequestBody::new(signer);
 
     let request_params = RequestParams {
-        url: "https://gamma-api.polymarket.com/profiles",
+        url: &api_url(Api::Gamma, "/profiles"),
         method: Method::POST,
         query_args: None,
         body: Some(body),
@@ -134,7 +137,7 @@ pub async fn update_username(
 ) -> Result<(), CustomError> {
     let headers = build_poly_headers(amp_cookie, polymarket_nonce, polymarket_session);
 
-    let url = format!("https://gamma-api.polymarket.com/profiles/{}", profile_id);
+    let url = api_url(Api::Gamma, &format!("/profiles/{}", profile_id));
 
     let body = UpdateUsernameRequestBody::new(username);
 
@@ -167,10 +170,7 @@ pub async fn update_preferences(
 ) -> Result<(), CustomError> {
     let headers = build_poly_headers(amp_cookie, polymarket_nonce, polymarket_session);
 
-    let url = format!(
-        "https://gamma-api.polymarket.com/preferences/{}",
-        preferences_id
-    );
+    let url = api_url(Api::Gamma, &format!("/preferences/{}", preferences_id));
 
     let body = UpdatePreferencesRequestBody::new();
 
@@ -203,7 +203,7 @@ pub async fn get_user(
     let headers = build_poly_headers(amp_cookie, polymarket_nonce, polymarket_session);
 
     let request_params = RequestParams {
-        url: "https://gamma-api.polymarket.com/users",
+        url: &api_url(Api::Gamma, "/users"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: None,
@@ -237,7 +237,7 @@ pub async fn get_user_positions(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://data-api.polymarket.com/positions",
+        url: &api_url(Api::Data, "/positions"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -270,7 +270,7 @@ pub async fn get_user_volume(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://lb-api.polymarket.com/volume",
+        url: &api_url(Api::Leaderboard, "/volume"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -303,7 +303,7 @@ pub async fn get_user_pnl(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://lb-api.polymarket.com/profit",
+        url: &api_url(Api::Leaderboard, "/profit"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -332,7 +332,7 @@ pub async fn get_user_trade_count(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://data-api.polymarket.com/traded",
+        url: &api_url(Api::Data, "/traded"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -361,7 +361,7 @@ pub async fn get_user_open_positions_value(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://data-api.polymarket.com/value",
+        url: &api_url(Api::Data, "/value"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -394,7 +394,7 @@ pub async fn get_user_last_activity_time(
         .collect();
 
     let request_params = RequestParams {
-        url: "https://data-api.polymarket.com/activity",
+        url: &api_url(Api::Data, "/activity"),
         method: Method::GET,
         body: None::<serde_json::Value>,
         query_args: Some(query_args),
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use alloy::{primitives::Address, signers::Signer};
 
 use chrono::Utc;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::utils::poly::get_proxy_wallet_address;
@@ -237,20 +238,28 @@ impl<'a> UpdatePreferencesRequestBody<'a> {
 #[serde(rename_all = "camelCase")]
 pub struct UserPosition {
     pub asset: String,
-    pub size: f64,
+    pub size: Decimal,
     pub negative_risk: bool,
+    #[serde(default)]
+    pub condition_id: String,
+    #[serde(default)]
+    pub outcome_index: usize,
+    #[serde(default)]
+    pub redeemable: bool,
+    #[serde(default)]
+    pub title: String,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserVolumeStats {
-    pub amount: f64,
+    pub amount: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserPnlStats {
-    pub amount: f64,
+    pub amount: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
@@ -262,7 +271,7 @@ pub struct UserTradesResponseBody {
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UserOpenPositionsStats {
-    pub value: f64,
+    pub value: Decimal,
 }
 
 #[derive(Deserialize, Debug)]
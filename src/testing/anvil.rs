@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use alloy::{
+    node_bindings::{Anvil, AnvilInstance},
+    primitives::{Address, B256, U256},
+    providers::{ext::AnvilApi, ProviderBuilder, RootProvider},
+    signers::local::PrivateKeySigner,
+    transports::http::{Client, Http},
+};
+use alloy_chains::NamedChain;
+
+use super::contracts::{address_slot, approval_slot, mock_multicall, mock_safe, mock_token};
+use crate::{
+    onchain::{
+        client::EvmClient,
+        constants::{MULTICALL_CONTRACT_ADDRESS, USDCE_CONTRACT_ADDRESS},
+    },
+    polymarket::api::relayer::constants::CONDITIONAL_TOKENS_CONTRACT_ADDRESS,
+};
+
+pub type LocalProvider = RootProvider<Http<Client>>;
+
+// A local anvil node with the Polygon chain id and the mock contracts deployed at the production
+// addresses, dropped together with the node process
+pub struct LocalChain {
+    pub provider: Arc<LocalProvider>,
+    pub private_key: String,
+    _anvil: AnvilInstance,
+}
+
+impl LocalChain {
+    // Tests using it are `#[ignore]`d and run with `cargo test -- --ignored` where Foundry is
+    // installed, a missing anvil fails them
+    pub async fn spawn() -> Self {
+        let anvil = Anvil::new()
+            .chain_id(NamedChain::Polygon as u64)
+            .try_spawn()
+            .unwrap_or_else(|e| panic!("anvil is required for local chain tests: {e}"));
+
+        let provider = Arc::new(ProviderBuilder::new().on_http(anvil.endpoint_url()));
+        let private_key = const_hex::encode_prefixed(anvil.keys()[0].to_bytes());
+
+        let contracts = [
+            (USDCE_CONTRACT_ADDRESS, mock_token()),
+            (CONDITIONAL_TOKENS_CONTRACT_ADDRESS, mock_token()),
+            (MULTICALL_CONTRACT_ADDRESS, mock_multicall()),
+        ];
+        for (address, code) in contracts {
+            provider.anvil_set_code(address, code).await.unwrap();
+        }
+
+        Self {
+            provider,
+            private_key,
+            _anvil: anvil,
+        }
+    }
+
+    pub fn client(&self) -> EvmClient<LocalProvider, Http<Client>> {
+        EvmClient::new(
+            self.provider.clone(),
+            &self.private_key,
+            NamedChain::Polygon,
+        )
+    }
+
+    pub fn address(&self) -> Address {
+        PrivateKeySigner::from_slice(&const_hex::decode(&self.private_key).unwrap())
+            .unwrap()
+            .address()
+    }
+
+    pub async fn set_token_balance(&self, token: Address, owner: Address, balance: U256) {
+        self.set_storage(token, address_slot(owner), balance).await;
+    }
+
+    // Sets an ERC-20 allowance, or an ERC-1155 operator approval when `value` is 1
+    pub async fn set_approval(
+        &self,
+        token: Address,
+        owner: Address,
+        spender: Address,
+        value: U256,
+    ) {
+        self.set_storage(token, approval_slot(owner, spender), value)
+            .await;
+    }
+
+    pub async fn deploy_safe(&self, address: Address, owners: &[Address]) {
+        self.provider
+            .anvil_set_code(address, mock_safe())
+            .await
+            .unwrap();
+
+        self.set_storage(address, U256::ZERO, U256::from(owners.len()))
+            .await;
+        for (index, owner) in owners.iter().enumerate() {
+            self.set_storage(address, U256::from(index + 1), address_slot(*owner))
+                .await;
+        }
+    }
+
+    async fn set_storage(&self, address: Address, slot: U256, value: U256) {
+        self.provider
+            .anvil_set_storage_at(address, slot, B256::from(value))
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use alloy::{
+    primitives::{keccak256, Address, Bytes, U256},
+    sol_types::SolCall,
+};
+
+use crate::onchain::{
+    client::IERC20::{allowanceCall, balanceOfCall, transferCall},
+    multicall::{isApprovedForAllCall, GnosisSafeL2::getOwnersCall, Multicall3::aggregate3Call},
+};
+
+// The mock contracts are written in EVM assembly so the tests don't need a Solidity toolchain.
+// Their storage layouts are kept trivial so the tests can seed state with `anvil_setStorageAt`
+
+const ADD: u8 = 0x01;
+const SUB: u8 = 0x03;
+const LT: u8 = 0x10;
+const EQ: u8 = 0x14;
+const ISZERO: u8 = 0x15;
+const AND: u8 = 0x16;
+const SHL: u8 = 0x1b;
+const SHR: u8 = 0x1c;
+const SHA3: u8 = 0x20;
+const CALLER: u8 = 0x33;
+const CALLDATALOAD: u8 = 0x35;
+const CALLDATACOPY: u8 = 0x37;
+const RETURNDATASIZE: u8 = 0x3d;
+const RETURNDATACOPY: u8 = 0x3e;
+const POP: u8 = 0x50;
+const MLOAD: u8 = 0x51;
+const MSTORE: u8 = 0x52;
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const GAS: u8 = 0x5a;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+const DUP1: u8 = 0x80;
+const DUP2: u8 = 0x81;
+const DUP3: u8 = 0x82;
+const DUP4: u8 = 0x83;
+const DUP8: u8 = 0x87;
+const SWAP1: u8 = 0x90;
+const CALL: u8 = 0xf1;
+const RETURN: u8 = 0xf3;
+const REVERT: u8 = 0xfd;
+
+#[derive(Clone, Copy)]
+enum Asm {
+    Op(u8),
+    Push(u32),
+    Label(&'static str),
+    // Pushes the offset of a label, followed by JUMP or JUMPI
+    Target(&'static str),
+}
+
+use Asm::{Label, Op, Push, Target};
+
+fn assemble(program: &[Asm]) -> Bytes {
+    let push_width = |value: u32| (4 - value.leading_zeros() as usize / 8).max(1);
+
+    let mut labels = HashMap::new();
+    let mut offset = 0;
+    for item in program {
+        match item {
+            Op(_) | Label(_) => {
+                if let Label(name) = item {
+                    labels.insert(*name, offset);
+                }
+                offset += 1;
+            }
+            Push(value) => offset += 1 + push_width(*value),
+            Target(_) => offset += 3,
+        }
+    }
+
+    let mut code = vec![];
+    for item in program {
+        match *item {
+            Op(op) => code.push(op),
+            Label(_) => code.push(JUMPDEST),
+            Push(value) => {
+                let width = push_width(value);
+                code.push(PUSH1 + width as u8 - 1);
+                code.extend_from_slice(&value.to_be_bytes()[4 - width..]);
+            }
+            Target(name) => {
+                let offset = labels[name] as u16;
+                code.push(PUSH2);
+                code.extend_from_slice(&offset.to_be_bytes());
+            }
+        }
+    }
+
+    code.into()
+}
+
+fn selector<C: SolCall>() -> Asm {
+    Push(u32::from_be_bytes(C::SELECTOR))
+}
+
+// Loads the selector and jumps to the label of the matching function, reverts otherwise
+fn dispatch(functions: &[(Asm, &'static str)]) -> Vec<Asm> {
+    let mut program = vec![Push(0), Op(CALLDATALOAD), Push(0xe0), Op(SHR)];
+
+    for (selector, label) in functions {
+        program.extend([Op(DUP1), *selector, Op(EQ), Target(label), Op(JUMPI)]);
+    }
+
+    program.extend([Push(0), Op(DUP1), Op(REVERT)]);
+    program
+}
+
+// ERC-20 reads, `transfer` and the ERC-1155 operator approvals in one contract. Balances are stored
+// at the slot equal to the owner address, allowances and operator approvals at
+// keccak256(owner . spender)
+pub fn mock_token() -> Bytes {
+    let mut program = dispatch(&[
+        (selector::<balanceOfCall>(), "balance_of"),
+        (selector::<transferCall>(), "transfer"),
+        (selector::<allowanceCall>(), "allowance"),
+        (selector::<isApprovedForAllCall>(), "allowance"),
+    ]);
+
+    program.extend([
+        Label("balance_of"),
+        Push(4),
+        Op(CALLDATALOAD),
+        Op(SLOAD),
+        Target("return_word"),
+        Op(JUMP),
+        // Reverts when the amount exceeds the caller balance
+        Label("transfer"),
+        Op(CALLER),
+        Op(SLOAD),
+        Push(0x24),
+        Op(CALLDATALOAD),
+        Op(DUP1),
+        Op(DUP3),
+        Op(LT),
+        Target("revert"),
+        Op(JUMPI),
+        Op(DUP1),
+        Op(DUP3),
+        Op(SUB),
+        Op(CALLER),
+        Op(SSTORE),
+        Push(4),
+        Op(CALLDATALOAD),
+        Op(DUP1),
+        Op(SLOAD),
+        Op(DUP3),
+        Op(ADD),
+        Op(SWAP1),
+        Op(SSTORE),
+        Push(1),
+        Target("return_word"),
+        Op(JUMP),
+        Label("allowance"),
+        Push(4),
+        Op(CALLDATALOAD),
+        Push(0),
+        Op(MSTORE),
+        Push(0x24),
+        Op(CALLDATALOAD),
+        Push(0x20),
+        Op(MSTORE),
+        Push(0x40),
+        Push(0),
+        Op(SHA3),
+        Op(SLOAD),
+        Target("return_word"),
+        Op(JUMP),
+        Label("return_word"),
+        Push(0),
+        Op(MSTORE),
+        Push(0x20),
+        Push(0),
+        Op(RETURN),
+        Label("revert"),
+        Push(0),
+        Op(DUP1),
+        Op(REVERT),
+    ]);
+
+    assemble(&program)
+}
+
+// A Safe that only answers `getOwners`, the owner count is stored at slot 0 and the owners at
+// slots 1 to n
+pub fn mock_safe() -> Bytes {
+    let mut program = dispatch(&[(selector::<getOwnersCall>(), "get_owners")]);
+
+    program.extend([
+        Label("get_owners"),
+        Push(0x20),
+        Push(0),
+        Op(MSTORE),
+        Push(0),
+        Op(SLOAD),
+        Op(DUP1),
+        Push(0x20),
+        Op(MSTORE),
+        Push(0),
+        Label("loop"),
+        Op(DUP2),
+        Op(DUP2),
+        Op(LT),
+        Op(ISZERO),
+        Target("done"),
+        Op(JUMPI),
+        Op(DUP1),
+        Push(1),
+        Op(ADD),
+        Op(SLOAD),
+        Op(DUP2),
+        Push(5),
+        Op(SHL),
+        Push(0x40),
+        Op(ADD),
+        Op(MSTORE),
+        Push(1),
+        Op(ADD),
+        Target("loop"),
+        Op(JUMP),
+        Label("done"),
+        Op(POP),
+        Push(5),
+        Op(SHL),
+        Push(0x40),
+        Op(ADD),
+        Push(0),
+        Op(RETURN),
+    ]);
+
+    assemble(&program)
+}
+
+// Multicall3 `aggregate3`. The loop state lives in the first four memory words (index, write
+// pointer, call count, start of the call offsets) and the ABI encoded results are built from 0x80
+pub fn mock_multicall() -> Bytes {
+    let mut program = dispatch(&[(selector::<aggregate3Call>(), "aggregate3")]);
+
+    program.extend([
+        Label("aggregate3"),
+        Push(4),
+        Op(CALLDATALOAD),
+        Push(4),
+        Op(ADD),
+        Op(DUP1),
+        Op(CALLDATALOAD),
+        Push(0x40),
+        Op(MSTORE),
+        Push(0x20),
+        Op(ADD),
+        Push(0x60),
+        Op(MSTORE),
+        Push(0x20),
+        Push(0x80),
+        Op(MSTORE),
+        Push(0x40),
+        Op(MLOAD),
+        Push(0xa0),
+        Op(MSTORE),
+        Push(0x40),
+        Op(MLOAD),
+        Push(5),
+        Op(SHL),
+        Push(0xc0),
+        Op(ADD),
+        Push(0x20),
+        Op(MSTORE),
+        Push(0),
+        Push(0),
+        Op(MSTORE),
+        Label("loop"),
+        Push(0x40),
+        Op(MLOAD),
+        Push(0),
+        Op(MLOAD),
+        Op(LT),
+        Op(ISZERO),
+        Target("done"),
+        Op(JUMPI),
+        // Offset of this result relative to the start of the array
+        Push(0xc0),
+        Push(0x20),
+        Op(MLOAD),
+        Op(SUB),
+        Push(0),
+        Op(MLOAD),
+        Push(5),
+        Op(SHL),
+        Push(0xc0),
+        Op(ADD),
+        Op(MSTORE),
+        // Position of the call tuple and of its calldata
+        Push(0),
+        Op(MLOAD),
+        Push(5),
+        Op(SHL),
+        Push(0x60),
+        Op(MLOAD),
+        Op(ADD),
+        Op(CALLDATALOAD),
+        Push(0x60),
+        Op(MLOAD),
+        Op(ADD),
+        Op(DUP1),
+        Push(0x40),
+        Op(ADD),
+        Op(CALLDATALOAD),
+        Op(DUP2),
+        Op(ADD),
+        Op(DUP1),
+        Op(CALLDATALOAD),
+        Op(DUP1),
+        Op(DUP3),
+        Push(0x20),
+        Op(ADD),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x60),
+        Op(ADD),
+        Op(CALLDATACOPY),
+        Push(0),
+        Push(0),
+        Op(DUP3),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x60),
+        Op(ADD),
+        Push(0),
+        Op(DUP8),
+        Op(CALLDATALOAD),
+        Op(GAS),
+        Op(CALL),
+        Op(DUP1),
+        Push(0x20),
+        Op(MLOAD),
+        Op(MSTORE),
+        // Failed calls revert the batch unless `allowFailure` is set
+        Op(ISZERO),
+        Op(DUP4),
+        Push(0x20),
+        Op(ADD),
+        Op(CALLDATALOAD),
+        Op(ISZERO),
+        Op(AND),
+        Target("revert"),
+        Op(JUMPI),
+        Op(POP),
+        Op(POP),
+        Op(POP),
+        Push(0x40),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x20),
+        Op(ADD),
+        Op(MSTORE),
+        Op(RETURNDATASIZE),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x40),
+        Op(ADD),
+        Op(MSTORE),
+        Op(RETURNDATASIZE),
+        Push(0),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x60),
+        Op(ADD),
+        Op(RETURNDATACOPY),
+        // Zero the padding, the call input was copied to the same place
+        Push(0),
+        Op(RETURNDATASIZE),
+        Push(0x20),
+        Op(MLOAD),
+        Push(0x60),
+        Op(ADD),
+        Op(ADD),
+        Op(MSTORE),
+        Op(RETURNDATASIZE),
+        Push(0x1f),
+        Op(ADD),
+        Push(5),
+        Op(SHR),
+        Push(5),
+        Op(SHL),
+        Push(0x60),
+        Op(ADD),
+        Push(0x20),
+        Op(MLOAD),
+        Op(ADD),
+        Push(0x20),
+        Op(MSTORE),
+        Push(0),
+        Op(MLOAD),
+        Push(1),
+        Op(ADD),
+        Push(0),
+        Op(MSTORE),
+        Target("loop"),
+        Op(JUMP),
+        Label("done"),
+        Push(0x80),
+        Push(0x20),
+        Op(MLOAD),
+        Op(SUB),
+        Push(0x80),
+        Op(RETURN),
+        Label("revert"),
+        Push(0),
+        Op(DUP1),
+        Op(REVERT),
+    ]);
+
+    assemble(&program)
+}
+
+// Storage slot of an allowance or an operator approval in `mock_token`
+pub fn approval_slot(owner: Address, spender: Address) -> U256 {
+    let mut key = [0u8; 64];
+    key[12..32].copy_from_slice(owner.as_slice());
+    key[44..64].copy_from_slice(spender.as_slice());
+
+    keccak256(key).into()
+}
+
+pub fn address_slot(address: Address) -> U256 {
+    U256::from_be_slice(address.as_slice())
+}
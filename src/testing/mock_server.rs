@@ -0,0 +1,875 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+use alloy::{
+    primitives::{Address, PrimitiveSignature, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::{eip712_domain, SolCall, SolStruct, SolValue},
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use base64::{engine::general_purpose::URL_SAFE, prelude::BASE64_STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{
+    config::Config,
+    db::account::Account,
+    onchain::{
+        client::IERC20::{allowanceCall, balanceOfCall},
+        constants::MULTICALL_CONTRACT_ADDRESS,
+        multicall::{isApprovedForAllCall, Multicall3::aggregate3Call, Result as Call3Result},
+    },
+    polymarket::api::{
+        relayer::constants::MULTISEND_CONTRACT_ADDRESS, typedefs::ClobAuth, urls::set_api_urls,
+    },
+    utils::poly::parse_cookies,
+};
+
+const DEFAULT_CONFIG: &str = include_str!("../../data/config.toml");
+
+const CREATE_PROFILE_FIXTURE: &str = include_str!("fixtures/create_profile.json");
+const ORDER_BOOK_FIXTURE: &str = include_str!("fixtures/order_book.json");
+const PLACE_ORDER_FIXTURE: &str = include_str!("fixtures/place_order.json");
+const POSITION_FIXTURE: &str = include_str!("fixtures/position.json");
+
+// Bytecode returned for deployed proxy wallets, only its presence is checked
+const PROXY_WALLET_CODE: &str =
+    "0x608060405273ffffffffffffffffffffffffffffffffffffffff600054167fa619486e";
+
+static MOCK_SERVER: OnceLock<MockServer> = OnceLock::new();
+// Held by the test using the server, the API URLs and the server state are process-wide
+static MOCK_SERVER_LOCK: Mutex<()> = Mutex::new(());
+
+type SharedState = Arc<Mutex<MockState>>;
+
+// Everything is keyed by the signer or the proxy wallet address
+#[derive(Default)]
+struct MockState {
+    auth_nonces: HashSet<String>,
+    sessions: HashMap<String, Address>,
+    profiles: HashMap<Address, Value>,
+    api_keys: HashMap<Address, MockApiKey>,
+    positions: HashMap<Address, Vec<Value>>,
+    unsynced_position_polls: HashMap<Address, usize>,
+    orders: HashMap<Address, Vec<Value>>,
+    relayer_nonces: HashMap<Address, u64>,
+    relayer_transactions: HashMap<Address, Vec<Value>>,
+    deployed_wallets: HashSet<Address>,
+    approved_wallets: HashSet<Address>,
+    usdc_balances: HashMap<Address, U256>,
+    search_events: Vec<Value>,
+}
+
+#[derive(Clone)]
+struct MockApiKey {
+    api_key: String,
+    secret: String,
+    passphrase: String,
+}
+
+// In-process stand-in for the Gamma, CLOB, data, leaderboard and relayer APIs and the Polygon RPC
+pub struct MockServer {
+    base_url: String,
+    state: SharedState,
+}
+
+// Exclusive access to the mock server for the duration of a test
+pub struct MockServerGuard {
+    server: &'static MockServer,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Deref for MockServerGuard {
+    type Target = MockServer;
+
+    fn deref(&self) -> &MockServer {
+        self.server
+    }
+}
+
+// Starts the server on first use. Tests using it run one at a time: each one waits for the
+// previous guard to be dropped, then gets an empty state and the API base URLs pointed at the
+// server
+pub fn mock_server() -> MockServerGuard {
+    // A failed test poisons the lock, the state is reset anyway
+    let lock = MOCK_SERVER_LOCK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let server = MOCK_SERVER.get_or_init(start_mock_server);
+    server.reset();
+
+    MockServerGuard {
+        server,
+        _lock: lock,
+    }
+}
+
+fn start_mock_server() -> MockServer {
+    let state = SharedState::default();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+
+    let server = MockServer {
+        base_url: format!("http://{}", listener.local_addr().unwrap()),
+        state: state.clone(),
+    };
+
+    // Test runtimes are dropped with their test, the server gets a runtime of its own
+    std::thread::spawn(move || {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                axum::serve(listener, router(state)).await.unwrap();
+            })
+    });
+
+    server
+}
+
+pub fn random_account() -> Account {
+    let signer = PrivateKeySigner::random();
+    let private_key = const_hex::encode_prefixed(signer.to_bytes());
+
+    Account::new(&private_key, "encrypted", None, None)
+}
+
+impl MockServer {
+    // The default config with every URL pointing at the mock server and no delays
+    pub fn config(&self) -> Config {
+        let base_url = &self.base_url;
+        let urls = [
+            ("POLYGON_RPC_URL", "rpc"),
+            ("GAMMA_API_URL", "gamma"),
+            ("CLOB_API_URL", "clob"),
+            ("DATA_API_URL", "data"),
+            ("LB_API_URL", "lb"),
+            ("RELAYER_API_URL", "relayer"),
+        ]
+        .map(|(key, path)| (key, toml::Value::String(format!("{base_url}/{path}"))));
+        let delays = [
+            "REGISTRATION_SLEEP_RANGE",
+            "SELL_DELAY_RANGE",
+            "WITHDRAW_DELAY_RANGE",
+            "LIMIT_ORDER_DELAY_RANGE",
+        ]
+        .map(|key| (key, Config::parse_override_value("[0, 0]")));
+        let overrides = urls
+            .into_iter()
+            .chain(delays)
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<Vec<_>>();
+
+        Config::from_toml_str(DEFAULT_CONFIG, None, &overrides).unwrap()
+    }
+
+    fn reset(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = MockState::default();
+        set_api_urls(self.config().api_urls());
+    }
+
+    // Signs the account in without going through the nonce and login endpoints
+    pub fn sign_in(&self, account: &mut Account) {
+        let session = format!("session-{}", account.signer().address());
+        self.state
+            .lock()
+            .unwrap()
+            .sessions
+            .insert(session.clone(), account.signer().address());
+
+        account.set_polymarket_nonce("nonce");
+        account.set_polymarket_session(&session);
+    }
+
+    pub fn add_position(&self, proxy_wallet: Address, token_id: &str, size: f64) {
+        let mut position = serde_json::from_str::<Value>(POSITION_FIXTURE).unwrap();
+        position["proxyWallet"] = json!(proxy_wallet);
+        position["asset"] = json!(token_id);
+        position["size"] = json!(size);
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .positions
+            .entry(proxy_wallet)
+            .or_default()
+            .push(position);
+    }
+
+    // A position in a resolved market, the data API reports it as redeemable
+    pub fn add_resolved_position(&self, proxy_wallet: Address, token_id: &str, size: f64) {
+        self.add_position(proxy_wallet, token_id, size);
+
+        let mut state = self.state.lock().unwrap();
+        if let Some(position) = state
+            .positions
+            .get_mut(&proxy_wallet)
+            .and_then(|positions| positions.last_mut())
+        {
+            position["redeemable"] = json!(true);
+        }
+    }
+
+    // The data API lags behind the exchange, the next `polls` position requests come back empty
+    pub fn delay_positions(&self, proxy_wallet: Address, polls: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.unsynced_position_polls.insert(proxy_wallet, polls);
+    }
+
+    pub fn positions(&self, proxy_wallet: Address) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+        state
+            .positions
+            .get(&proxy_wallet)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_usdc_balance(&self, wallet: Address, balance: U256) {
+        let mut state = self.state.lock().unwrap();
+        state.usdc_balances.insert(wallet, balance);
+    }
+
+    pub fn has_profile(&self, address: Address) -> bool {
+        self.state.lock().unwrap().profiles.contains_key(&address)
+    }
+
+    pub fn is_deployed(&self, proxy_wallet: Address) -> bool {
+        let state = self.state.lock().unwrap();
+        state.deployed_wallets.contains(&proxy_wallet)
+    }
+
+    pub fn is_approved(&self, proxy_wallet: Address) -> bool {
+        let state = self.state.lock().unwrap();
+        state.approved_wallets.contains(&proxy_wallet)
+    }
+
+    // Order requests accepted from the maker, as they were posted
+    pub fn orders(&self, maker: Address) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+        state.orders.get(&maker).cloned().unwrap_or_default()
+    }
+
+    // Returned by the public search in order, whatever the query text
+    pub fn add_search_event(&self, event: Value) {
+        self.state.lock().unwrap().search_events.push(event);
+    }
+
+    // Relayer request bodies submitted for the proxy wallet
+    pub fn relayer_transactions(&self, proxy_wallet: Address) -> Vec<Value> {
+        let state = self.state.lock().unwrap();
+        state
+            .relayer_transactions
+            .get(&proxy_wallet)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+fn router(state: SharedState) -> Router {
+    let gamma = Router::new()
+        .route("/nonce", get(gamma_nonce))
+        .route("/login", get(gamma_login))
+        .route("/users", get(gamma_users))
+        .route("/profiles", post(gamma_create_profile))
+        .route("/profiles/:id", put(gamma_update_profile))
+        .route("/preferences/:id", put(gamma_update_preferences))
+        .route("/public-search", get(gamma_public_search));
+
+    let clob = Router::new()
+        .route("/auth/derive-api-key", get(clob_derive_api_key))
+        .route("/auth/api-key", post(clob_create_api_key))
+        .route("/tick-size", get(clob_tick_size))
+        .route("/neg-risk", get(clob_neg_risk))
+        .route("/book", get(clob_book))
+        .route("/order", post(clob_place_order));
+
+    let data = Router::new().route("/positions", get(data_positions));
+
+    let relayer = Router::new()
+        .route("/nonce", get(relayer_nonce))
+        .route("/submit", post(relayer_submit))
+        .route("/transaction", get(relayer_transaction));
+
+    Router::new()
+        .nest("/gamma", gamma)
+        .nest("/clob", clob)
+        .nest("/data", data)
+        .nest("/relayer", relayer)
+        .route("/rpc", post(rpc))
+        .with_state(state)
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn random_token(len: usize) -> String {
+    let bytes = (0..len).map(|_| thread_rng().gen()).collect::<Vec<u8>>();
+    const_hex::encode(bytes)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn session_address(state: &MockState, headers: &HeaderMap) -> Option<Address> {
+    let cookies = parse_cookies(header(headers, "cookie")?);
+    state
+        .sessions
+        .get(cookies.get("polymarketsession")?)
+        .copied()
+}
+
+// Sign-in with Ethereum, the payload and the signature are joined with `:::` and base64 encoded
+fn verify_login(state: &MockState, authorization: &str) -> Option<Address> {
+    let decoded = String::from_utf8(BASE64_STANDARD.decode(authorization).ok()?).ok()?;
+    let (payload, signature) = decoded.split_once(":::")?;
+    let payload = serde_json::from_str::<Value>(payload).ok()?;
+    let field = |name: &str| payload[name].as_str().map(str::to_string);
+
+    if !state.auth_nonces.contains(&field("nonce")?) {
+        return None;
+    }
+
+    let address = Address::from_str(&field("address")?).ok()?;
+    let message = [
+        format!(
+            "{} wants you to sign in with your Ethereum account:",
+            field("domain")?
+        ),
+        address.to_string(),
+        String::new(),
+        field("statement")?,
+        String::new(),
+        format!("URI: {}", field("uri")?),
+        format!("Version: {}", field("version")?),
+        format!("Chain ID: {}", payload["chainId"].as_u64()?),
+        format!("Nonce: {}", field("nonce")?),
+        format!("Issued At: {}", field("issuedAt")?),
+        format!("Expiration Time: {}", field("expirationTime")?),
+    ]
+    .join("\n");
+
+    let signer = PrimitiveSignature::from_str(signature)
+        .ok()?
+        .recover_address_from_msg(message)
+        .ok()?;
+
+    (signer == address).then_some(address)
+}
+
+// Level one CLOB auth, an EIP-712 `ClobAuth` signature by the wallet
+fn verify_l1_auth(headers: &HeaderMap) -> Option<Address> {
+    let address = Address::from_str(header(headers, "poly_address")?).ok()?;
+    let auth = ClobAuth {
+        address,
+        timestamp: header(headers, "poly_timestamp")?.to_string(),
+        nonce: U256::from_str(header(headers, "poly_nonce")?).ok()?,
+        message: "This message attests that I control the given wallet".to_string(),
+    };
+    let domain = eip712_domain! {
+        name: "ClobAuthDomain",
+        version: "1",
+        chain_id: 137,
+    };
+
+    let signer = PrimitiveSignature::from_str(header(headers, "poly_signature")?)
+        .ok()?
+        .recover_address_from_prehash(&auth.eip712_signing_hash(&domain))
+        .ok()?;
+
+    (signer == address).then_some(address)
+}
+
+// Level two CLOB auth, an HMAC of the request keyed with the API secret
+fn verify_l2_auth(
+    state: &MockState,
+    headers: &HeaderMap,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> Option<MockApiKey> {
+    let address = Address::from_str(header(headers, "poly_address")?).ok()?;
+    let api_key = state.api_keys.get(&address)?;
+
+    if header(headers, "poly_api_key")? != api_key.api_key
+        || header(headers, "poly_passphrase")? != api_key.passphrase
+    {
+        return None;
+    }
+
+    let message = format!("{}{method}{path}{body}", header(headers, "poly_timestamp")?);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&URL_SAFE.decode(&api_key.secret).ok()?).ok()?;
+    mac.update(message.as_bytes());
+
+    (URL_SAFE.encode(mac.finalize().into_bytes()) == header(headers, "poly_signature")?)
+        .then(|| api_key.clone())
+}
+
+async fn gamma_nonce(State(state): State<SharedState>) -> Response {
+    let nonce = random_token(16);
+    state.lock().unwrap().auth_nonces.insert(nonce.clone());
+
+    (
+        [(
+            SET_COOKIE,
+            format!("polymarketnonce={nonce}; Path=/; HttpOnly"),
+        )],
+        Json(json!({ "nonce": nonce })),
+    )
+        .into_response()
+}
+
+async fn gamma_login(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(address) =
+        header(&headers, "authorization").and_then(|value| verify_login(&state, value))
+    else {
+        return error(StatusCode::UNAUTHORIZED, "invalid signature");
+    };
+
+    let session = format!("session-{}", random_token(16));
+    state.sessions.insert(session.clone(), address);
+
+    (
+        [(
+            SET_COOKIE,
+            format!("polymarketsession={session}; Path=/; HttpOnly"),
+        )],
+        Json(json!({ "type": "metamask", "address": address })),
+    )
+        .into_response()
+}
+
+async fn gamma_users(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let state = state.lock().unwrap();
+
+    let Some(address) = session_address(&state, &headers) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+
+    let users = match state.profiles.get(&address) {
+        Some(profile) => profile["users"].clone(),
+        None => json!([]),
+    };
+
+    Json(users).into_response()
+}
+
+async fn gamma_create_profile(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(address) = session_address(&state, &headers) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+
+    let mut profile = serde_json::from_str::<Value>(CREATE_PROFILE_FIXTURE).unwrap();
+    for key in ["name", "pseudonym", "proxyWallet"] {
+        profile[key] = body[key].clone();
+    }
+    profile["users"][0]["address"] = json!(address);
+    profile["users"][0]["proxyWallet"] = body["proxyWallet"].clone();
+    profile["users"][0]["username"] = body["name"].clone();
+
+    state.profiles.insert(address, profile.clone());
+
+    Json(profile).into_response()
+}
+
+async fn gamma_update_profile(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(address) = session_address(&state, &headers) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+
+    match state.profiles.get_mut(&address) {
+        Some(profile) if profile["id"] == id => {
+            profile["name"] = body["name"].clone();
+            profile["users"][0]["username"] = body["name"].clone();
+            Json(profile.clone()).into_response()
+        }
+        _ => error(StatusCode::NOT_FOUND, "profile not found"),
+    }
+}
+
+async fn gamma_update_preferences(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(address) = session_address(&state, &headers) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+
+    match state.profiles.get_mut(&address) {
+        Some(profile) if profile["users"][0]["preferences"][0]["id"] == id => {
+            let preferences = &mut profile["users"][0]["preferences"][0];
+            preferences["emailNotificationPreferences"] =
+                body["emailNotificationPreferences"].clone();
+            preferences["marketInterests"] = body["marketInterests"].clone();
+            Json(preferences.clone()).into_response()
+        }
+        _ => error(StatusCode::NOT_FOUND, "preferences not found"),
+    }
+}
+
+async fn gamma_public_search(
+    State(state): State<SharedState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let arg = |name: &str| {
+        query
+            .get(name)
+            .and_then(|value| value.parse::<usize>().ok())
+    };
+    let (Some(limit), Some(page)) = (arg("limit_per_type"), arg("page")) else {
+        return error(StatusCode::BAD_REQUEST, "invalid pagination");
+    };
+
+    let state = state.lock().unwrap();
+    let events = state
+        .search_events
+        .iter()
+        .skip(page.saturating_sub(1) * limit)
+        .take(limit)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Json(json!({ "events": events })).into_response()
+}
+
+fn api_key_response(api_key: &MockApiKey) -> Response {
+    Json(json!({
+        "apiKey": api_key.api_key,
+        "secret": api_key.secret,
+        "passphrase": api_key.passphrase,
+    }))
+    .into_response()
+}
+
+async fn clob_derive_api_key(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let Some(address) = verify_l1_auth(&headers) else {
+        return error(StatusCode::UNAUTHORIZED, "Unauthorized/Invalid api key");
+    };
+
+    match state.lock().unwrap().api_keys.get(&address) {
+        Some(api_key) => api_key_response(api_key),
+        None => error(StatusCode::BAD_REQUEST, "Could not derive api key!"),
+    }
+}
+
+async fn clob_create_api_key(State(state): State<SharedState>, headers: HeaderMap) -> Response {
+    let Some(address) = verify_l1_auth(&headers) else {
+        return error(StatusCode::UNAUTHORIZED, "Unauthorized/Invalid api key");
+    };
+
+    let secret = (0..32).map(|_| thread_rng().gen()).collect::<Vec<u8>>();
+    let api_key = MockApiKey {
+        api_key: uuid::Uuid::new_v4().to_string(),
+        secret: URL_SAFE.encode(secret),
+        passphrase: random_token(32),
+    };
+
+    let mut state = state.lock().unwrap();
+    let api_key = state.api_keys.entry(address).or_insert(api_key);
+
+    api_key_response(api_key)
+}
+
+async fn clob_tick_size() -> Response {
+    Json(json!({ "minimum_tick_size": 0.01 })).into_response()
+}
+
+async fn clob_neg_risk() -> Response {
+    Json(json!({ "neg_risk": false })).into_response()
+}
+
+async fn clob_book(Query(query): Query<HashMap<String, String>>) -> Response {
+    let mut book = serde_json::from_str::<Value>(ORDER_BOOK_FIXTURE).unwrap();
+    book["asset_id"] = json!(query.get("token_id"));
+
+    Json(book).into_response()
+}
+
+async fn clob_place_order(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(api_key) = verify_l2_auth(&state, &headers, "POST", "/order", &body) else {
+        return error(StatusCode::UNAUTHORIZED, "Unauthorized/Invalid api key");
+    };
+
+    let request = serde_json::from_str::<Value>(&body).unwrap_or_default();
+    let order = &request["order"];
+
+    if request["owner"] != api_key.api_key {
+        return error(
+            StatusCode::BAD_REQUEST,
+            "the order owner has to be the owner of the API KEY",
+        );
+    }
+
+    let Some(maker) = order["maker"]
+        .as_str()
+        .and_then(|maker| maker.parse::<Address>().ok())
+    else {
+        return error(StatusCode::BAD_REQUEST, "invalid order payload");
+    };
+
+    // Sells are filled right away and close the position
+    if order["side"] == "SELL" {
+        if let Some(positions) = state.positions.get_mut(&maker) {
+            positions.retain(|position| position["asset"] != order["tokenId"]);
+        }
+    }
+
+    let order_id = format!("0x{}", random_token(32));
+    let mut response = serde_json::from_str::<Value>(PLACE_ORDER_FIXTURE).unwrap();
+    response["orderID"] = json!(order_id);
+    response["makingAmount"] = order["makerAmount"].clone();
+    response["takingAmount"] = order["takerAmount"].clone();
+    if order["side"] == "SELL" {
+        response["status"] = json!("matched");
+        response["transactionsHashes"] = json!([format!("0x{}", random_token(32))]);
+    }
+
+    state.orders.entry(maker).or_default().push(request);
+
+    Json(response).into_response()
+}
+
+async fn data_positions(
+    State(state): State<SharedState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let Some(user) = query
+        .get("user")
+        .and_then(|user| user.parse::<Address>().ok())
+    else {
+        return error(StatusCode::BAD_REQUEST, "invalid user");
+    };
+
+    let mut state = state.lock().unwrap();
+
+    if let Some(polls) = state.unsynced_position_polls.get_mut(&user) {
+        if *polls > 0 {
+            *polls -= 1;
+            return Json(json!([])).into_response();
+        }
+    }
+
+    Json(state.positions.get(&user).cloned().unwrap_or_default()).into_response()
+}
+
+async fn relayer_nonce(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let state = state.lock().unwrap();
+
+    if session_address(&state, &headers).is_none() {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    }
+
+    let Some(address) = query
+        .get("address")
+        .and_then(|address| address.parse::<Address>().ok())
+    else {
+        return error(StatusCode::BAD_REQUEST, "invalid address");
+    };
+
+    let nonce = state.relayer_nonces.get(&address).copied().unwrap_or(0);
+
+    Json(json!({ "nonce": nonce.to_string() })).into_response()
+}
+
+async fn relayer_submit(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let mut state = state.lock().unwrap();
+
+    let Some(address) = session_address(&state, &headers) else {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    };
+
+    let proxy_wallet = body["proxyWallet"]
+        .as_str()
+        .and_then(|wallet| wallet.parse().ok());
+    let to = body["to"]
+        .as_str()
+        .and_then(|to| to.parse::<Address>().ok());
+    let (Some(proxy_wallet), Some(to)) = (proxy_wallet, to) else {
+        return error(StatusCode::BAD_REQUEST, "invalid transaction");
+    };
+
+    match body["type"].as_str() {
+        Some("SAFE-CREATE") => {
+            state.deployed_wallets.insert(proxy_wallet);
+        }
+        Some("SAFE") => {
+            *state.relayer_nonces.entry(address).or_default() += 1;
+
+            // The only multisend the bot submits is the approval bundle
+            if to == MULTISEND_CONTRACT_ADDRESS {
+                state.approved_wallets.insert(proxy_wallet);
+            }
+        }
+        _ => return error(StatusCode::BAD_REQUEST, "unknown transaction type"),
+    }
+
+    state
+        .relayer_transactions
+        .entry(proxy_wallet)
+        .or_default()
+        .push(body);
+
+    Json(json!({
+        "transactionID": uuid::Uuid::new_v4().to_string(),
+        "transactionHash": format!("0x{}", random_token(32)),
+        "state": "STATE_NEW",
+    }))
+    .into_response()
+}
+
+// Relayer transactions are mined by the time they are polled
+async fn relayer_transaction(Query(query): Query<HashMap<String, String>>) -> Response {
+    if !query.contains_key("id") {
+        return error(StatusCode::BAD_REQUEST, "missing id");
+    }
+
+    Json(json!([{
+        "state": "STATE_MINED",
+        "transactionHash": format!("0x{}", random_token(32)),
+    }]))
+    .into_response()
+}
+
+async fn rpc(State(state): State<SharedState>, Json(request): Json<Value>) -> Response {
+    let state = state.lock().unwrap();
+    let params = &request["params"];
+
+    let result = match request["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => Some(json!("0x89")),
+        "eth_blockNumber" => Some(json!("0x3a1b2c4")),
+        "eth_getCode" => params[0]
+            .as_str()
+            .and_then(|address| address.parse::<Address>().ok())
+            .map(|address| match state.deployed_wallets.contains(&address) {
+                true => json!(PROXY_WALLET_CODE),
+                false => json!("0x"),
+            }),
+        "eth_call" => {
+            let call = &params[0];
+            let to = call["to"].as_str().and_then(|to| to.parse().ok());
+            let input = call["input"]
+                .as_str()
+                .or(call["data"].as_str())
+                .and_then(|input| const_hex::decode(input).ok());
+
+            to.zip(input)
+                .and_then(|(to, input)| eth_call(&state, to, &input))
+                .map(|output| json!(const_hex::encode_prefixed(output)))
+        }
+        _ => None,
+    };
+
+    let response = match result {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32601, "message": "unsupported request" },
+        }),
+    };
+
+    Json(response).into_response()
+}
+
+// Answers the token reads the bot makes, USDC.e balances and the exchange approvals
+fn eth_call(state: &MockState, to: Address, input: &[u8]) -> Option<Vec<u8>> {
+    if to == MULTICALL_CONTRACT_ADDRESS {
+        let calls = aggregate3Call::abi_decode(input, true).ok()?.calls;
+        let results = calls
+            .into_iter()
+            .map(|call| {
+                eth_call(state, call.target, &call.callData).map(|data| Call3Result {
+                    success: true,
+                    returnData: data.into(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        return Some(aggregate3Call::abi_encode_returns(&(results,)));
+    }
+
+    let selector = input.get(..4)?;
+
+    if selector == balanceOfCall::SELECTOR {
+        let owner = balanceOfCall::abi_decode(input, true).ok()?.account;
+        let balance = state.usdc_balances.get(&owner).copied().unwrap_or_default();
+        Some(balance.abi_encode())
+    } else if selector == allowanceCall::SELECTOR {
+        let owner = allowanceCall::abi_decode(input, true).ok()?.owner;
+        let allowance = match state.approved_wallets.contains(&owner) {
+            true => U256::MAX,
+            false => U256::ZERO,
+        };
+        Some(allowance.abi_encode())
+    } else if selector == isApprovedForAllCall::SELECTOR {
+        let owner = isApprovedForAllCall::abi_decode(input, true).ok()?.owner;
+        Some(state.approved_wallets.contains(&owner).abi_encode())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_is_reset_between_tests() {
+        let proxy_wallet = random_account().get_proxy_address();
+
+        let mock = mock_server();
+        mock.add_position(proxy_wallet, "1", 10.0);
+        assert_eq!(mock.positions(proxy_wallet).len(), 1);
+        drop(mock);
+
+        assert!(mock_server().positions(proxy_wallet).is_empty());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod anvil;
+pub mod contracts;
+pub mod mock_server;
+pub mod sink_server;
+pub mod stream_server;
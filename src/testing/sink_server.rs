@@ -0,0 +1,84 @@
+use axum::{extract::Request, Json, Router};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::mpsc,
+};
+
+// Accepts JSON posted to any path and reports it with the path, every request succeeds
+pub async fn http_sink() -> (String, mpsc::UnboundedReceiver<(String, Value)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+
+    let router = Router::new().fallback(move |request: Request| {
+        let requests = requests.clone();
+
+        async move {
+            let path = request.uri().path().to_string();
+            let body = axum::body::to_bytes(request.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            requests
+                .send((path, serde_json::from_slice(&body).unwrap()))
+                .unwrap();
+
+            Json(json!({ "ok": true }))
+        }
+    });
+
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+    (url, received)
+}
+
+// A plain text SMTP server that accepts every message and reports its DATA section
+pub async fn smtp_sink() -> (u16, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (messages, received) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let messages = messages.clone();
+
+            tokio::spawn(async move {
+                writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_uppercase();
+
+                    let reply: &[u8] = if command.starts_with("EHLO") {
+                        b"250-localhost\r\n250 8BITMIME\r\n"
+                    } else if command.starts_with("DATA") {
+                        writer.write_all(b"354 End data with .\r\n").await.unwrap();
+
+                        let mut data = vec![];
+                        while let Ok(Some(line)) = lines.next_line().await {
+                            if line == "." {
+                                break;
+                            }
+                            data.push(line);
+                        }
+                        messages.send(data.join("\n")).unwrap();
+
+                        b"250 Queued\r\n"
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+
+                    writer.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (port, received)
+}
@@ -0,0 +1,41 @@
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+// Serves one scripted session per connection and reports every subscription message. Every
+// session but the last one is closed by the server after its events are sent
+pub async fn stream_server(sessions: Vec<Vec<Value>>) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (subscriptions, received) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        let session_count = sessions.len();
+
+        for (index, events) in sessions.into_iter().enumerate() {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = accept_async(stream).await.unwrap();
+
+            let Some(Ok(Message::Text(subscription))) = ws.next().await else {
+                panic!("expected a subscription");
+            };
+            subscriptions
+                .send(serde_json::from_str(&subscription).unwrap())
+                .unwrap();
+
+            for event in events {
+                ws.send(Message::Text(event.to_string())).await.unwrap();
+            }
+
+            if index + 1 < session_count {
+                ws.close(None).await.unwrap();
+            } else {
+                // Keeps reading so pings are answered by the library
+                while ws.next().await.is_some() {}
+            }
+        }
+    });
+
+    (url, received)
+}
@@ -0,0 +1,69 @@
+use alloy::primitives::U256;
+use rust_decimal::Decimal;
+
+// Integer token units of `amount`. Fails instead of rounding when the amount is negative or has
+// more decimal places than the token supports
+pub fn to_units(amount: Decimal, decimals: u32) -> eyre::Result<U256> {
+    if amount.is_sign_negative() && !amount.is_zero() {
+        eyre::bail!("Negative amount: {amount}");
+    }
+
+    let amount = amount.normalize();
+    if amount.scale() > decimals {
+        eyre::bail!("{amount} has more than {decimals} decimal places");
+    }
+
+    let mantissa = U256::from(amount.mantissa().unsigned_abs());
+    let factor = U256::from(10).pow(U256::from(decimals - amount.scale()));
+
+    Ok(mantissa * factor)
+}
+
+pub fn from_units(value: U256, decimals: u32) -> eyre::Result<Decimal> {
+    let mantissa =
+        i128::try_from(value).map_err(|_| eyre::eyre!("{value} does not fit into a decimal"))?;
+
+    let amount = Decimal::try_from_i128_with_scale(mantissa, decimals)?;
+
+    Ok(amount.normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn test_to_units() {
+        assert_eq!(
+            to_units(dec!(12.345678), 6).unwrap(),
+            U256::from(12_345_678)
+        );
+        assert_eq!(to_units(dec!(0.10), 6).unwrap(), U256::from(100_000));
+        assert_eq!(to_units(dec!(7), 0).unwrap(), U256::from(7));
+        assert_eq!(to_units(dec!(-0), 6).unwrap(), U256::ZERO);
+
+        assert!(to_units(dec!(0.0000001), 6).is_err());
+        assert!(to_units(dec!(-1), 6).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_units_round_trip(units in any::<u64>(), decimals in 0u32..=18) {
+            let amount = from_units(U256::from(units), decimals).unwrap();
+
+            prop_assert_eq!(to_units(amount, decimals).unwrap(), U256::from(units));
+        }
+
+        #[test]
+        fn test_decimal_round_trip(mantissa in 0i64..i64::MAX, scale in 0u32..=6) {
+            let amount = Decimal::new(mantissa, scale);
+            let units = to_units(amount, 6).unwrap();
+
+            prop_assert_eq!(units, U256::from(mantissa) * U256::from(10u64.pow(6 - scale)));
+            prop_assert_eq!(from_units(units, 6).unwrap(), amount);
+        }
+    }
+}
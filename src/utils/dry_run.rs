@@ -0,0 +1,65 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use tabled::{settings::Style, Table, Tabled};
+
+// Stands in for relayer transaction ids and hashes that were never submitted
+pub const DRY_RUN_ID: &str = "dry-run";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ACTIONS: Mutex<Vec<DryRunAction>> = Mutex::new(vec![]);
+
+#[derive(Tabled, Clone)]
+struct DryRunAction {
+    #[tabled(rename = "Account")]
+    account: String,
+
+    #[tabled(rename = "Action")]
+    action: String,
+
+    #[tabled(rename = "Details")]
+    details: String,
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// Records an action that was skipped, it is listed by `display_summary`
+pub fn record(account: impl ToString, action: &str, details: impl ToString) {
+    let action = DryRunAction {
+        account: account.to_string(),
+        action: action.to_string(),
+        details: details.to_string(),
+    };
+
+    tracing::info!(
+        "[DRY RUN] {} | {}: {}",
+        action.account,
+        action.action,
+        action.details
+    );
+
+    ACTIONS.lock().unwrap().push(action);
+}
+
+// Prints and clears the actions recorded since the last summary
+pub fn display_summary() {
+    let actions = std::mem::take(&mut *ACTIONS.lock().unwrap());
+
+    if actions.is_empty() {
+        tracing::info!("[DRY RUN] Nothing would have been sent");
+        return;
+    }
+
+    let mut table = Table::new(&actions);
+    let table = table.with(Style::modern_rounded());
+
+    println!("Dry run, nothing was sent:\n{table}");
+}
@@ -1,3 +1,6 @@
+use std::{fs::File, io::Write, path::Path};
+
+use serde::Serialize;
 use tokio::io::AsyncBufReadExt;
 
 pub async fn read_file_lines(path: &str) -> eyre::Result<Vec<String>> {
@@ -11,3 +14,62 @@ pub async fn read_file_lines(path: &str) -> eyre::Result<Vec<String>> {
 
     Ok(lines_vec)
 }
+
+// Writes to a temp file next to `path`, syncs it and renames it over the target so readers never see a partial file
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> eyre::Result<()> {
+    let path = path.as_ref();
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| eyre::eyre!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    let tmp_path = dir.join(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let result = (|| -> std::io::Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+
+        // Persist the rename itself, directories can't be opened for syncing on every platform
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+
+    result.map_err(|e| eyre::eyre!("Failed to write {}: {e}", path.display()))
+}
+
+pub fn write_json_atomic<T: Serialize>(path: impl AsRef<Path>, value: &T) -> eyre::Result<()> {
+    write_atomic(path, &serde_json::to_vec_pretty(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = std::env::temp_dir().join(format!("write-atomic-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("db.json");
+
+        write_json_atomic(&path, &vec![1, 2, 3]).unwrap();
+        write_json_atomic(&path, &vec![4]).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<u8>>(&contents).unwrap(), vec![4]);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use fake::faker::name::en::Name;
 use fake::{faker::internet::en::Username, Fake};
 use indicatif::{ProgressBar, ProgressStyle};
@@ -6,9 +6,16 @@ use rand::{thread_rng, Rng};
 use reqwest::Method;
 use term_size::dimensions;
 
-use super::fetch::{send_http_request_with_retries, RequestParams};
+use super::{
+    dry_run,
+    fetch::{send_http_request_with_retries, RequestParams},
+};
 
 pub async fn pretty_sleep(sleep_range: [u64; 2]) {
+    if dry_run::is_enabled() {
+        return;
+    }
+
     let random_sleep_duration_secs = random_in_range(sleep_range);
 
     let pb = ProgressBar::new(random_sleep_duration_secs);
@@ -109,6 +116,13 @@ pub fn get_timestamp_with_offset(hours_to_add: i64) -> (String, String) {
     )
 }
 
+pub fn format_timestamp(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
 pub async fn swap_ip_address(link: &str) -> eyre::Result<()> {
     let request_params = RequestParams {
         url: link,
@@ -1,6 +1,9 @@
 pub mod constants;
+pub mod decimal;
+pub mod dry_run;
 pub mod fetch;
 pub mod files;
 pub mod logger;
 pub mod misc;
+pub mod notifier;
 pub mod poly;
@@ -0,0 +1,119 @@
+use futures_util::future::BoxFuture;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{read_secret, Notification, Notifier};
+use crate::utils::fetch::{send_http_request_with_retries, RequestParams};
+
+const DEFAULT_BOT_API_URL: &str = "https://api.telegram.org";
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+pub struct BotConfig {
+    #[serde(default = "default_bot_api_url")]
+    pub url: String,
+    pub token_env: String,
+    pub chat_id: String,
+}
+
+fn default_bot_api_url() -> String {
+    DEFAULT_BOT_API_URL.to_string()
+}
+
+// Sends a text message to one chat through a Telegram compatible bot API
+pub struct BotNotifier {
+    send_message_url: String,
+    chat_id: String,
+}
+
+impl BotNotifier {
+    pub fn new(config: &BotConfig) -> eyre::Result<Self> {
+        let token = read_secret(&config.token_env)?;
+
+        Ok(Self {
+            send_message_url: format!(
+                "{}/bot{token}/sendMessage",
+                config.url.trim_end_matches('/')
+            ),
+            chat_id: config.chat_id.clone(),
+        })
+    }
+}
+
+impl Notifier for BotNotifier {
+    fn name(&self) -> &str {
+        "bot"
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            let body = json!({
+                "chat_id": self.chat_id,
+                "text": format!("{}\n{}", notification.title, notification.body),
+                "disable_web_page_preview": true,
+            });
+
+            let request_params = RequestParams {
+                url: &self.send_message_url,
+                method: Method::POST,
+                body: Some(body),
+                query_args: None,
+            };
+
+            let response = send_http_request_with_retries::<Value>(
+                &request_params,
+                None,
+                None,
+                Some(3),
+                None,
+                |_| true,
+            )
+            .await?;
+
+            // The API answers 200 with `ok: false` for some rejected messages
+            match response.body {
+                Some(body) if body["ok"] == false => {
+                    eyre::bail!("Message rejected: {}", body["description"])
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::sink_server::http_sink, utils::notifier::NotificationKind};
+
+    #[tokio::test]
+    async fn test_bot_sends_message_to_chat() {
+        let (url, mut requests) = http_sink().await;
+        std::env::set_var("BOT_NOTIFIER_TEST_TOKEN", "123456:ABC-DEF");
+
+        let notifier = BotNotifier::new(&BotConfig {
+            url: format!("{url}/"),
+            token_env: "BOT_NOTIFIER_TEST_TOKEN".to_string(),
+            chat_id: "-100123".to_string(),
+        })
+        .unwrap();
+
+        notifier
+            .notify(&Notification {
+                kind: NotificationKind::ModuleCompleted,
+                title: "Withdraw finished".to_string(),
+                body: "12 accounts withdrawn, 1 failed".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let (path, body) = requests.recv().await.unwrap();
+        assert_eq!(path, "/bot123456:ABC-DEF/sendMessage");
+        assert_eq!(body["chat_id"], "-100123");
+        assert_eq!(
+            body["text"],
+            "Withdraw finished\n12 accounts withdrawn, 1 failed"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::dry_run;
+
+use bot::{BotConfig, BotNotifier};
+use smtp::{SmtpConfig, SmtpNotifier};
+use webhook::{WebhookConfig, WebhookNotifier};
+
+pub mod bot;
+pub mod smtp;
+pub mod webhook;
+
+// A sink that doesn't answer in time is skipped so it can't hold up the module reporting to it
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(15);
+
+static NOTIFIERS: RwLock<Option<Arc<Notifiers>>> = RwLock::new(None);
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationKind {
+    ModuleCompleted,
+    ModuleFailed,
+    AccountFailed,
+    TransactionConfirmed,
+    LowBalance,
+    WatchAlert,
+}
+
+impl NotificationKind {
+    pub const ALL: [Self; 6] = [
+        Self::ModuleCompleted,
+        Self::ModuleFailed,
+        Self::AccountFailed,
+        Self::TransactionConfirmed,
+        Self::LowBalance,
+        Self::WatchAlert,
+    ];
+}
+
+// A message for whoever runs the bot, every sink formats it for its own channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, eyre::Result<()>>;
+}
+
+// One `NOTIFIERS` entry, the `KIND` key selects the sink
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "KIND", rename_all = "lowercase")]
+pub enum NotifierConfig {
+    Webhook(WebhookConfig),
+    Bot(BotConfig),
+    Smtp(SmtpConfig),
+}
+
+impl NotifierConfig {
+    pub fn build(&self) -> eyre::Result<Box<dyn Notifier>> {
+        Ok(match self {
+            NotifierConfig::Webhook(config) => Box::new(WebhookNotifier::new(config)),
+            NotifierConfig::Bot(config) => Box::new(BotNotifier::new(config)?),
+            NotifierConfig::Smtp(config) => Box::new(SmtpNotifier::new(config)?),
+        })
+    }
+}
+
+struct Notifiers {
+    sinks: Vec<Box<dyn Notifier>>,
+    kinds: Vec<NotificationKind>,
+}
+
+// Secrets are read from the environment here, a missing one fails the run before any module starts
+pub fn init_notifiers(configs: &[NotifierConfig], kinds: &[NotificationKind]) -> eyre::Result<()> {
+    let sinks = configs
+        .iter()
+        .map(NotifierConfig::build)
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    *NOTIFIERS.write().unwrap() = Some(Arc::new(Notifiers {
+        sinks,
+        kinds: kinds.to_vec(),
+    }));
+
+    Ok(())
+}
+
+// Sends to every configured sink if the kind is enabled in `NOTIFY_ON`
+pub async fn notify(kind: NotificationKind, title: impl Into<String>, body: impl Into<String>) {
+    let Some(notifiers) = NOTIFIERS.read().unwrap().clone() else {
+        return;
+    };
+
+    if notifiers.sinks.is_empty() || !notifiers.kinds.contains(&kind) {
+        return;
+    }
+
+    let notification = Notification {
+        kind,
+        title: title.into(),
+        body: body.into(),
+    };
+
+    if dry_run::is_enabled() {
+        tracing::info!(
+            "[DRY RUN] Notification not sent | {}: {}",
+            notification.title,
+            notification.body
+        );
+        return;
+    }
+
+    notify_all(&notifiers.sinks, &notification).await;
+}
+
+// A failing sink is logged and skipped, it never stops the caller
+pub async fn notify_all(notifiers: &[Box<dyn Notifier>], notification: &Notification) {
+    for notifier in notifiers {
+        match tokio::time::timeout(NOTIFY_TIMEOUT, notifier.notify(notification)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("Failed to notify via {}: {e}", notifier.name()),
+            Err(_) => tracing::warn!("Timeout while notifying via {}", notifier.name()),
+        }
+    }
+}
+
+fn read_secret(var: &str) -> eyre::Result<String> {
+    std::env::var(var).map_err(|_| eyre::eyre!("Environment variable `{var}` is not set"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    struct Recorder(Arc<Mutex<Vec<Notification>>>);
+
+    impl Notifier for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, eyre::Result<()>> {
+            self.0.lock().unwrap().push(notification.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct Failing;
+
+    impl Notifier for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn notify<'a>(&'a self, _: &'a Notification) -> BoxFuture<'a, eyre::Result<()>> {
+            Box::pin(async { eyre::bail!("connection refused") })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_stop_the_others() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let notifiers: Vec<Box<dyn Notifier>> =
+            vec![Box::new(Failing), Box::new(Recorder(received.clone()))];
+        let notification = Notification {
+            kind: NotificationKind::WatchAlert,
+            title: "Watch alert".to_string(),
+            body: "mid price 0.62 is above 0.6".to_string(),
+        };
+
+        notify_all(&notifiers, &notification).await;
+
+        assert_eq!(*received.lock().unwrap(), [notification]);
+    }
+
+    #[test]
+    fn test_notifier_configs() {
+        let configs = toml::from_str::<toml::Table>(
+            r#"
+            NOTIFIERS = [
+                { KIND = "webhook", URL = "https://example.com/hooks/bot" },
+                { KIND = "bot", TOKEN_ENV = "NOTIFIER_TEST_BOT_TOKEN", CHAT_ID = "-100123" },
+                { KIND = "smtp", HOST = "smtp.example.com", FROM = "bot@example.com", TO = ["ops@example.com"] },
+            ]
+            "#,
+        )
+        .unwrap()
+        .remove("NOTIFIERS")
+        .unwrap()
+        .try_into::<Vec<NotifierConfig>>()
+        .unwrap();
+
+        let NotifierConfig::Bot(bot) = &configs[1] else {
+            panic!("expected a bot sink, got {:?}", configs[1]);
+        };
+        assert_eq!(bot.url, "https://api.telegram.org");
+
+        let error = configs[1].build().err().unwrap();
+        assert!(error.to_string().contains("NOTIFIER_TEST_BOT_TOKEN"));
+
+        for config in [&configs[0], &configs[2]] {
+            config.build().unwrap();
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Deserialize;
+
+use super::{read_secret, Notification, Notifier};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    // Implicit TLS, port 465 by default
+    Tls,
+    // Upgrades a plain connection, port 587 by default
+    #[default]
+    Starttls,
+    // No encryption, only meant for a relay on the local network, port 25 by default
+    None,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+// Mails every notification to a fixed list of recipients
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: &SmtpConfig) -> eyre::Result<Self> {
+        let mut builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        }
+        .timeout(Some(SMTP_TIMEOUT));
+
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+
+        if let Some(username) = &config.username {
+            let password = match &config.password_env {
+                Some(var) => read_secret(var)?,
+                None => eyre::bail!("SMTP user `{username}` needs a PASSWORD_ENV"),
+            };
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+
+        let to = config
+            .to
+            .iter()
+            .map(|address| address.parse())
+            .collect::<Result<Vec<Mailbox>, _>>()?;
+
+        if to.is_empty() {
+            eyre::bail!("SMTP sink has no recipients");
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse()?,
+            to,
+        })
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &str {
+        "smtp"
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            let mut message = Message::builder()
+                .from(self.from.clone())
+                .subject(&notification.title)
+                .header(ContentType::TEXT_PLAIN);
+
+            for to in &self.to {
+                message = message.to(to.clone());
+            }
+
+            self.transport
+                .send(message.body(notification.body.clone())?)
+                .await?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::sink_server::smtp_sink, utils::notifier::NotificationKind};
+
+    #[tokio::test]
+    async fn test_smtp_mails_every_recipient() {
+        let (port, mut messages) = smtp_sink().await;
+
+        let notifier = SmtpNotifier::new(&SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port: Some(port),
+            security: SmtpSecurity::None,
+            username: None,
+            password_env: None,
+            from: "Polymarket Bot <bot@example.com>".to_string(),
+            to: vec![
+                "ops@example.com".to_string(),
+                "alerts@example.com".to_string(),
+            ],
+        })
+        .unwrap();
+
+        notifier
+            .notify(&Notification {
+                kind: NotificationKind::AccountFailed,
+                title: "Redeem failed".to_string(),
+                body: "0x5D2d…9b1F | Transaction not mined within timeout".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let message = messages.recv().await.unwrap();
+        assert!(message.contains("Subject: Redeem failed"));
+        assert!(message.contains("To: ops@example.com, alerts@example.com"));
+        assert!(message.contains("Transaction not mined within timeout"));
+    }
+
+    #[test]
+    fn test_smtp_user_requires_a_password() {
+        let config = SmtpConfig {
+            host: "smtp.example.com".to_string(),
+            port: None,
+            security: SmtpSecurity::Starttls,
+            username: Some("bot".to_string()),
+            password_env: None,
+            from: "bot@example.com".to_string(),
+            to: vec!["ops@example.com".to_string()],
+        };
+
+        let error = SmtpNotifier::new(&config).err().unwrap();
+        assert!(error.to_string().contains("PASSWORD_ENV"));
+    }
+}
@@ -0,0 +1,93 @@
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{Notification, Notifier};
+use crate::utils::fetch::{send_http_request_with_retries, RequestParams};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+}
+
+// Posts every notification as a JSON object to a single url
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &WebhookConfig) -> Self {
+        Self {
+            url: config.url.clone(),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            let body = json!({
+                "kind": notification.kind,
+                "title": notification.title,
+                "body": notification.body,
+                "timestamp": Utc::now().to_rfc3339(),
+            });
+
+            let request_params = RequestParams {
+                url: &self.url,
+                method: Method::POST,
+                body: Some(body),
+                query_args: None,
+            };
+
+            send_http_request_with_retries::<serde_json::Value>(
+                &request_params,
+                None,
+                None,
+                Some(3),
+                None,
+                |_| true,
+            )
+            .await?;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::sink_server::http_sink, utils::notifier::NotificationKind};
+
+    #[tokio::test]
+    async fn test_webhook_posts_json() {
+        let (url, mut requests) = http_sink().await;
+        let notifier = WebhookNotifier::new(&WebhookConfig {
+            url: format!("{url}/hooks/bot"),
+        });
+
+        notifier
+            .notify(&Notification {
+                kind: NotificationKind::LowBalance,
+                title: "Low balance".to_string(),
+                body: "0x5D2d…9b1F holds 0.42 USDC.e".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let (path, body) = requests.recv().await.unwrap();
+        assert_eq!(path, "/hooks/bot");
+        assert_eq!(body["kind"], "low-balance");
+        assert_eq!(body["title"], "Low balance");
+        assert_eq!(body["body"], "0x5D2d…9b1F holds 0.42 USDC.e");
+        assert!(body["timestamp"].is_string());
+    }
+}